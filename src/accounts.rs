@@ -0,0 +1,738 @@
+use std::{collections::HashMap, hash::Hash, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use news_flash::{
+    NewsFlash,
+    error::NewsFlashError,
+    models::{
+        Article, ArticleFilter, ArticleID, ArticleOrder, Category, CategoryID, CategoryMapping,
+        Enclosure, FatArticle, Feed, FeedID, FeedMapping, PluginCapabilities, Tag, TagID, Tagging,
+        Thumbnail,
+    },
+};
+use tokio::sync::{RwLock, RwLockReadGuard};
+
+pub mod prelude {
+    pub use super::{Account, AccountId, Accounts, Qualify};
+}
+
+// separates the account name from the id in ids of the unified accounts
+const ACCOUNT_SEPARATOR: char = '\u{1f}';
+
+/// Ids of news-flash models which are qualified with the name of their account
+pub trait AccountId: Clone {
+    fn id_str(&self) -> &str;
+    fn from_id_string(id: String) -> Self;
+}
+
+macro_rules! account_id {
+    ($($id_type:ty),*) => {
+        $(
+            impl AccountId for $id_type {
+                fn id_str(&self) -> &str {
+                    self.as_str()
+                }
+
+                fn from_id_string(id: String) -> Self {
+                    <$id_type>::from_owned(id)
+                }
+            }
+
+            impl Qualify for $id_type {
+                fn qualify(self, account: &Account) -> Self {
+                    account.qualify_id(self)
+                }
+            }
+        )*
+    };
+}
+
+/// Models whose ids are qualified with the name of their account
+pub trait Qualify {
+    fn qualify(self, account: &Account) -> Self;
+}
+
+account_id!(ArticleID, FeedID, CategoryID, TagID);
+
+pub struct Account {
+    name: String,
+    // ids of the primary account are left untouched
+    primary: bool,
+    news_flash_lock: Arc<RwLock<NewsFlash>>,
+}
+
+impl Account {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub async fn news_flash(&self) -> RwLockReadGuard<'_, NewsFlash> {
+        self.news_flash_lock.read().await
+    }
+
+    pub fn qualify_id<I: AccountId>(&self, id: I) -> I {
+        if self.primary {
+            return id;
+        }
+        I::from_id_string(format!("{}{ACCOUNT_SEPARATOR}{}", self.name, id.id_str()))
+    }
+}
+
+/// The account of the active profile and the accounts of the profiles which are shown alongside
+/// it (see `unified_profiles`)
+pub struct Accounts {
+    // the primary account comes first
+    accounts: Vec<Account>,
+}
+
+impl Accounts {
+    pub fn new(
+        primary_name: &str,
+        primary_news_flash_lock: Arc<RwLock<NewsFlash>>,
+        unified_news_flashes: Vec<(String, NewsFlash)>,
+    ) -> Self {
+        let primary = Account {
+            name: primary_name.to_owned(),
+            primary: true,
+            news_flash_lock: primary_news_flash_lock,
+        };
+
+        let unified = unified_news_flashes
+            .into_iter()
+            .map(|(name, news_flash)| Account {
+                name,
+                primary: false,
+                news_flash_lock: Arc::new(RwLock::new(news_flash)),
+            });
+
+        Self {
+            accounts: std::iter::once(primary).chain(unified).collect(),
+        }
+    }
+
+    pub fn primary(&self) -> &Account {
+        &self.accounts[0]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.iter()
+    }
+
+    /// Returns true if the accounts of other profiles are shown alongside the primary one
+    pub fn is_unified(&self) -> bool {
+        self.accounts.len() > 1
+    }
+
+    /// Returns the account owning the given id
+    pub fn account_of<I: AccountId>(&self, id: &I) -> &Account {
+        id.id_str()
+            .split_once(ACCOUNT_SEPARATOR)
+            .and_then(|(name, _)| {
+                self.accounts
+                    .iter()
+                    .find(|account| !account.primary && account.name == name)
+            })
+            .unwrap_or(self.primary())
+    }
+
+    pub fn is_owner<I: AccountId>(&self, account: &Account, id: &I) -> bool {
+        std::ptr::eq(self.account_of(id), account)
+    }
+
+    /// Returns the account owning the given id and the id as known to the account
+    pub fn owner<I: AccountId>(&self, id: &I) -> (&Account, I) {
+        let account = self.account_of(id);
+        if account.primary {
+            return (account, id.clone());
+        }
+
+        let local_id = id
+            .id_str()
+            .split_once(ACCOUNT_SEPARATOR)
+            .map(|(_, local_id)| I::from_id_string(local_id.to_owned()))
+            .unwrap_or_else(|| id.clone());
+        (account, local_id)
+    }
+
+    /// Groups the ids by the accounts owning them
+    pub fn group<I: AccountId>(&self, ids: &[I]) -> Vec<(&Account, Vec<I>)> {
+        self.accounts
+            .iter()
+            .filter_map(|account| {
+                let local_ids = ids
+                    .iter()
+                    .filter(|id| self.is_owner(account, *id))
+                    .map(|id| self.owner(id).1)
+                    .collect::<Vec<I>>();
+                (!local_ids.is_empty()).then_some((account, local_ids))
+            })
+            .collect()
+    }
+
+    fn local_ids<I: AccountId>(&self, account: &Account, ids: &Option<Vec<I>>) -> Option<Vec<I>> {
+        ids.as_ref().map(|ids| {
+            ids.iter()
+                .filter(|id| self.is_owner(account, *id))
+                .map(|id| self.owner(id).1)
+                .collect()
+        })
+    }
+
+    /// Splits the filter into filters for the accounts; accounts without any of the required
+    /// feeds, categories, tags or articles are left out
+    pub fn split_filter(&self, filter: &ArticleFilter) -> Vec<(&Account, ArticleFilter)> {
+        fn excludes<I>(ids: &Option<Vec<I>>, local_ids: &Option<Vec<I>>) -> bool {
+            matches!((ids, local_ids), (Some(ids), Some(local_ids)) if !ids.is_empty() && local_ids.is_empty())
+        }
+
+        self.accounts
+            .iter()
+            .filter_map(|account| {
+                let account_filter = ArticleFilter {
+                    feeds: self.local_ids(account, &filter.feeds),
+                    feed_blacklist: self.local_ids(account, &filter.feed_blacklist),
+                    categories: self.local_ids(account, &filter.categories),
+                    category_blacklist: self.local_ids(account, &filter.category_blacklist),
+                    tags: self.local_ids(account, &filter.tags),
+                    ids: self.local_ids(account, &filter.ids),
+                    ..filter.clone()
+                };
+
+                let excluded = excludes(&filter.feeds, &account_filter.feeds)
+                    || excludes(&filter.categories, &account_filter.categories)
+                    || excludes(&filter.tags, &account_filter.tags)
+                    || excludes(&filter.ids, &account_filter.ids);
+
+                (!excluded).then_some((account, account_filter))
+            })
+            .collect()
+    }
+
+    // runs the query on all accounts and qualifies the results
+    async fn query<R: Qualify>(
+        &self,
+        query: impl Fn(&NewsFlash) -> Result<R, NewsFlashError>,
+    ) -> color_eyre::Result<Vec<R>> {
+        let mut results = Vec::new();
+        for account in self.accounts.iter() {
+            let news_flash = account.news_flash().await;
+            results.push(query(&news_flash)?.qualify(account));
+        }
+        Ok(results)
+    }
+
+    pub async fn get_feeds(&self) -> color_eyre::Result<(Vec<Feed>, Vec<FeedMapping>)> {
+        Ok(concat_pairs(
+            self.query(|news_flash| news_flash.get_feeds()).await?,
+        ))
+    }
+
+    pub async fn get_categories(
+        &self,
+    ) -> color_eyre::Result<(Vec<Category>, Vec<CategoryMapping>)> {
+        Ok(concat_pairs(
+            self.query(|news_flash| news_flash.get_categories()).await?,
+        ))
+    }
+
+    pub async fn get_tags(&self) -> color_eyre::Result<(Vec<Tag>, Vec<Tagging>)> {
+        Ok(concat_pairs(
+            self.query(|news_flash| news_flash.get_tags()).await?,
+        ))
+    }
+
+    /// Returns the articles of all accounts, articles of several accounts are sorted by date
+    pub async fn get_articles(&self, filter: ArticleFilter) -> color_eyre::Result<Vec<Article>> {
+        let mut articles = Vec::new();
+        for (account, account_filter) in self.split_filter(&filter) {
+            let news_flash = account.news_flash().await;
+            articles.extend(news_flash.get_articles(account_filter)?.qualify(account));
+        }
+
+        if self.is_unified() {
+            merge_by_date(&mut articles, &filter);
+        }
+
+        Ok(articles)
+    }
+
+    pub async fn get_article(&self, article_id: &ArticleID) -> color_eyre::Result<Article> {
+        let (account, article_id) = self.owner(article_id);
+        let news_flash = account.news_flash().await;
+        Ok(news_flash.get_article(&article_id)?.qualify(account))
+    }
+
+    pub async fn get_enclosures(
+        &self,
+        article_id: &ArticleID,
+    ) -> color_eyre::Result<Vec<Enclosure>> {
+        let (account, article_id) = self.owner(article_id);
+        let news_flash = account.news_flash().await;
+        Ok(news_flash.get_enclosures(&article_id)?.qualify(account))
+    }
+
+    pub async fn unread_count_all(&self) -> color_eyre::Result<i64> {
+        let mut unread_count = 0;
+        for account in self.accounts.iter() {
+            unread_count += account.news_flash().await.unread_count_all()?;
+        }
+        Ok(unread_count)
+    }
+
+    pub async fn unread_count_feed_map(
+        &self,
+        exclude_future: bool,
+    ) -> color_eyre::Result<HashMap<FeedID, i64>> {
+        Ok(self
+            .query(|news_flash| news_flash.unread_count_feed_map(exclude_future))
+            .await?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    pub async fn marked_count_feed_map(&self) -> color_eyre::Result<HashMap<FeedID, i64>> {
+        Ok(self
+            .query(|news_flash| news_flash.marked_count_feed_map())
+            .await?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    /// Returns the capabilities of the provider of the account owning the given id
+    pub async fn features_of<I: AccountId>(
+        &self,
+        id: &I,
+    ) -> color_eyre::Result<PluginCapabilities> {
+        Ok(self.account_of(id).news_flash().await.features().await?)
+    }
+
+    /// Returns the capabilities which the provider of any account supports
+    pub async fn any_features(&self) -> color_eyre::Result<PluginCapabilities> {
+        let mut features = PluginCapabilities::empty();
+        for account in self.accounts.iter() {
+            features |= account.news_flash().await.features().await?;
+        }
+        Ok(features)
+    }
+
+    /// Returns the most recent sync of all accounts
+    pub async fn last_sync(&self) -> DateTime<Utc> {
+        let mut last_sync = DateTime::<Utc>::default();
+        for account in self.accounts.iter() {
+            last_sync = last_sync.max(account.news_flash().await.last_sync().await);
+        }
+        last_sync
+    }
+
+    /// Returns the id of the tag in the given account: this is either the tag itself or the tag
+    /// with the same label
+    pub async fn tag_in_account(
+        &self,
+        tag_id: &TagID,
+        account: &Account,
+    ) -> color_eyre::Result<Option<TagID>> {
+        let (owner, local_tag_id) = self.owner(tag_id);
+        if std::ptr::eq(owner, account) {
+            return Ok(Some(local_tag_id));
+        }
+
+        let (owner_tags, _) = owner.news_flash().await.get_tags()?;
+        let Some(label) = owner_tags
+            .into_iter()
+            .find(|tag| tag.tag_id == local_tag_id)
+            .map(|tag| tag.label)
+        else {
+            return Ok(None);
+        };
+
+        let (tags, _) = account.news_flash().await.get_tags()?;
+        Ok(tags
+            .into_iter()
+            .find(|tag| tag.label == label)
+            .map(|tag| tag.tag_id))
+    }
+}
+
+// orders the articles of several accounts by date and applies the limit of the filter to all of
+// them
+fn merge_by_date(articles: &mut Vec<Article>, filter: &ArticleFilter) {
+    articles.sort_by_key(|article| std::cmp::Reverse(article.date));
+    if matches!(filter.order, Some(ArticleOrder::OldestFirst)) {
+        articles.reverse();
+    }
+    if let Some(limit) = filter.limit {
+        articles.truncate(limit.max(0) as usize);
+    }
+}
+
+fn concat_pairs<A, B>(pairs: Vec<(Vec<A>, Vec<B>)>) -> (Vec<A>, Vec<B>) {
+    pairs.into_iter().fold(
+        (Vec::new(), Vec::new()),
+        |(mut all_a, mut all_b), (a, b)| {
+            all_a.extend(a);
+            all_b.extend(b);
+            (all_a, all_b)
+        },
+    )
+}
+
+impl<T: Qualify> Qualify for Vec<T> {
+    fn qualify(self, account: &Account) -> Self {
+        if account.primary {
+            return self;
+        }
+        self.into_iter().map(|item| item.qualify(account)).collect()
+    }
+}
+
+impl<T: Qualify> Qualify for Option<T> {
+    fn qualify(self, account: &Account) -> Self {
+        self.map(|item| item.qualify(account))
+    }
+}
+
+impl<A: Qualify, B: Qualify> Qualify for (A, B) {
+    fn qualify(self, account: &Account) -> Self {
+        (self.0.qualify(account), self.1.qualify(account))
+    }
+}
+
+impl<K: Qualify + Eq + Hash, V> Qualify for HashMap<K, V> {
+    fn qualify(self, account: &Account) -> Self {
+        if account.primary {
+            return self;
+        }
+        self.into_iter()
+            .map(|(key, value)| (key.qualify(account), value))
+            .collect()
+    }
+}
+
+impl Qualify for Feed {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            feed_id: self.feed_id.qualify(account),
+            ..self
+        }
+    }
+}
+
+impl Qualify for FeedMapping {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            feed_id: self.feed_id.qualify(account),
+            category_id: self.category_id.qualify(account),
+            ..self
+        }
+    }
+}
+
+impl Qualify for Category {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            category_id: self.category_id.qualify(account),
+            ..self
+        }
+    }
+}
+
+impl Qualify for CategoryMapping {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            parent_id: self.parent_id.qualify(account),
+            category_id: self.category_id.qualify(account),
+            ..self
+        }
+    }
+}
+
+impl Qualify for Tag {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            tag_id: self.tag_id.qualify(account),
+            ..self
+        }
+    }
+}
+
+impl Qualify for Tagging {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            article_id: self.article_id.qualify(account),
+            tag_id: self.tag_id.qualify(account),
+        }
+    }
+}
+
+impl Qualify for Article {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            article_id: self.article_id.qualify(account),
+            feed_id: self.feed_id.qualify(account),
+            ..self
+        }
+    }
+}
+
+impl Qualify for FatArticle {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            article_id: self.article_id.qualify(account),
+            feed_id: self.feed_id.qualify(account),
+            ..self
+        }
+    }
+}
+
+impl Qualify for Thumbnail {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            article_id: self.article_id.qualify(account),
+            ..self
+        }
+    }
+}
+
+impl Qualify for Enclosure {
+    fn qualify(self, account: &Account) -> Self {
+        Self {
+            article_id: self.article_id.qualify(account),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use chrono::TimeZone;
+    use news_flash::models::{Marked, PluginID, Read};
+    use reqwest::Client;
+
+    use super::*;
+
+    fn news_flash(name: &str) -> (NewsFlash, PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("eilmeldung-accounts-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let news_flash = NewsFlash::builder()
+            .config_dir(dir.join("config"))
+            .data_dir(dir.join("data"))
+            .plugin(PluginID::new("local_rss"))
+            .create()
+            .unwrap();
+        (news_flash, dir)
+    }
+
+    // the primary account "home" and the unified account "work"
+    fn accounts(name: &str) -> (Accounts, Vec<PathBuf>) {
+        let (home, home_dir) = news_flash(&format!("{name}-home"));
+        let (work, work_dir) = news_flash(&format!("{name}-work"));
+        (
+            Accounts::new(
+                "home",
+                Arc::new(RwLock::new(home)),
+                vec![("work".to_owned(), work)],
+            ),
+            vec![home_dir, work_dir],
+        )
+    }
+
+    fn remove(dirs: Vec<PathBuf>) {
+        for dir in dirs {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    fn work(accounts: &Accounts) -> &Account {
+        accounts.iter().nth(1).unwrap()
+    }
+
+    fn article(id: &str, day: u32) -> Article {
+        let date = Utc.with_ymd_and_hms(2026, 10, day, 12, 0, 0).unwrap();
+        Article {
+            article_id: ArticleID::new(id),
+            title: None,
+            author: None,
+            feed_id: FeedID::new("feed"),
+            url: None,
+            date,
+            synced: date,
+            summary: None,
+            direction: None,
+            unread: Read::Unread,
+            marked: Marked::Unmarked,
+            thumbnail_url: None,
+            updated: None,
+        }
+    }
+
+    #[test]
+    fn test_qualify_and_owner() {
+        let (accounts, dirs) = accounts("owner");
+        let work = work(&accounts);
+
+        // ids of the primary account are not qualified
+        let home_id = FeedID::new("feed");
+        assert_eq!(home_id.clone().qualify(accounts.primary()), home_id);
+        let (owner, local_id) = accounts.owner(&home_id);
+        assert_eq!(owner.name(), "home");
+        assert_eq!(local_id, home_id);
+
+        let work_id = FeedID::new("feed").qualify(work);
+        assert_eq!(work_id.as_str(), "work\u{1f}feed");
+        let (owner, local_id) = accounts.owner(&work_id);
+        assert_eq!(owner.name(), "work");
+        assert_eq!(local_id, FeedID::new("feed"));
+
+        // ids of unknown accounts belong to the primary account
+        let unknown_id = FeedID::new("unknown\u{1f}feed");
+        let (owner, local_id) = accounts.owner(&unknown_id);
+        assert_eq!(owner.name(), "home");
+        assert_eq!(local_id, unknown_id);
+
+        remove(dirs);
+    }
+
+    #[test]
+    fn test_group() {
+        let (accounts, dirs) = accounts("group");
+        let work = work(&accounts);
+
+        let ids = [
+            ArticleID::new("a1"),
+            ArticleID::new("b1").qualify(work),
+            ArticleID::new("a2"),
+        ];
+        let groups = accounts
+            .group(&ids)
+            .into_iter()
+            .map(|(account, ids)| (account.name().to_owned(), ids))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            groups,
+            [
+                (
+                    "home".to_owned(),
+                    vec![ArticleID::new("a1"), ArticleID::new("a2")]
+                ),
+                ("work".to_owned(), vec![ArticleID::new("b1")]),
+            ]
+        );
+
+        remove(dirs);
+    }
+
+    #[test]
+    fn test_split_filter() {
+        let (accounts, dirs) = accounts("split");
+        let work = work(&accounts);
+
+        // without restrictions all accounts are queried
+        let filters = accounts.split_filter(&ArticleFilter::default());
+        assert_eq!(filters.len(), 2);
+
+        // accounts without any of the feeds are left out
+        let filter = ArticleFilter {
+            feeds: Some(vec![FeedID::new("feed").qualify(work)]),
+            feed_blacklist: Some(vec![FeedID::new("blocked")]),
+            ..Default::default()
+        };
+        let filters = accounts.split_filter(&filter);
+        assert_eq!(filters.len(), 1);
+        let (account, filter) = &filters[0];
+        assert_eq!(account.name(), "work");
+        assert_eq!(filter.feeds, Some(vec![FeedID::new("feed")]));
+        // a blacklist does not exclude an account
+        assert_eq!(filter.feed_blacklist, Some(vec![]));
+
+        remove(dirs);
+    }
+
+    #[tokio::test]
+    async fn test_tag_in_account() {
+        let (accounts, dirs) = accounts("tag");
+        let work = work(&accounts);
+        let client = Client::new();
+
+        let home_tag = accounts
+            .primary()
+            .news_flash()
+            .await
+            .add_tag("rust", None, &client)
+            .await
+            .unwrap();
+        let work_tag = work
+            .news_flash()
+            .await
+            .add_tag("rust", None, &client)
+            .await
+            .unwrap();
+        let work_only_tag = work
+            .news_flash()
+            .await
+            .add_tag("work only", None, &client)
+            .await
+            .unwrap();
+
+        let qualified_work_tag = work_tag.tag_id.clone().qualify(work);
+        assert_eq!(
+            accounts
+                .tag_in_account(&qualified_work_tag, work)
+                .await
+                .unwrap(),
+            Some(work_tag.tag_id)
+        );
+        // tags of other accounts are found by their label
+        assert_eq!(
+            accounts
+                .tag_in_account(&qualified_work_tag, accounts.primary())
+                .await
+                .unwrap(),
+            Some(home_tag.tag_id)
+        );
+        assert_eq!(
+            accounts
+                .tag_in_account(&work_only_tag.tag_id.qualify(work), accounts.primary())
+                .await
+                .unwrap(),
+            None
+        );
+
+        remove(dirs);
+    }
+
+    #[test]
+    fn test_merge_by_date() {
+        let mut articles = vec![article("a", 1), article("b", 3), article("c", 2)];
+        merge_by_date(&mut articles, &ArticleFilter::default());
+        assert_eq!(
+            articles
+                .iter()
+                .map(|article| article.article_id.as_str())
+                .collect::<Vec<_>>(),
+            ["b", "c", "a"]
+        );
+
+        let mut articles = vec![article("a", 1), article("b", 3), article("c", 2)];
+        merge_by_date(
+            &mut articles,
+            &ArticleFilter {
+                order: Some(ArticleOrder::OldestFirst),
+                limit: Some(2),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            articles
+                .iter()
+                .map(|article| article.article_id.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "c"]
+        );
+    }
+}
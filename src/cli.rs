@@ -1,10 +1,13 @@
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use crate::prelude::*;
 use clap::{Args, Parser};
 use getset::Getters;
 use log::LevelFilter;
-use news_flash::NewsFlash;
+use news_flash::{NewsFlash, models::ArticleFilter};
 use reqwest::Client;
 
 #[derive(Parser, Debug, Getters)]
@@ -31,6 +34,10 @@ pub struct CliArgs {
     #[arg(long)]
     news_flash_state_dir: Option<String>,
 
+    /// Profile to use (as defined in `[profiles.<name>]` of config.toml)
+    #[arg(short, long)]
+    profile: Option<String>,
+
     /// Show secrets when printing login data
     #[arg(long)]
     show_secrets: bool,
@@ -38,6 +45,10 @@ pub struct CliArgs {
     #[command(flatten)]
     action: CliAction,
 
+    /// Print the stats as JSON (with `--stats`)
+    #[arg(long, requires = "stats")]
+    json: bool,
+
     #[arg(long)]
     quiet: bool,
 }
@@ -53,10 +64,14 @@ struct CliAction {
     #[arg(long)]
     sync: bool,
 
-    /// Print current stats (unread, read, marked articles per feed) and then exit
+    /// Print current stats (unread, read, marked articles) and reading stats and then exit
     #[arg(long)]
     stats: bool,
 
+    /// Check all feeds for errors and redirects, print a health report and then exit
+    #[arg(long)]
+    health: bool,
+
     /// Export to OPML file (if provider supports OPML export)
     #[arg(long)]
     export_opml: Option<PathBuf>,
@@ -93,20 +108,119 @@ async fn sync(
     cli_args: &CliArgs,
     news_flash: &NewsFlash,
     client: &Client,
+    state_dir: &Path,
 ) -> color_eyre::Result<bool> {
     if !cli_args.action().sync {
         return Ok(false);
     }
     let new_articles = news_flash.sync(client, Default::default()).await?;
 
+    let (feeds, _) = news_flash.get_feeds()?;
+    FeedHealthRecords::load(state_dir).update_from_sync(&feeds)?;
+
     if !*cli_args.quiet() {
         println!(
             "{}",
-            config
-                .cli_sync_stats_format
-                .gen_output(news_flash, &new_articles)?
+            config.cli_sync_stats_format.gen_output(
+                news_flash.get_feeds()?,
+                news_flash.get_categories()?,
+                &new_articles
+            )
+        );
+    }
+
+    Ok(true)
+}
+
+async fn health(
+    config: &Config,
+    cli_args: &CliArgs,
+    news_flash: &NewsFlash,
+    state_dir: &Path,
+) -> color_eyre::Result<bool> {
+    if !cli_args.action().health {
+        return Ok(false);
+    }
+
+    let (feeds, _) = news_flash.get_feeds()?;
+    let mut feed_health = FeedHealthRecords::load(state_dir);
+
+    // redirects are not followed so that moved feeds are detected
+    let client = client_builder(
+        Duration::from_secs(config.network_timeout_seconds),
+        &config.network,
+    )?
+    .redirect(reqwest::redirect::Policy::none())
+    .build()?;
+    feed_health.check(&feeds, &client).await?;
+
+    let report = feed_health.report(&feeds);
+    let mut output =
+        String::from("|:-|:-|:-|:-|\n|**Feed**|**Status**|**Last Success**|**Details**|\n|-\n");
+    for (feed, health) in report.iter() {
+        let details = health.details().join("; ");
+
+        output.push_str(
+            &format!(
+                "|{}|{}|{}|{details}|\n",
+                feed.label.replace('|', "/"),
+                health.status(),
+                health.last_success_text(&config.date_format)
+            )
+            .replace('*', "\\*"),
         );
     }
+    output.push_str("|-\n");
+
+    if !cli_args.quiet() {
+        termimad::print_text(&output);
+    }
+
+    Ok(true)
+}
+
+async fn stats(
+    cli_args: &CliArgs,
+    news_flash: &NewsFlash,
+    state_dir: &Path,
+) -> color_eyre::Result<bool> {
+    if !cli_args.action().stats {
+        return Ok(false);
+    }
+
+    let (feeds, feed_mappings) = news_flash.get_feeds()?;
+    let (categories, _) = news_flash.get_categories()?;
+    let (tags, _) = news_flash.get_tags()?;
+    let articles = news_flash.get_articles(ArticleFilter::default())?;
+
+    let stats = ReadingStats::compute(
+        &ReadingEvents::new(state_dir).load(),
+        &articles,
+        &feeds,
+        &feed_mappings,
+        &categories,
+        &tags,
+    );
+
+    if *cli_args.quiet() {
+        return Ok(true);
+    }
+
+    if *cli_args.json() {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(true);
+    }
+
+    let escape = |s: &str| s.replace('|', "/").replace('*', "\\*");
+    let mut output = String::new();
+    for (title, rows) in stats.sections() {
+        output.push_str(&format!("\n**{title}**\n|:-|:-|\n"));
+        for (label, value) in rows {
+            output.push_str(&format!("|{}|{}|\n", escape(&label), escape(&value)));
+        }
+        output.push_str("|-\n");
+    }
+    termimad::print_text(&output);
 
     Ok(true)
 }
@@ -177,6 +291,7 @@ pub async fn execute_cli_actions(
     cli_args: &CliArgs,
     news_flash: &NewsFlash,
     client: &Client,
+    state_dir: &Path,
 ) -> color_eyre::Result<bool> {
     // print login data
     if print_login_data(cli_args, news_flash).await? {
@@ -184,7 +299,17 @@ pub async fn execute_cli_actions(
     }
 
     // sync
-    if sync(config, cli_args, news_flash, client).await? {
+    if sync(config, cli_args, news_flash, client, state_dir).await? {
+        return Ok(true);
+    }
+
+    // feed health
+    if health(config, cli_args, news_flash, state_dir).await? {
+        return Ok(true);
+    }
+
+    // stats
+    if stats(cli_args, news_flash, state_dir).await? {
         return Ok(true);
     }
 
@@ -0,0 +1,79 @@
+use std::io::Write;
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use log::{debug, info};
+
+pub mod prelude {
+    pub use super::{ClipboardBackend, copy_to_clipboard};
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardBackend {
+    // OSC 52 in SSH sessions or if the system clipboard is not available, system clipboard
+    // otherwise
+    #[default]
+    Auto,
+    System,
+    Osc52,
+}
+
+pub fn copy_to_clipboard(text: &str, backend: ClipboardBackend) -> color_eyre::Result<()> {
+    use ClipboardBackend as B;
+    match backend {
+        B::System => copy_to_system_clipboard(text),
+        B::Osc52 => copy_with_osc52(text),
+        B::Auto if is_remote_session() => copy_with_osc52(text),
+        B::Auto => copy_to_system_clipboard(text).or_else(|err| {
+            debug!("system clipboard not available ({err}), using OSC 52");
+            copy_with_osc52(text)
+        }),
+    }
+}
+
+fn is_remote_session() -> bool {
+    ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+        .iter()
+        .any(|variable| std::env::var_os(variable).is_some())
+}
+
+fn copy_to_system_clipboard(text: &str) -> color_eyre::Result<()> {
+    arboard::Clipboard::new()?.set_text(text)?;
+    Ok(())
+}
+
+// the terminal sets the clipboard when it receives the escape sequence, which also works over SSH
+fn copy_with_osc52(text: &str) -> color_eyre::Result<()> {
+    info!("copying to clipboard via OSC 52");
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(osc52_sequence(text, std::env::var_os("TMUX").is_some()).as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+fn osc52_sequence(text: &str, in_tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text));
+    if in_tmux {
+        // tmux passes the sequence on to the outer terminal (requires `allow-passthrough`)
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(
+            osc52_sequence("eilmeldung", false),
+            "\x1b]52;c;ZWlsbWVsZHVuZw==\x07"
+        );
+        assert_eq!(
+            osc52_sequence("eilmeldung", true),
+            "\x1bPtmux;\x1b\x1b]52;c;ZWlsbWVsZHVuZw==\x07\x1b\\"
+        );
+    }
+}
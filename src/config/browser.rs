@@ -0,0 +1,124 @@
+use crate::prelude::*;
+use std::process::{Command, Stdio};
+
+use indexmap::IndexMap;
+use log::info;
+use regex::Regex;
+use serde::Deserialize;
+
+// command for the URLs matching the pattern, e.g., videos on YouTube are opened with mpv
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UrlHandler {
+    #[serde(deserialize_with = "deserialize_pattern")]
+    pub pattern: Regex,
+    pub command: String,
+}
+
+fn deserialize_pattern<'de, D>(deserializer: D) -> Result<Regex, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(|err| {
+        serde::de::Error::custom(ConfigError::UrlHandlerParseError(err.to_string()).to_string())
+    })
+}
+
+// opens the URLs of the items with the first matching URL handler, the browser command or the
+// default browser of the system; commands with `{urls}` are called once with all their URLs
+pub fn open_in_browser(config: &Config, items: &[ShareItem]) -> color_eyre::Result<()> {
+    let mut items_for_command: IndexMap<Option<&str>, Vec<&ShareItem>> = IndexMap::new();
+    for item in items {
+        let command = config
+            .url_handlers
+            .iter()
+            .find(|handler| handler.pattern.is_match(item.url.as_str()))
+            .map(|handler| handler.command.as_str())
+            .or(config.browser_command.as_deref());
+        items_for_command.entry(command).or_default().push(item);
+    }
+
+    for (command, items) in items_for_command {
+        match command {
+            None => {
+                for item in items {
+                    webbrowser::open(item.url.as_str())?;
+                }
+            }
+            Some(command) if command.contains("{urls}") => execute(command, &items)?,
+            Some(command) => {
+                for item in items {
+                    execute(command, &[item])?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn execute(command_line: &str, items: &[&ShareItem]) -> color_eyre::Result<()> {
+    let Some(first_item) = items.first() else {
+        return Ok(());
+    };
+    let (cmd, args) = prepare_command(command_line)?;
+
+    // `{urls}` as an argument is replaced by all URLs, the other placeholders by the values of the
+    // first item
+    let args = args
+        .iter()
+        .flat_map(|arg| match arg.as_str() {
+            "{urls}" => items.iter().map(|item| item.url.to_string()).collect(),
+            _ => vec![first_item.fill_in(arg, str::to_owned)],
+        })
+        .collect::<Vec<String>>();
+
+    info!("opening with {cmd} {args:?}");
+    Command::new(&cmd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .args(args)
+        .spawn()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_url_handlers() {
+        #[derive(serde::Deserialize)]
+        struct Handlers {
+            url_handlers: Vec<UrlHandler>,
+        }
+
+        let handlers: Handlers = toml::from_str(
+            r#"
+            url_handlers = [
+              { pattern = 'youtube\.com|youtu\.be', command = "mpv {url}" },
+              { pattern = '\.pdf$', command = "zathura {url}" },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert!(
+            handlers.url_handlers[0]
+                .pattern
+                .is_match("https://www.youtube.com/watch?v=1")
+        );
+        assert!(
+            handlers.url_handlers[1]
+                .pattern
+                .is_match("https://example.com/paper.pdf")
+        );
+        assert!(
+            toml::from_str::<Handlers>(r#"url_handlers = [{ pattern = "(", command = "x" }]"#)
+                .is_err()
+        );
+    }
+}
@@ -19,6 +19,9 @@ pub struct IconSet {
     enclosure_audio: Option<char>,
     enclosure_image: Option<char>,
     flagged: Option<char>,
+    downloaded: Option<char>,
+    group_expanded: Option<char>,
+    group_collapsed: Option<char>,
     command_line_prompt: Option<char>,
     image: Option<char>,
     url: Option<char>,
@@ -29,8 +32,10 @@ pub struct IconSet {
     feeds: Option<char>,
     last_synced: Option<char>,
     feed: Option<char>,
+    feed_error: Option<char>,
     category: Option<char>,
     categories: Option<char>,
+    account: Option<char>,
     tags: Option<char>,
     query: Option<char>,
     // Sorting and Filtering
@@ -86,6 +91,9 @@ impl IconSet {
     icon_property!(enclosure_audio, '', 'A');
     icon_property!(enclosure_image, '', 'I');
     icon_property!(flagged, '', '*');
+    icon_property!(downloaded, '', 'D');
+    icon_property!(group_expanded, '', 'v');
+    icon_property!(group_collapsed, '', '>');
     icon_property!(image, '', 'I');
     icon_property!(url, '', 'L');
     icon_property!(big_icon_left, '', ' ');
@@ -94,8 +102,10 @@ impl IconSet {
     icon_property!(feeds, '󱀂', 'A');
     icon_property!(last_synced, '', 'S');
     icon_property!(feed, '', 'F');
+    icon_property!(feed_error, '', '!');
     icon_property!(category, '󰉋', 'C');
     icon_property!(categories, '󰉓', 'C');
+    icon_property!(account, '', '@');
     icon_property!(tags, '󰓻', 'T');
     icon_property!(query, '', 'Q');
     icon_property!(piped, '󰟥', '|');
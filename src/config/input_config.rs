@@ -119,7 +119,7 @@ fn generate_default_input_commands() -> IndexMap<KeySequence, CommandSequence> {
         "c d"       => "confirm remove",
         "c x"       => "confirm removeall",
         "c f"       => "cmd feedadd",
-        "c e"       => "confirm feedadd https://github.com/christo-auer/eilmeldung/releases.atom eilmeldung releases",
+        "c e"       => "feedadd https://github.com/christo-auer/eilmeldung/releases.atom eilmeldung releases",
         "c a"       => "cmd categoryadd",
         "c u"       => "cmd feedchangeurl",
         "c y"       => "yank",
@@ -127,10 +127,14 @@ fn generate_default_input_commands() -> IndexMap<KeySequence, CommandSequence> {
         "c P"       => "paste before",
         "c c"       => "cmd tagchangecolor",
         "c s"       => "confirm sortfeeds",
+        "c i"       => "feedinfo",
         "S"         => "cmd share",
         "e"         => "openenclosure",
         "E"         => "cmd openenclosure",
         "?"         => "helpinput",
+        "g t"       => "toc",
+        "] ]"       => "nextheading",
+        "[ ["       => "prevheading",
 
     ]
 }
@@ -1,12 +1,16 @@
 mod border_theme;
+mod browser;
 mod dimension;
 mod feed_list_content_identfier;
 mod icon_set;
 mod input_config;
 mod login_configuration;
+mod network;
 mod paths;
+mod profile;
 mod share_target;
 mod sync_stats;
+mod sync_window;
 mod theme;
 
 use std::{
@@ -15,9 +19,11 @@ use std::{
 };
 
 use crate::prelude::*;
+use indexmap::IndexMap;
 
 pub mod prelude {
     pub use super::border_theme::BorderTheme;
+    pub use super::browser::{UrlHandler, open_in_browser};
     pub use super::dimension::Dimension;
     pub use super::feed_list_content_identfier::{
         FeedListContentIdentifier, FeedListItemType, LabeledQuery,
@@ -25,12 +31,17 @@ pub mod prelude {
     pub use super::icon_set::IconSet;
     pub use super::input_config::InputConfig;
     pub use super::login_configuration::LoginConfiguration;
+    pub use super::network::NetworkConfig;
     pub use super::paths::{CONFIG_FILE, PROJECT_DIRS};
+    pub use super::profile::{DEFAULT_PROFILE, Profile};
     pub use super::resolve_eilmeldung_config_dir;
-    pub use super::share_target::ShareTarget;
+    pub use super::share_target::{ShareItem, ShareMode, ShareTarget, Webhook};
     pub use super::sync_stats::SyncStatsOutputFormat;
+    pub use super::sync_window::SyncWindow;
     pub use super::theme::Theme;
-    pub use super::{ArticleContentType, ArticleScope, Config, ConfigError, load_config};
+    pub use super::{
+        ArticleContentType, ArticleScope, Config, ConfigError, GroupBy, ListLayout, load_config,
+    };
 }
 
 use config::FileFormat;
@@ -65,6 +76,12 @@ pub enum ConfigError {
     SecretCommandExecutionError(String),
     #[error("invalid login configuration: {0}")]
     LoginConfigurationInvalid(String),
+    #[error("unknown profile: {0}")]
+    UnknownProfile(String),
+    #[error("sync window could not be parsed: {0}")]
+    SyncWindowParseError(String),
+    #[error("invalid URL handler pattern: {0}")]
+    UrlHandlerParseError(String),
 }
 
 #[derive(Debug, Clone, serde::Deserialize, Eq, PartialEq)]
@@ -134,6 +151,91 @@ impl ArticleScope {
     }
 }
 
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    serde::Deserialize,
+    Default,
+    strum::EnumIter,
+    strum::EnumString,
+    strum::EnumMessage,
+    strum::AsRefStr,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    #[default]
+    #[strum(serialize = "none", message = "none", detailed_message = "no groups")]
+    None,
+    #[strum(
+        serialize = "day",
+        message = "day",
+        detailed_message = "day of the article"
+    )]
+    Day,
+    #[strum(
+        serialize = "week",
+        message = "week",
+        detailed_message = "week of the article"
+    )]
+    Week,
+    #[strum(serialize = "feed", message = "feed", detailed_message = "feed")]
+    Feed,
+    #[strum(
+        serialize = "category",
+        message = "category",
+        detailed_message = "category of the feed"
+    )]
+    Category,
+    #[strum(
+        serialize = "tag",
+        message = "tag",
+        detailed_message = "first tag of the article"
+    )]
+    Tag,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Debug,
+    serde::Deserialize,
+    Default,
+    strum::EnumIter,
+    strum::EnumString,
+    strum::EnumMessage,
+    strum::AsRefStr,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ListLayout {
+    #[default]
+    #[strum(
+        serialize = "table",
+        message = "table",
+        detailed_message = "one line per article with the columns of `article_table`"
+    )]
+    Table,
+    #[strum(
+        serialize = "cards",
+        message = "cards",
+        detailed_message = "several lines per article as defined by `article_card`"
+    )]
+    Cards,
+}
+
+impl ListLayout {
+    pub fn toggle(self) -> Self {
+        match self {
+            ListLayout::Table => ListLayout::Cards,
+            ListLayout::Cards => ListLayout::Table,
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Deserialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct Config {
@@ -143,11 +245,14 @@ pub struct Config {
     pub border_theme: BorderTheme,
     pub refresh_fps: u64,
     pub network_timeout_seconds: u64,
+    pub network: NetworkConfig,
     pub keep_articles_days: u16,
 
     pub startup_commands: Vec<Command>,
 
     pub sync_every_minutes: Option<u64>,
+    pub sync_windows: Vec<SyncWindow>,
+    pub metered_check_cmd: Option<String>,
 
     pub after_sync_commands: Vec<Command>,
 
@@ -162,16 +267,25 @@ pub struct Config {
     pub feed_label: String,
     pub category_label: String,
     pub categories_label: String,
+    pub account_label: String,
     pub tags_label: String,
     pub tag_label: String,
     pub query_label: String,
     pub article_table: String,
+    pub article_list_layout: ListLayout,
+    pub article_card: Vec<String>,
+    pub article_card_thumbnails: bool,
     pub date_format: String,
     pub article_scope: ArticleScope,
     pub feed_list_scope: ArticleScope,
+    pub collapse_duplicates: bool,
+    pub group_by: GroupBy,
+    pub duplicate_titles: bool,
+    pub mark_duplicates_read: bool,
 
     pub article_list_show_position: bool,
     pub content_show_position: bool,
+    pub content_read_at_end: bool,
 
     pub shadows: bool,
     pub articles_after_selection: usize,
@@ -187,6 +301,9 @@ pub struct Config {
     pub default_sort_order: SortOrder,
     pub zen_mode_show_header: bool,
     pub content_show_urls: bool,
+    pub content_show_images: bool,
+    pub content_image_height: u16,
+    pub content_image_max_kilobytes: u64,
     pub hint_type: HintType,
 
     pub feed_list_focused_width: Dimension,
@@ -199,12 +316,31 @@ pub struct Config {
     pub audio_enclosure_command: Option<String>,
     pub image_enclosure_command: Option<String>,
 
+    pub download_dir: String,
+    pub download_filename: String,
+    pub download_max_concurrent: usize,
+
+    pub player_command: String,
+
+    pub pipe_timeout_seconds: u64,
+    pub pipe_cache: bool,
+
     pub feed_list: Vec<FeedListContentIdentifier>,
 
+    pub browser_command: Option<String>,
+    pub url_handlers: Vec<UrlHandler>,
+
     pub share_targets: Vec<ShareTarget>,
+    pub clipboard_backend: ClipboardBackend,
 
     pub login_setup: Option<LoginConfiguration>,
 
+    pub profiles: IndexMap<String, Profile>,
+    pub unified_profiles: Vec<String>,
+    // name of the active profile (None for the top-level configuration)
+    #[serde(skip)]
+    pub profile: Option<String>,
+
     pub cli_sync_stats_format: SyncStatsOutputFormat,
 
     // DEPRECATED
@@ -238,6 +374,18 @@ impl Config {
             ));
         }
 
+        if self.profiles.contains_key(DEFAULT_PROFILE) {
+            return Err(color_eyre::eyre::eyre!(
+                "the profile name {DEFAULT_PROFILE} is reserved for the top-level configuration"
+            ));
+        }
+
+        if let Some(profile) = self.unified_profiles.iter().find(|profile| {
+            *profile != DEFAULT_PROFILE && !self.profiles.contains_key(profile.as_str())
+        }) {
+            return Err(ConfigError::UnknownProfile(profile.to_owned()).into());
+        }
+
         deprecated!(self.show_top_bar);
         deprecated!(self.scrollbar_begin_symbol);
         deprecated!(self.scrollbar_end_symbol);
@@ -247,6 +395,15 @@ impl Config {
         Ok(())
     }
 
+    // automatic syncs are only done inside the sync windows (if any are given)
+    pub fn is_in_sync_window(&self, time: chrono::NaiveTime) -> bool {
+        self.sync_windows.is_empty()
+            || self
+                .sync_windows
+                .iter()
+                .any(|sync_window| sync_window.contains(time))
+    }
+
     fn validate_input_config(&mut self) -> color_eyre::Result<()> {
         Self::default()
             .input_config
@@ -276,10 +433,13 @@ impl Default for Config {
         Self {
             refresh_fps: 10,
             network_timeout_seconds: 60,
+            network: Default::default(),
             keep_articles_days: 30,
 
             startup_commands: Default::default(),
             sync_every_minutes: None,
+            sync_windows: Default::default(),
+            metered_check_cmd: None,
 
             after_sync_commands: Default::default(),
             notify_after_sync: true,
@@ -292,10 +452,18 @@ impl Default for Config {
             last_synced_label: "{icon} Last Synced".into(),
             category_label: "{icon} {label} {unread_count}".into(),
             categories_label: "{icon} Categories {unread_count}".into(),
+            account_label: "{icon} {label} {unread_count}".into(),
             tags_label: "{icon} Tags {unread_count}".into(),
             tag_label: "{icon} {label} {unread_count}".into(),
             query_label: "{icon} {label}".into(),
             article_table: "{flagged},{read},{marked},{tag_icons},{age},{title}".into(),
+            article_list_layout: ListLayout::Table,
+            article_card: vec![
+                "{flagged}{read}{marked} {title}".into(),
+                "      {feed} · {age} {tags}".into(),
+                "      {summary}".into(),
+            ],
+            article_card_thumbnails: false,
             date_format: "%m/%d %H:%M".into(),
             theme: Default::default(),
             icon_set: Default::default(),
@@ -303,10 +471,15 @@ impl Default for Config {
             input_config: Default::default(),
             article_scope: ArticleScope::Unread,
             feed_list_scope: ArticleScope::All,
+            collapse_duplicates: false,
+            group_by: GroupBy::None,
+            duplicate_titles: false,
+            mark_duplicates_read: false,
 
             shadows: true,
             article_list_show_position: true,
             content_show_position: true,
+            content_read_at_end: false,
             articles_after_selection: 3,
             auto_scrape: true,
             thumbnail_show: true,
@@ -318,6 +491,9 @@ impl Default for Config {
             content_preferred_type: ArticleContentType::Markdown,
             zen_mode_show_header: false,
             content_show_urls: false,
+            content_show_images: false,
+            content_image_height: 12,
+            content_image_max_kilobytes: 4096,
             hint_type: HintType::Letters,
 
             feed_list_focused_width: Dimension::Percentage(25),
@@ -341,6 +517,15 @@ impl Default for Config {
             audio_enclosure_command: None,
             image_enclosure_command: None,
 
+            download_dir: "~/Downloads/eilmeldung".into(),
+            download_filename: "{feed}/{date} {title}.{ext}".into(),
+            download_max_concurrent: 2,
+
+            player_command: "mpv --no-terminal --idle=once --input-ipc-server={socket}".into(),
+
+            pipe_timeout_seconds: 300,
+            pipe_cache: false,
+
             feed_list: vec![
                 FeedListContentIdentifier::Query(LabeledQuery {
                     label: "Today Unread".to_owned(),
@@ -355,6 +540,9 @@ impl Default for Config {
                 FeedListContentIdentifier::Tags(FeedListItemType::Tree),
             ],
 
+            browser_command: None,
+            url_handlers: Default::default(),
+
             share_targets: vec![
                 ShareTarget::Clipboard,
                 ShareTarget::Reddit,
@@ -362,7 +550,11 @@ impl Default for Config {
                 ShareTarget::Instapaper,
                 ShareTarget::Telegram,
             ],
+            clipboard_backend: ClipboardBackend::Auto,
             login_setup: None,
+            profiles: Default::default(),
+            unified_profiles: Default::default(),
+            profile: None,
             mouse_support: false,
 
             // DEPRECATED
@@ -418,7 +610,7 @@ pub fn resolve_eilmeldung_config_dir(cli_args: &CliArgs) -> PathBuf {
         .unwrap_or(PathBuf::from(PROJECT_DIRS.config_dir()))
 }
 
-pub fn load_config(config_dir: &Path) -> color_eyre::Result<Config> {
+pub fn load_config(config_dir: &Path, profile: Option<&str>) -> color_eyre::Result<Config> {
     let mut config_path = PathBuf::from(config_dir);
     config_path.push(CONFIG_FILE);
 
@@ -429,14 +621,25 @@ pub fn load_config(config_dir: &Path) -> color_eyre::Result<Config> {
     info!("Trying to load config from {}", config_path);
 
     if !Path::new(config_path).exists() {
+        if let Some(profile) = profile {
+            return Err(ConfigError::UnknownProfile(profile.to_owned()).into());
+        }
         info!("No config file found, using default config");
         return Ok(Config::default());
     }
 
-    let mut config = match config::Config::builder()
-        .add_source(config::File::new(config_path, FileFormat::Toml))
-        .build()
-    {
+    let builder = config::Config::builder();
+    let builder = match profile {
+        Some(profile) => {
+            info!("Applying profile {profile}");
+            let config_toml =
+                profile::apply_profile(&std::fs::read_to_string(config_path)?, profile)?;
+            builder.add_source(config::File::from_str(&config_toml, FileFormat::Toml))
+        }
+        None => builder.add_source(config::File::new(config_path, FileFormat::Toml)),
+    };
+
+    let mut config = match builder.build() {
         Ok(config) => config.try_deserialize::<Config>()?,
         Err(err) => {
             warn!("unable to read config file: {err}");
@@ -445,6 +648,7 @@ pub fn load_config(config_dir: &Path) -> color_eyre::Result<Config> {
     };
 
     config.validate()?;
+    config.profile = profile.map(str::to_owned);
 
     Ok(config)
 }
@@ -0,0 +1,22 @@
+use indexmap::IndexMap;
+
+/// Settings of the HTTP clients (sync, scraper, thumbnails, images and connectivity check)
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// proxy for all requests, e.g. `http://proxy:3128` or `socks5h://localhost:1080`; if not
+    /// given, the proxy environment variables (`HTTPS_PROXY`, etc.) are used
+    pub proxy: Option<String>,
+
+    /// hosts (or domains, IPs and networks) which are not accessed through the proxy
+    pub no_proxy: Vec<String>,
+
+    /// user agent sent instead of the default one
+    pub user_agent: Option<String>,
+
+    /// headers sent with every request
+    pub headers: IndexMap<String, String>,
+
+    /// paths of PEM files with additional CA certificates
+    pub ca_certificates: Vec<String>,
+}
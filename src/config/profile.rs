@@ -0,0 +1,167 @@
+use std::path::{Path, PathBuf};
+
+use crate::prelude::*;
+
+/// Name of the profile which refers to the top-level configuration
+pub const DEFAULT_PROFILE: &str = "default";
+
+const PROFILES_DIR: &str = "profiles";
+const STATE_DIR_KEY: &str = "state_dir";
+const NEWS_FLASH_CONFIG_DIR_KEY: &str = "news_flash_config_dir";
+const PROFILES_KEY: &str = "profiles";
+
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub struct Profile {
+    /// directory with the news-flash state files (database, etc.) of this profile
+    #[serde(default)]
+    pub state_dir: Option<String>,
+
+    /// directory with the news-flash config files (authentication, etc.) of this profile
+    #[serde(default)]
+    pub news_flash_config_dir: Option<String>,
+
+    /// settings overriding the top-level configuration
+    #[serde(flatten)]
+    pub settings: toml::Table,
+}
+
+fn expand_dir(dir: &str) -> color_eyre::Result<PathBuf> {
+    Ok(PathBuf::from(shellexpand::full(dir)?.as_ref()))
+}
+
+impl Profile {
+    /// Returns the state dir of the profile, defaulting to a sub directory of the given state dir
+    pub fn resolve_state_dir(&self, name: &str, state_dir: &Path) -> color_eyre::Result<PathBuf> {
+        match self.state_dir.as_deref() {
+            Some(dir) => expand_dir(dir),
+            None => Ok(state_dir.join(PROFILES_DIR).join(name)),
+        }
+    }
+
+    /// Returns the news-flash config dir of the profile, defaulting to a sub directory of the
+    /// given config dir
+    pub fn resolve_news_flash_config_dir(
+        &self,
+        name: &str,
+        news_flash_config_dir: &Path,
+    ) -> color_eyre::Result<PathBuf> {
+        match self.news_flash_config_dir.as_deref() {
+            Some(dir) => expand_dir(dir),
+            None => Ok(news_flash_config_dir.join(PROFILES_DIR).join(name)),
+        }
+    }
+}
+
+// merges the overlay into the base: tables are merged recursively, all other values (including
+// arrays) are replaced
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Applies the settings of the given profile to the configuration and returns the resulting
+/// configuration as TOML
+pub(super) fn apply_profile(config_toml: &str, profile: &str) -> color_eyre::Result<String> {
+    let mut config_table: toml::Table = toml::from_str(config_toml)?;
+
+    let Some(toml::Value::Table(mut profile_table)) = config_table
+        .get(PROFILES_KEY)
+        .and_then(|profiles| profiles.get(profile))
+        .cloned()
+    else {
+        return Err(ConfigError::UnknownProfile(profile.to_owned()).into());
+    };
+
+    // these are not settings of the configuration
+    profile_table.remove(STATE_DIR_KEY);
+    profile_table.remove(NEWS_FLASH_CONFIG_DIR_KEY);
+    if profile_table.remove(PROFILES_KEY).is_some() {
+        return Err(ConfigError::ValidationError(format!(
+            "profile {profile} must not define profiles"
+        ))
+        .into());
+    }
+
+    merge_tables(&mut config_table, profile_table);
+
+    Ok(toml::to_string(&config_table)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_err, assert_ok};
+
+    use super::*;
+
+    const CONFIG: &str = r#"
+refresh_fps = 10
+share_targets = ["clipboard", "reddit"]
+
+[theme.color_palette]
+background = "black"
+foreground = "white"
+
+[profiles.work]
+state_dir = "/tmp/work"
+refresh_fps = 20
+share_targets = ["mastodon"]
+
+[profiles.work.theme.color_palette]
+foreground = "gray"
+"#;
+
+    #[test]
+    fn test_apply_profile() {
+        let config_toml = assert_ok!(apply_profile(CONFIG, "work"));
+        let config_table: toml::Table = assert_ok!(toml::from_str(&config_toml));
+
+        assert_eq!(config_table["refresh_fps"].as_integer(), Some(20));
+        // arrays are replaced
+        assert_eq!(
+            config_table["share_targets"].as_array().map(Vec::len),
+            Some(1)
+        );
+        // tables are merged
+        assert_eq!(
+            config_table["theme"]["color_palette"]["background"].as_str(),
+            Some("black")
+        );
+        assert_eq!(
+            config_table["theme"]["color_palette"]["foreground"].as_str(),
+            Some("gray")
+        );
+        assert!(!config_table.contains_key(STATE_DIR_KEY));
+    }
+
+    #[test]
+    fn test_apply_unknown_profile() {
+        let error = assert_err!(apply_profile(CONFIG, "home"));
+        assert_eq!(error.to_string(), "unknown profile: home");
+    }
+
+    #[test]
+    fn test_resolve_dirs() {
+        let profile = Profile::default();
+        assert_eq!(
+            assert_ok!(profile.resolve_state_dir("home", Path::new("/state"))),
+            PathBuf::from("/state/profiles/home")
+        );
+
+        let profile = Profile {
+            news_flash_config_dir: Some("/config/home".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            assert_ok!(profile.resolve_news_flash_config_dir("home", Path::new("/config"))),
+            PathBuf::from("/config/home")
+        );
+    }
+}
@@ -4,9 +4,10 @@ use std::{
     str::FromStr,
 };
 
-use crate::prelude::*;
-use arboard::Clipboard;
+use crate::{config::login_configuration::Secret, prelude::*};
+use indexmap::{IndexMap, IndexSet};
 use log::info;
+use news_flash::models::{Article, Feed};
 use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
 use strum::EnumMessage;
 use url::Url;
@@ -55,6 +56,202 @@ pub enum ShareTarget {
 
     #[strum(message = "Command", detailed_message = "command")]
     Command(String, Vec<String>),
+
+    #[strum(message = "Webhook", detailed_message = "HTTP request to a webhook")]
+    Webhook(Box<Webhook>),
+}
+
+// how several articles are shared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareMode {
+    // each article on its own (e.g., one browser tab or one command per article)
+    Each,
+    // all articles at once (see `ShareItem::join`)
+    Join,
+}
+
+// an article (or link) as it is shared; all fields are available as placeholders in templates
+#[derive(Debug, Clone)]
+pub struct ShareItem {
+    pub url: Url,
+    pub title: String,
+    pub feed: String,
+    pub author: String,
+    pub date: String,
+    pub summary: String,
+    pub tags: String,
+}
+
+impl ShareItem {
+    pub fn new(title: &str, url: &Url) -> Self {
+        Self {
+            url: url.to_owned(),
+            title: title.to_owned(),
+            feed: String::new(),
+            author: String::new(),
+            date: String::new(),
+            summary: String::new(),
+            tags: String::new(),
+        }
+    }
+
+    // `None` if the article has no URL
+    pub fn from_article(
+        article: &Article,
+        feed: Option<&Feed>,
+        tags: Vec<&str>,
+        date_format: &str,
+    ) -> Option<Self> {
+        Some(Self {
+            url: (**article.url.as_ref()?).to_owned(),
+            title: html_sanitize(article.title.as_deref().unwrap_or("no title")),
+            feed: feed.map(|feed| feed.label.to_owned()).unwrap_or_default(),
+            author: article.author.to_owned().unwrap_or_default(),
+            date: article
+                .date
+                .with_timezone(&chrono::Local)
+                .format(date_format)
+                .to_string(),
+            summary: html_sanitize(article.summary.as_deref().unwrap_or_default()),
+            tags: tags.join(", "),
+        })
+    }
+
+    pub fn fill_in(&self, template: &str, escape: impl Fn(&str) -> String) -> String {
+        template
+            .replace("{url}", &escape(self.url.as_str()))
+            .replace("{title}", &escape(&self.title))
+            .replace("{feed}", &escape(&self.feed))
+            .replace("{author}", &escape(&self.author))
+            .replace("{date}", &escape(&self.date))
+            .replace("{summary}", &escape(&self.summary))
+            .replace("{tags}", &escape(&self.tags))
+    }
+
+    // one item for several items: the URL of the first item, the titles separated by `; ` and a
+    // Markdown list of all items as summary
+    pub fn join(items: &[ShareItem]) -> Option<Self> {
+        let (first, rest) = items.split_first()?;
+        if rest.is_empty() {
+            return Some(first.to_owned());
+        }
+
+        let unique_joined = |field: fn(&ShareItem) -> &str, separator: &str| {
+            items
+                .iter()
+                .map(field)
+                .filter(|value| !value.is_empty())
+                .collect::<IndexSet<&str>>()
+                .into_iter()
+                .collect::<Vec<&str>>()
+                .join(separator)
+        };
+
+        Some(Self {
+            url: first.url.to_owned(),
+            title: unique_joined(|item| &item.title, "; "),
+            feed: unique_joined(|item| &item.feed, ", "),
+            author: unique_joined(|item| &item.author, ", "),
+            date: first.date.to_owned(),
+            summary: items
+                .iter()
+                .map(|item| format!("- {}", item.to_markdown()))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            tags: unique_joined(|item| &item.tags, ", "),
+        })
+    }
+
+    fn to_markdown(&self) -> String {
+        format!("[{}]({})", self.title.replace(['[', ']'], ""), self.url)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookMethod {
+    #[default]
+    Post,
+    Put,
+}
+
+#[derive(Debug, Default, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    #[default]
+    Json,
+    Form,
+}
+
+// share target which sends the article to an HTTP endpoint, e.g., a read-it-later service or a
+// chat webhook
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Webhook {
+    pub name: String,
+    #[serde(rename = "webhook")]
+    pub url: String,
+    #[serde(default)]
+    pub method: WebhookMethod,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    // template of the body; the placeholders are escaped according to the format
+    pub body: String,
+    #[serde(default)]
+    pub headers: IndexMap<String, String>,
+    // value of the `Authorization` header (after `auth_scheme`)
+    pub auth: Option<Secret>,
+    pub auth_scheme: Option<String>,
+}
+
+impl Webhook {
+    fn fill_in_body(&self, item: &ShareItem) -> String {
+        item.fill_in(&self.body, |value| match self.format {
+            WebhookFormat::Json => {
+                let quoted = serde_json::Value::from(value).to_string();
+                quoted[1..quoted.len() - 1].to_owned()
+            }
+            WebhookFormat::Form => utf8_percent_encode(value, NON_ALPHANUMERIC).to_string(),
+        })
+    }
+
+    // sends the article and returns the status of the response
+    pub async fn send(
+        &self,
+        item: &ShareItem,
+        client: &reqwest::Client,
+    ) -> color_eyre::Result<reqwest::StatusCode> {
+        let mut request = match self.method {
+            WebhookMethod::Post => client.post(&self.url),
+            WebhookMethod::Put => client.put(&self.url),
+        }
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            match self.format {
+                WebhookFormat::Json => "application/json",
+                WebhookFormat::Form => "application/x-www-form-urlencoded",
+            },
+        )
+        .body(self.fill_in_body(item));
+
+        for (name, value) in self.headers.iter() {
+            request = request.header(name, value);
+        }
+
+        if let Some(auth) = self.auth.as_ref() {
+            let secret = auth.get_secret()?;
+            request = request.header(
+                reqwest::header::AUTHORIZATION,
+                match self.auth_scheme.as_deref() {
+                    Some(scheme) => format!("{scheme} {secret}"),
+                    None => secret,
+                },
+            );
+        }
+
+        info!("sending article to webhook {} at {}", self.name, self.url);
+        Ok(request.send().await?.status())
+    }
 }
 
 impl AsRef<str> for ShareTarget {
@@ -62,6 +259,7 @@ impl AsRef<str> for ShareTarget {
         match self {
             ShareTarget::Custom(name, ..) => name,
             ShareTarget::Command(name, ..) => name,
+            ShareTarget::Webhook(webhook) => &webhook.name,
             target => target.into(),
         }
     }
@@ -73,6 +271,7 @@ impl Display for ShareTarget {
         match self {
             T::Custom(name, ..) => f.write_str(name),
             T::Command(name, ..) => f.write_str(name),
+            T::Webhook(webhook) => f.write_str(&webhook.name),
             target => f.write_str(target.get_message().unwrap_or(self.as_ref())),
         }
     }
@@ -116,10 +315,7 @@ impl FromStr for ShareTarget {
 }
 
 impl ShareTarget {
-    fn to_url(&self, title: &str, url: &Url) -> Result<Url, ConfigError> {
-        let url_escaped = utf8_percent_encode(url.as_ref(), NON_ALPHANUMERIC).to_string();
-        let title_escaped = utf8_percent_encode(title, NON_ALPHANUMERIC).to_string();
-
+    fn to_url(&self, item: &ShareItem) -> Result<Url, ConfigError> {
         use ShareTarget as T;
         let url_template = match self {
             T::Reddit => REDDIT_URL_TEMPLATE,
@@ -128,38 +324,86 @@ impl ShareTarget {
             T::Telegram => TELEGRAM_URL_TEMPLATE,
             T::Custom(_, url_template) => url_template,
             T::Clipboard => return Err(ConfigError::ShareTargetInvalid),
-            T::Command(..) => return Err(ConfigError::ShareTargetInvalid),
+            T::Command(..) | T::Webhook(..) => return Err(ConfigError::ShareTargetInvalid),
         };
 
-        let url_str = url_template
-            .to_owned()
-            .replace("{url}", &url_escaped)
-            .replace("{title}", &title_escaped);
+        let url_str = item.fill_in(url_template, |value| {
+            utf8_percent_encode(value, NON_ALPHANUMERIC).to_string()
+        });
         Ok(Url::parse(&url_str)?)
     }
 
-    pub fn share(&self, title: &str, url: &Url) -> color_eyre::Result<()> {
+    // the clipboard receives all articles at once, the other targets each article
+    pub fn default_mode(&self) -> ShareMode {
+        match self {
+            ShareTarget::Clipboard => ShareMode::Join,
+            _ => ShareMode::Each,
+        }
+    }
+
+    // the target opens a URL in the browser
+    pub fn opens_browser(&self) -> bool {
+        !matches!(
+            self,
+            ShareTarget::Clipboard | ShareTarget::Command(..) | ShareTarget::Webhook(..)
+        )
+    }
+
+    // the items as they are shared in the given mode: either all or one joined item
+    pub fn items_for_mode(items: &[ShareItem], mode: ShareMode) -> Vec<ShareItem> {
+        match mode {
+            ShareMode::Each => items.to_vec(),
+            ShareMode::Join => ShareItem::join(items).into_iter().collect(),
+        }
+    }
+
+    // text copied to the clipboard: the URLs one per line or, joined, a Markdown list of links
+    // (only the URL for a single item)
+    fn clipboard_text(items: &[ShareItem], mode: ShareMode) -> String {
+        match (items, mode) {
+            ([item], _) => item.url.to_string(),
+            (items, ShareMode::Each) => items
+                .iter()
+                .map(|item| item.url.to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+            (items, ShareMode::Join) => ShareItem::join(items)
+                .map(|item| item.summary)
+                .unwrap_or_default(),
+        }
+    }
+
+    // webhooks are sent asynchronously (see `Webhook::send`)
+    pub fn share(
+        &self,
+        items: &[ShareItem],
+        mode: ShareMode,
+        clipboard_backend: ClipboardBackend,
+    ) -> color_eyre::Result<()> {
         match self {
             ShareTarget::Clipboard => {
-                let mut clipboard = Clipboard::new()?;
-                clipboard.set_text(url.to_string())?;
-                Ok(())
+                copy_to_clipboard(&Self::clipboard_text(items, mode), clipboard_backend)
             }
 
-            ShareTarget::Command(_, args) => Self::execute_as_command(args, title, url),
+            ShareTarget::Command(_, args) => Self::items_for_mode(items, mode)
+                .iter()
+                .try_for_each(|item| Self::execute_as_command(args, item)),
 
-            _ => {
-                let share_url = self.to_url(title, url)?;
-                webbrowser::open(share_url.to_string().as_str())?;
-                Ok(())
-            }
+            ShareTarget::Webhook(..) => Err(ConfigError::ShareTargetInvalid.into()),
+
+            _ => Self::items_for_mode(items, mode)
+                .iter()
+                .try_for_each(|item| {
+                    let share_url = self.to_url(item)?;
+                    webbrowser::open(share_url.to_string().as_str())?;
+                    Ok(())
+                }),
         }
     }
 
     fn to_command(
         command_args: &[String],
-        title: &str,
-        url: &Url,
+        item: &ShareItem,
     ) -> color_eyre::Result<(Command, Vec<String>)> {
         let Some((cmd, args)) = command_args.split_first() else {
             return Err(color_eyre::eyre::eyre!("command is empty"));
@@ -167,22 +411,15 @@ impl ShareTarget {
 
         let filled_in_args = args
             .iter()
-            .map(|arg| {
-                arg.replace("{url}", url.as_ref())
-                    .replace("{title}", title.as_ref())
-            })
+            .map(|arg| item.fill_in(arg, str::to_owned))
             .collect::<Vec<String>>();
 
         info!("executing command {} {:?}", cmd, filled_in_args);
         Ok((Command::new(cmd), filled_in_args))
     }
 
-    fn execute_as_command(
-        command_args: &[String],
-        title: &str,
-        url: &Url,
-    ) -> color_eyre::Result<()> {
-        let (mut cmd, filled_in_args) = Self::to_command(command_args, title, url)?;
+    fn execute_as_command(command_args: &[String], item: &ShareItem) -> color_eyre::Result<()> {
+        let (mut cmd, filled_in_args) = Self::to_command(command_args, item)?;
 
         cmd.stdin(Stdio::null())
             .stdout(Stdio::null())
@@ -199,9 +436,20 @@ impl<'de> serde::de::Deserialize<'de> for ShareTarget {
     where
         D: serde::Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
+        // share targets are strings, webhooks are tables
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Definition {
+            Target(String),
+            Webhook(Box<Webhook>),
+        }
 
-        ShareTarget::from_str(&s).map_err(|err| serde::de::Error::custom(err.to_string()))
+        match Definition::deserialize(deserializer)? {
+            Definition::Target(s) => {
+                ShareTarget::from_str(&s).map_err(|err| serde::de::Error::custom(err.to_string()))
+            }
+            Definition::Webhook(webhook) => Ok(ShareTarget::Webhook(webhook)),
+        }
     }
 }
 
@@ -244,8 +492,11 @@ mod tests {
                 .map(|s| s.to_owned())
                 .collect::<Vec<String>>();
 
-            let (_command, replaced_args) =
-                ShareTarget::to_command(&args, $title, &Url::from_str($url).unwrap()).unwrap();
+            let (_command, replaced_args) = ShareTarget::to_command(
+                &args,
+                &ShareItem::new($title, &Url::from_str($url).unwrap()),
+            )
+            .unwrap();
 
             assert_eq!(replaced_args, $replaced,);
         }};
@@ -304,12 +555,11 @@ mod tests {
 
     #[test]
     fn test_to_url() {
-        // pub fn to_url(&self, title: &str, url: &Url) -> Result<Url, ConfigError> {
         use ShareTarget as T;
-        let url = T::Mastodon.to_url(
+        let url = T::Mastodon.to_url(&ShareItem::new(
             "Title",
             &Url::from_str("http://www.newssite.com/article?id=123").unwrap(),
-        );
+        ));
         assert_matches!(url, Ok(Url { .. }));
 
         assert_eq!(
@@ -348,4 +598,143 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_webhook() {
+        #[derive(serde::Deserialize)]
+        struct Targets {
+            share_targets: Vec<ShareTarget>,
+        }
+
+        let targets: Targets = toml::from_str(
+            r#"share_targets = [
+                "clipboard",
+                { name = "linkding", webhook = "https://links.example.com/api/bookmarks/", body = '{"url": "{url}", "title": "{title}"}', auth = "abcd", auth_scheme = "Token" },
+                { name = "form", webhook = "https://example.com/save", method = "put", format = "form", body = "url={url}&title={title}" },
+            ]"#,
+        )
+        .unwrap();
+
+        use ShareTarget as S;
+        assert_matches!(&targets.share_targets[0], S::Clipboard);
+        let url = Url::from_str("https://www.newssite.com/article?id=123").unwrap();
+
+        let S::Webhook(webhook) = &targets.share_targets[1] else {
+            panic!("expected webhook");
+        };
+        assert_eq!(webhook.name, "linkding");
+        assert_eq!(
+            webhook.fill_in_body(&ShareItem::new("A \"quoted\" title", &url)),
+            r#"{"url": "https://www.newssite.com/article?id=123", "title": "A \"quoted\" title"}"#
+        );
+
+        let S::Webhook(webhook) = &targets.share_targets[2] else {
+            panic!("expected webhook");
+        };
+        assert_matches!(webhook.method, WebhookMethod::Put);
+        assert_eq!(
+            webhook.fill_in_body(&ShareItem::new("Title", &url)),
+            "url=https%3A%2F%2Fwww%2Enewssite%2Ecom%2Farticle%3Fid%3D123&title=Title"
+        );
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let item = ShareItem {
+            feed: "News".to_owned(),
+            author: "Jane".to_owned(),
+            date: "01/02".to_owned(),
+            summary: "Summary".to_owned(),
+            tags: "rust, tui".to_owned(),
+            ..ShareItem::new("Title", &Url::from_str("https://example.com/1").unwrap())
+        };
+        assert_eq!(
+            item.fill_in(
+                "{title} ({feed}, {author}, {date}): {summary} [{tags}] {url}",
+                str::to_owned
+            ),
+            "Title (News, Jane, 01/02): Summary [rust, tui] https://example.com/1"
+        );
+        assert_eq!(item.to_markdown(), "[Title](https://example.com/1)");
+    }
+
+    fn item(title: &str, url: &str, feed: &str) -> ShareItem {
+        ShareItem {
+            feed: feed.to_owned(),
+            ..ShareItem::new(title, &Url::from_str(url).unwrap())
+        }
+    }
+
+    #[test]
+    fn test_join() {
+        assert!(ShareItem::join(&[]).is_none());
+
+        let single = item("One", "https://example.com/1", "News");
+        let joined = ShareItem::join(std::slice::from_ref(&single)).unwrap();
+        assert_eq!(joined.title, "One");
+        assert_eq!(joined.summary, "");
+
+        let joined = ShareItem::join(&[
+            single,
+            item("Two", "https://example.com/2", "News"),
+            item("Three", "https://example.com/3", "Blog"),
+        ])
+        .unwrap();
+        assert_eq!(joined.url.as_str(), "https://example.com/1");
+        assert_eq!(joined.title, "One; Two; Three");
+        assert_eq!(joined.feed, "News, Blog");
+        assert_eq!(
+            joined.summary,
+            "- [One](https://example.com/1)\n- [Two](https://example.com/2)\n- [Three](https://example.com/3)"
+        );
+    }
+
+    #[test]
+    fn test_share_modes() {
+        use ShareTarget as S;
+
+        let items = [
+            item("One", "https://example.com/1", ""),
+            item("Two", "https://example.com/2", ""),
+        ];
+
+        assert_eq!(S::Clipboard.default_mode(), ShareMode::Join);
+        assert_eq!(S::Reddit.default_mode(), ShareMode::Each);
+        assert!(S::Reddit.opens_browser());
+        assert!(!S::Clipboard.opens_browser());
+        assert!(!S::Command("cmd".to_owned(), vec!["echo".to_owned()]).opens_browser());
+
+        assert_eq!(
+            S::clipboard_text(&items, ShareMode::Join),
+            "- [One](https://example.com/1)\n- [Two](https://example.com/2)"
+        );
+        assert_eq!(
+            S::clipboard_text(&items, ShareMode::Each),
+            "https://example.com/1\nhttps://example.com/2"
+        );
+        assert_eq!(
+            S::clipboard_text(&items[..1], ShareMode::Join),
+            "https://example.com/1"
+        );
+
+        assert_eq!(S::items_for_mode(&items, ShareMode::Each).len(), 2);
+        assert_eq!(S::items_for_mode(&items, ShareMode::Join).len(), 1);
+    }
+
+    #[test]
+    fn test_share_options() {
+        let mut options = ShareOptions::default();
+        assert_eq!(options.mode(&ShareTarget::Clipboard), ShareMode::Join);
+        assert_eq!(options.mode(&ShareTarget::Mastodon), ShareMode::Each);
+
+        options.set("--each").unwrap();
+        assert_eq!(options.mode(&ShareTarget::Clipboard), ShareMode::Each);
+        // `--each` and `--join` exclude each other
+        assert!(options.set("--join").is_err());
+        assert!(options.set("--sh").is_err());
+
+        let mut options = ShareOptions::default();
+        options.set("--join").unwrap();
+        assert_eq!(options.mode(&ShareTarget::Mastodon), ShareMode::Join);
+    }
 }
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use crate::prelude::*;
 use itertools::Itertools;
-use news_flash::models::FeedID;
+use news_flash::models::{Category, CategoryMapping, Feed, FeedID, FeedMapping};
 
 #[derive(Debug, Clone, serde::Deserialize)]
 #[serde(default)]
@@ -35,11 +35,13 @@ impl SyncStatsOutputFormat {
         }
     }
 
+    // the feeds and categories are those of the account (or of all accounts) which was synced
     pub fn gen_output(
         &self,
-        news_flash: &news_flash::NewsFlash,
+        feeds: (Vec<Feed>, Vec<FeedMapping>),
+        categories: (Vec<Category>, Vec<CategoryMapping>),
         new_articles: &HashMap<FeedID, i64>,
-    ) -> color_eyre::Result<String> {
+    ) -> String {
         let mut output = String::new();
 
         let all_unread: i64 = new_articles.values().sum();
@@ -51,7 +53,7 @@ impl SyncStatsOutputFormat {
             mut categories,
             category_for_category_id,
             category_mapping_for_category_id,
-        ) = get_feeds_and_categories(news_flash)?;
+        ) = get_feeds_and_categories(feeds, categories);
 
         sort_feeds_and_categories(
             &mut feeds,
@@ -105,6 +107,6 @@ impl SyncStatsOutputFormat {
             );
         }
 
-        Ok(output)
+        output
     }
 }
@@ -0,0 +1,108 @@
+use crate::prelude::*;
+use chrono::NaiveTime;
+use std::str::FromStr;
+
+// time of day during which automatic syncs are allowed, e.g. `07:00-22:00`; windows may span
+// midnight (`22:00-06:00`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl SyncWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl FromStr for SyncWindow {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((start, end)) = s.split_once(['-', '–']) else {
+            return Err(ConfigError::SyncWindowParseError(
+                "expected <start>-<end>".to_owned(),
+            ));
+        };
+
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| {
+                ConfigError::SyncWindowParseError(format!("expected time as HH:MM, got {time}"))
+            })
+        };
+
+        let sync_window = SyncWindow {
+            start: parse_time(start)?,
+            end: parse_time(end)?,
+        };
+
+        if sync_window.start == sync_window.end {
+            return Err(ConfigError::SyncWindowParseError(
+                "start and end must differ".to_owned(),
+            ));
+        }
+
+        Ok(sync_window)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SyncWindow {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        SyncWindow::from_str(&s).map_err(|err| serde::de::Error::custom(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn parse_valid() {
+        let sync_window = SyncWindow::from_str("07:00-22:00").unwrap();
+        assert_eq!(sync_window.start, time("07:00"));
+        assert_eq!(sync_window.end, time("22:00"));
+
+        assert!(SyncWindow::from_str(" 7:30 – 9:15 ").is_ok());
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(SyncWindow::from_str("").is_err());
+        assert!(SyncWindow::from_str("07:00").is_err());
+        assert!(SyncWindow::from_str("07:00-25:00").is_err());
+        assert!(SyncWindow::from_str("morning-evening").is_err());
+        assert!(SyncWindow::from_str("08:00-08:00").is_err());
+    }
+
+    #[test]
+    fn contains_same_day() {
+        let sync_window = SyncWindow::from_str("07:00-22:00").unwrap();
+        assert!(sync_window.contains(time("07:00")));
+        assert!(sync_window.contains(time("12:00")));
+        assert!(!sync_window.contains(time("22:00")));
+        assert!(!sync_window.contains(time("06:59")));
+    }
+
+    #[test]
+    fn contains_across_midnight() {
+        let sync_window = SyncWindow::from_str("22:00-06:00").unwrap();
+        assert!(sync_window.contains(time("23:30")));
+        assert!(sync_window.contains(time("00:00")));
+        assert!(sync_window.contains(time("05:59")));
+        assert!(!sync_window.contains(time("06:00")));
+        assert!(!sync_window.contains(time("12:00")));
+    }
+}
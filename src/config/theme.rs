@@ -186,6 +186,16 @@ pub struct StyleSet {
     tooltip_warning: ComponentStyle,
     tooltip_error: ComponentStyle,
 
+    code: ComponentStyle,
+    code_keyword: ComponentStyle,
+    code_string: ComponentStyle,
+    code_comment: ComponentStyle,
+    code_number: ComponentStyle,
+    code_type: ComponentStyle,
+    code_function: ComponentStyle,
+    table_header: ComponentStyle,
+    table_separator: ComponentStyle,
+
     unread: ComponentStyle,
     unread_count: ComponentStyle,
     marked_count: ComponentStyle,
@@ -225,6 +235,20 @@ impl Default for StyleSet {
                 .mods(&[M::Reversed]),
             tooltip_error: ComponentStyle::default().fg(C::Error).mods(&[M::Reversed]),
 
+            code: ComponentStyle::default().fg(C::Foreground),
+            code_keyword: ComponentStyle::default()
+                .fg(C::AccentPrimary)
+                .mods(&[M::Bold]),
+            code_string: ComponentStyle::default().fg(C::AccentTertiary),
+            code_comment: ComponentStyle::default().fg(C::Muted).mods(&[M::Italic]),
+            code_number: ComponentStyle::default().fg(C::AccentQuaternary),
+            code_type: ComponentStyle::default().fg(C::AccentSecondary),
+            code_function: ComponentStyle::default().fg(C::Highlight),
+            table_header: ComponentStyle::default()
+                .fg(C::AccentPrimary)
+                .mods(&[M::Bold]),
+            table_separator: ComponentStyle::default().fg(C::Muted),
+
             unread: ComponentStyle::default().mods(&[M::Bold]),
             read: ComponentStyle::default().mods(&[M::Dim]),
             selected: ComponentStyle::default().mods(&[M::Reversed]),
@@ -327,6 +351,15 @@ impl Theme {
       tooltip_info,
       tooltip_warning,
       tooltip_error,
+      code,
+      code_keyword,
+      code_string,
+      code_comment,
+      code_number,
+      code_type,
+      code_function,
+      table_header,
+      table_separator,
       unread_count,
       marked_count
     }
@@ -13,6 +13,7 @@ const IS_REACHABLE_RETRIES: u16 = 10;
 const TIME_BETWEEN_RETRIES: Duration = Duration::from_secs(1);
 
 pub struct ConnectivityMonitor {
+    config: Arc<Config>,
     message_sender: UnboundedSender<Message>,
     news_flash_utils: Arc<NewsFlashUtils>,
     is_running: Arc<Mutex<bool>>,
@@ -26,10 +27,12 @@ pub enum ConnectionLostReason {
 
 impl ConnectivityMonitor {
     pub fn new(
+        config: Arc<Config>,
         news_flash_utils: Arc<NewsFlashUtils>,
         message_sender: UnboundedSender<Message>,
     ) -> Self {
         Self {
+            config,
             message_sender,
             news_flash_utils,
 
@@ -38,9 +41,14 @@ impl ConnectivityMonitor {
     }
 
     async fn check_reachability(&self) -> color_eyre::Result<()> {
+        if self.news_flash_utils.is_offline_mode() {
+            trace!("offline mode, skipping reachability check");
+            return Ok(());
+        }
+
         let news_flash = self.news_flash_utils.news_flash_lock.read().await;
         info!("checking reachability of service");
-        let client = build_client(Duration::from_secs(10))?;
+        let client = build_client(Duration::from_secs(10), &self.config.network)?;
 
         let mut is_reachable: bool = true;
 
@@ -0,0 +1,497 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Mutex, RwLock},
+};
+
+use chrono::Utc;
+use log::{debug, info, warn};
+use news_flash::models::{Article, ArticleID, Enclosure, Feed};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::{io::AsyncWriteExt, sync::Semaphore};
+
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{Downloads, default_enclosure, download_path};
+}
+
+const DOWNLOADS_FILE: &str = "downloads.json";
+
+// extension of files while they are downloaded
+const PARTIAL_EXTENSION: &str = "part";
+
+// characters which are not allowed in file names on at least one OS
+const FORBIDDEN_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+// file names are cut to this number of characters (without extension)
+const MAX_FILE_NAME_CHARS: usize = 120;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DownloadedFile {
+    pub article_id: ArticleID,
+    pub url: String,
+    pub path: PathBuf,
+    // unix timestamp of the completion of the download
+    pub at: i64,
+}
+
+// bytes received of a running download; total is unknown if the server does not send it
+#[derive(Clone, Copy, Debug, Default)]
+struct Progress {
+    received: u64,
+    total: Option<u64>,
+}
+
+// summary of the running downloads shown in the status bar
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DownloadStatus {
+    pub running: usize,
+    pub queued: usize,
+    pub received: u64,
+    pub total: Option<u64>,
+}
+
+impl DownloadStatus {
+    pub fn percent(&self) -> Option<u64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.received * 100 / total).min(100))
+    }
+}
+
+// enclosures downloaded to the local disk and the downloads which are currently running; the
+// library of downloaded files is stored in the state dir
+pub struct Downloads {
+    path: PathBuf,
+    files: RwLock<Vec<DownloadedFile>>,
+    // URLs of queued (None) and running downloads
+    running: Mutex<HashMap<String, Option<Progress>>>,
+    semaphore: Semaphore,
+}
+
+impl Downloads {
+    pub fn load(state_dir: &Path, max_concurrent: usize) -> Self {
+        let path = state_dir.join(DOWNLOADS_FILE);
+
+        let files = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                warn!("unable to parse downloads in {path:?}: {err}");
+                Default::default()
+            }),
+            Err(err) => {
+                debug!("no downloads loaded from {path:?}: {err}");
+                Default::default()
+            }
+        };
+
+        Self {
+            path,
+            files: RwLock::new(files),
+            running: Default::default(),
+            semaphore: Semaphore::new(max_concurrent.max(1)),
+        }
+    }
+
+    // articles with at least one downloaded enclosure which still exists on disk
+    pub fn downloaded_articles(&self) -> HashSet<ArticleID> {
+        self.files
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|file| file.path.exists())
+            .map(|file| file.article_id.to_owned())
+            .collect()
+    }
+
+    pub fn files_of(&self, article_id: &ArticleID) -> Vec<DownloadedFile> {
+        self.files
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|file| &file.article_id == article_id && file.path.exists())
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_downloaded(&self, url: &str) -> bool {
+        self.files
+            .read()
+            .unwrap()
+            .iter()
+            .any(|file| file.url == url && file.path.exists())
+    }
+
+    pub fn status(&self) -> Option<DownloadStatus> {
+        let running = self.running.lock().unwrap();
+        if running.is_empty() {
+            return None;
+        }
+
+        let mut status = DownloadStatus {
+            total: Some(0),
+            ..Default::default()
+        };
+        for progress in running.values() {
+            match progress {
+                None => status.queued += 1,
+                Some(progress) => {
+                    status.running += 1;
+                    status.received += progress.received;
+                    status.total = status.total.zip(progress.total).map(|(a, b)| a + b);
+                }
+            }
+        }
+        Some(status)
+    }
+
+    // queues the download of the enclosure and returns false if it is already downloaded or
+    // queued
+    pub fn enqueue(&self, url: &str) -> bool {
+        if self.is_downloaded(url) {
+            return false;
+        }
+        let mut running = self.running.lock().unwrap();
+        if running.contains_key(url) {
+            return false;
+        }
+        running.insert(url.to_owned(), None);
+        true
+    }
+
+    // removes a queued download which is not started
+    pub fn cancel(&self, url: &str) {
+        self.running.lock().unwrap().remove(url);
+    }
+
+    // downloads a queued enclosure as soon as one of the download slots is free; the file is
+    // written with a partial extension which is removed when the download is complete
+    pub async fn download(
+        &self,
+        client: &Client,
+        article_id: &ArticleID,
+        url: &str,
+        path: &Path,
+    ) -> color_eyre::Result<PathBuf> {
+        let mut partial_path = None;
+        let result = async {
+            let _permit = self.semaphore.acquire().await?;
+            self.set_progress(url, 0, None);
+
+            let mut response = client.get(url).send().await?.error_for_status()?;
+            let total = response.content_length();
+
+            let path = unique_path(path);
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let partial_path = partial_path.insert(path.with_added_extension(PARTIAL_EXTENSION));
+            let mut file = tokio::fs::File::create(&partial_path).await?;
+
+            let mut received = 0;
+            while let Some(chunk) = response.chunk().await? {
+                file.write_all(&chunk).await?;
+                received += chunk.len() as u64;
+                self.set_progress(url, received, total);
+            }
+            file.flush().await?;
+            tokio::fs::rename(&partial_path, &path).await?;
+
+            info!("downloaded {url} to {path:?}");
+            Ok::<PathBuf, color_eyre::Report>(path)
+        }
+        .await;
+
+        // incomplete files are not kept
+        if result.is_err()
+            && let Some(partial_path) = partial_path
+            && let Err(err) = tokio::fs::remove_file(&partial_path).await
+        {
+            debug!("unable to remove partial download {partial_path:?}: {err}");
+        }
+
+        self.running.lock().unwrap().remove(url);
+
+        let path = result?;
+        {
+            let mut files = self.files.write().unwrap();
+            files.retain(|file| file.url != url || file.path != path);
+            files.push(DownloadedFile {
+                article_id: article_id.to_owned(),
+                url: url.to_owned(),
+                path: path.to_owned(),
+                at: Utc::now().timestamp(),
+            });
+        }
+        self.save()?;
+
+        Ok(path)
+    }
+
+    fn set_progress(&self, url: &str, received: u64, total: Option<u64>) {
+        self.running
+            .lock()
+            .unwrap()
+            .insert(url.to_owned(), Some(Progress { received, total }));
+    }
+
+    fn save(&self) -> color_eyre::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(
+            &self.path,
+            serde_json::to_string(&*self.files.read().unwrap())?,
+        )?;
+        Ok(())
+    }
+}
+
+// the default enclosure of the given type (any type if None) or the first one of this type
+pub fn default_enclosure(
+    enclosures: &[Enclosure],
+    enclosure_type: Option<EnclosureType>,
+) -> Option<&Enclosure> {
+    let enclosures_matching_type = enclosures
+        .iter()
+        .filter(|enclosure| {
+            enclosure_type
+                .map(|enclosure_type| enclosure_type == (*enclosure).into())
+                .unwrap_or(true)
+        })
+        .collect::<Vec<&Enclosure>>();
+
+    enclosures_matching_type
+        .iter()
+        .find(|enclosure| enclosure.is_default)
+        .or_else(|| enclosures_matching_type.first())
+        .copied()
+}
+
+// the path of the downloaded enclosure: the filename template is filled in and appended to the
+// download directory
+pub fn download_path(
+    config: &Config,
+    article: &Article,
+    feed: Option<&Feed>,
+    enclosure: &Enclosure,
+) -> color_eyre::Result<PathBuf> {
+    let directory = PathBuf::from(shellexpand::full(&config.download_dir)?.as_ref());
+
+    let url_file_name = enclosure
+        .url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            percent_encoding::percent_decode_str(segment)
+                .decode_utf8_lossy()
+                .to_string()
+        })
+        .unwrap_or_else(|| "enclosure".to_owned());
+
+    let (stem, extension) = match url_file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem.to_owned(), extension.to_owned()),
+        _ => (
+            url_file_name.to_owned(),
+            enclosure
+                .mime_type
+                .as_deref()
+                .and_then(|mime| mime.split_once('/'))
+                .map(|(_, subtype)| subtype.to_owned())
+                .unwrap_or_else(|| "bin".to_owned()),
+        ),
+    };
+
+    let date = article
+        .date
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d")
+        .to_string();
+
+    Ok(directory.join(fill_in_file_name(
+        &config.download_filename,
+        &[
+            (
+                "{feed}",
+                feed.map(|feed| html_sanitize(&feed.label))
+                    .unwrap_or_default(),
+            ),
+            (
+                "{title}",
+                html_sanitize(article.title.as_deref().unwrap_or("untitled")),
+            ),
+            ("{date}", date),
+            ("{filename}", stem),
+            ("{ext}", extension),
+            (
+                "{type}",
+                <EnclosureType>::from(enclosure).as_ref().to_owned(),
+            ),
+        ],
+    )))
+}
+
+// fills in the placeholders of the template; `/` in the template separates directories while
+// the values are sanitized so that they do not create directories
+fn fill_in_file_name(template: &str, placeholders: &[(&str, String)]) -> PathBuf {
+    template
+        .split('/')
+        .map(|part| {
+            sanitize_file_name(
+                &placeholders
+                    .iter()
+                    .fold(part.to_owned(), |part, (placeholder, value)| {
+                        part.replace(placeholder, &sanitize_file_name(value))
+                    }),
+            )
+        })
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized = name
+        .chars()
+        .map(|char| {
+            if char.is_control() || FORBIDDEN_CHARS.contains(&char) {
+                '_'
+            } else {
+                char
+            }
+        })
+        .collect::<String>();
+
+    // no hidden files, no `..` and no trailing dots or spaces (Windows)
+    let sanitized = sanitized
+        .trim_start_matches('.')
+        .trim_end_matches(['.', ' '])
+        .trim();
+
+    match sanitized.rsplit_once('.') {
+        Some((stem, extension)) if stem.chars().count() > MAX_FILE_NAME_CHARS => format!(
+            "{}.{extension}",
+            stem.chars().take(MAX_FILE_NAME_CHARS).collect::<String>()
+        ),
+        _ => sanitized.chars().take(MAX_FILE_NAME_CHARS).collect(),
+    }
+}
+
+// appends a number to the file name if the file already exists
+fn unique_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    (2..)
+        .map(|number| path.with_file_name(format!("{stem} ({number}){extension}")))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fill_in_file_name() {
+        let placeholders = [
+            ("{feed}", "Tech/News: Daily".to_owned()),
+            ("{title}", "..Episode 1?".to_owned()),
+            ("{ext}", "mp3".to_owned()),
+        ];
+
+        assert_eq!(
+            fill_in_file_name("{feed}/{title}.{ext}", &placeholders),
+            PathBuf::from("Tech_News_ Daily").join("Episode 1_.mp3")
+        );
+        assert_eq!(
+            fill_in_file_name("../{title}.{ext}", &placeholders),
+            PathBuf::from("Episode 1_.mp3")
+        );
+    }
+
+    // serves the given response to the first request and closes the connection
+    async fn serve_once(response: &'static [u8]) -> String {
+        use tokio::io::AsyncReadExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).await;
+            let _ = stream.write_all(response).await;
+        });
+        format!("http://{address}/episode.mp3")
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "eilmeldung-downloads-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_download() {
+        let dir = temp_dir("complete");
+        let url =
+            serve_once(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello")
+                .await;
+        let downloads = Downloads::load(&dir, 1);
+        let article_id = ArticleID::new("article");
+
+        assert!(downloads.enqueue(&url));
+        let path = downloads
+            .download(&Client::new(), &article_id, &url, &dir.join("episode.mp3"))
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+        assert!(downloads.is_downloaded(&url));
+        assert!(downloads.status().is_none());
+        assert_eq!(downloads.files_of(&article_id).len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_download_removes_partial_file() {
+        let dir = temp_dir("failed");
+        // the connection is closed before the announced length is sent
+        let url = serve_once(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\nConnection: close\r\n\r\nincomplete",
+        )
+        .await;
+        let downloads = Downloads::load(&dir, 1);
+        let path = dir.join("media").join("episode.mp3");
+
+        assert!(downloads.enqueue(&url));
+        assert!(
+            downloads
+                .download(&Client::new(), &ArticleID::new("article"), &url, &path)
+                .await
+                .is_err()
+        );
+
+        assert!(!path.exists());
+        assert!(!path.with_added_extension(PARTIAL_EXTENSION).exists());
+        assert!(!downloads.is_downloaded(&url));
+        assert!(downloads.status().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,289 @@
+use futures::StreamExt;
+use kuchikikiki::traits::TendrilSink;
+use log::{debug, info};
+use reqwest::{Client, Url};
+
+use crate::prelude::*;
+
+pub mod prelude {
+    pub use super::{Discovery, FeedCandidate};
+}
+
+// number of entries shown in the preview
+const PREVIEW_ENTRIES: usize = 5;
+
+// number of candidates fetched at the same time
+const CONCURRENT_FETCHES: usize = 4;
+
+#[derive(Clone, Debug)]
+pub struct PreviewEntry {
+    pub title: String,
+    pub published: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// outcome of looking for feeds at the URL given to `feedadd`
+#[derive(Clone, Debug)]
+pub enum Discovery {
+    // the URL (after redirects) is a feed itself
+    Feed(Url),
+    // feeds linked by the web page at the URL
+    Candidates(Vec<FeedCandidate>),
+}
+
+// a feed found at the URL given to `feedadd`, including a preview of its latest entries
+#[derive(Clone, Debug)]
+pub struct FeedCandidate {
+    pub url: Url,
+    pub title: String,
+    // RSS, Atom or JSON Feed (if the feed could be parsed)
+    pub format: Option<&'static str>,
+    pub website: Option<String>,
+    pub entries: Vec<PreviewEntry>,
+    pub entry_count: usize,
+    pub posts_per_week: Option<f64>,
+    // reason why the feed could not be fetched or parsed
+    pub error: Option<String>,
+}
+
+impl FeedCandidate {
+    fn new(url: Url, title: String) -> Self {
+        Self {
+            url,
+            title,
+            format: None,
+            website: None,
+            entries: Vec::default(),
+            entry_count: 0,
+            posts_per_week: None,
+            error: None,
+        }
+    }
+
+    fn with_feed(mut self, feed: feed_rs::model::Feed) -> Self {
+        use feed_rs::model::FeedType as T;
+        self.format = Some(match feed.feed_type {
+            T::Atom => "Atom",
+            T::JSON => "JSON Feed",
+            T::RSS0 | T::RSS1 | T::RSS2 => "RSS",
+        });
+
+        if let Some(title) = feed.title.map(|title| html_sanitize(title.content.trim()))
+            && !title.is_empty()
+        {
+            self.title = title;
+        }
+
+        self.website = feed
+            .links
+            .iter()
+            .find(|link| link.rel.as_deref().is_none_or(|rel| rel == "alternate"))
+            .map(|link| link.href.to_owned());
+
+        let mut entries = feed
+            .entries
+            .into_iter()
+            .map(|entry| PreviewEntry {
+                title: entry
+                    .title
+                    .map(|title| html_sanitize(title.content.trim()))
+                    .unwrap_or_default(),
+                published: entry.published.or(entry.updated),
+            })
+            .collect::<Vec<PreviewEntry>>();
+
+        let dates = entries
+            .iter()
+            .filter_map(|entry| entry.published)
+            .collect::<Vec<_>>();
+        self.posts_per_week = posts_per_week(&dates);
+        self.entry_count = entries.len();
+
+        // newest first; entries without date keep their position at the end
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.published));
+        entries.truncate(PREVIEW_ENTRIES);
+        self.entries = entries;
+
+        self
+    }
+}
+
+// fetches the given URL: if it is a feed, there is nothing to choose from; if it is a web page,
+// the feeds linked via `<link rel="alternate">` are the candidates. All candidates are fetched
+// for the preview.
+pub async fn discover_feeds(url: &Url, client: &Client) -> color_eyre::Result<Discovery> {
+    let response = client.get(url.as_str()).send().await?.error_for_status()?;
+    let base_url = response.url().to_owned();
+    let body = response.bytes().await?;
+
+    if parse_feed(&body, &base_url).is_ok() {
+        debug!("{url} is a feed");
+        return Ok(Discovery::Feed(base_url));
+    }
+
+    let candidates = find_feed_links(&String::from_utf8_lossy(&body), &base_url);
+    info!("found {} feeds linked in {url}", candidates.len());
+
+    if candidates.is_empty() {
+        return Err(color_eyre::eyre::eyre!("no feeds found at {url}"));
+    }
+
+    Ok(Discovery::Candidates(
+        futures::stream::iter(candidates)
+            .map(|candidate| {
+                let client = client.clone();
+                async move { fetch_candidate(candidate, &client).await }
+            })
+            .buffered(CONCURRENT_FETCHES)
+            .collect::<Vec<FeedCandidate>>()
+            .await,
+    ))
+}
+
+async fn fetch_candidate(candidate: FeedCandidate, client: &Client) -> FeedCandidate {
+    let result = async {
+        let response = client
+            .get(candidate.url.as_str())
+            .send()
+            .await?
+            .error_for_status()?;
+        let base_url = response.url().to_owned();
+        let body = response.bytes().await?;
+        parse_feed(&body, &base_url)
+    }
+    .await;
+
+    match result {
+        Ok(feed) => candidate.with_feed(feed),
+        Err(err) => FeedCandidate {
+            error: Some(err.to_string()),
+            ..candidate
+        },
+    }
+}
+
+fn parse_feed(body: &[u8], base_url: &Url) -> color_eyre::Result<feed_rs::model::Feed> {
+    Ok(feed_rs::parser::Builder::new()
+        .base_uri(Some(base_url.as_str()))
+        .build()
+        .parse(body)?)
+}
+
+// feeds announced in the head of a web page
+fn find_feed_links(html: &str, base_url: &Url) -> Vec<FeedCandidate> {
+    let document = kuchikikiki::parse_html().one(html);
+    let Ok(links) = document.select(r#"link[rel~="alternate"]"#) else {
+        return Vec::default();
+    };
+
+    let mut candidates: Vec<FeedCandidate> = Vec::default();
+    for link in links {
+        let attributes = link.attributes.borrow();
+
+        let is_feed = attributes.get("type").is_some_and(|mime_type| {
+            let mime_type = mime_type.to_lowercase();
+            // oEmbed links are alternates as well, but no feeds
+            !mime_type.contains("oembed")
+                && ["rss", "atom", "xml", "json"]
+                    .iter()
+                    .any(|format| mime_type.contains(format))
+        });
+
+        let Some(url) = attributes
+            .get("href")
+            .filter(|_| is_feed)
+            .and_then(|href| base_url.join(href.trim()).ok())
+        else {
+            continue;
+        };
+
+        if candidates.iter().any(|candidate| candidate.url == url) {
+            continue;
+        }
+
+        let title = attributes
+            .get("title")
+            .map(|title| html_sanitize(title.trim()))
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| url.to_string());
+
+        candidates.push(FeedCandidate::new(url, title));
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HTML: &str = r#"<html><head>
+        <link rel="alternate" type="application/rss+xml" title=" Main &amp; more " href="/feed.xml">
+        <link rel="alternate" type="application/atom+xml" href="https://example.com/atom">
+        <link rel="alternate" type="application/json+oembed" href="/oembed">
+        <link rel="alternate" hreflang="de" href="/de/">
+        <link rel="stylesheet" type="text/xml" href="/style.xml">
+        <link rel="alternate" type="application/rss+xml" title="Duplicate" href="feed.xml">
+        <link rel="alternate" type="application/feed+json" title="JSON" href="feed.json">
+    </head><body></body></html>"#;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+        <rss version="2.0"><channel>
+            <title> Example </title>
+            <link>https://example.com/</link>
+            <item><title>Old</title><pubDate>Mon, 01 Jan 2024 10:00:00 GMT</pubDate></item>
+            <item><title>New</title><pubDate>Mon, 08 Jan 2024 10:00:00 GMT</pubDate></item>
+            <item><title>Undated</title></item>
+        </channel></rss>"#;
+
+    #[test]
+    fn test_find_feed_links() {
+        let base_url = Url::parse("https://example.com/blog/").unwrap();
+        let candidates = find_feed_links(HTML, &base_url);
+
+        let found = candidates
+            .iter()
+            .map(|candidate| (candidate.url.as_str(), candidate.title.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("https://example.com/feed.xml", "Main & more"),
+                ("https://example.com/atom", "https://example.com/atom"),
+                ("https://example.com/blog/feed.xml", "Duplicate"),
+                ("https://example.com/blog/feed.json", "JSON"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_feed_links_without_feeds() {
+        let base_url = Url::parse("https://example.com/").unwrap();
+        assert!(find_feed_links("<html><body>nothing</body></html>", &base_url).is_empty());
+    }
+
+    #[test]
+    fn test_with_feed() {
+        let url = Url::parse("https://example.com/feed.xml").unwrap();
+        let feed = parse_feed(RSS.as_bytes(), &url).unwrap();
+        let candidate = FeedCandidate::new(url.to_owned(), url.to_string()).with_feed(feed);
+
+        assert_eq!(candidate.title, "Example");
+        assert_eq!(candidate.format, Some("RSS"));
+        assert_eq!(candidate.website.as_deref(), Some("https://example.com/"));
+        assert_eq!(candidate.entry_count, 3);
+
+        // newest first, undated entries last
+        let titles = candidate
+            .entries
+            .iter()
+            .map(|entry| entry.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["New", "Old", "Undated"]);
+    }
+
+    #[test]
+    fn test_parse_feed_rejects_html() {
+        let url = Url::parse("https://example.com/").unwrap();
+        assert!(parse_feed(HTML.as_bytes(), &url).is_err());
+    }
+}
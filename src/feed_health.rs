@@ -0,0 +1,576 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use futures::StreamExt;
+use log::{debug, warn};
+use news_flash::models::{Feed, FeedID};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{Client, StatusCode, header::LOCATION};
+use serde::{Deserialize, Serialize};
+
+pub mod prelude {
+    pub use super::FeedHealthRecords;
+}
+
+const FEED_HEALTH_FILE: &str = "feed_health.json";
+
+// number of feeds checked at the same time
+const CONCURRENT_CHECKS: usize = 8;
+
+// HTTP status as found in the error messages of news-flash (e.g., `404 Not Found`)
+static HTTP_STATUS_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b([1-5][0-9]{2}) [A-Z]").unwrap());
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FeedHealth {
+    // unix timestamps of the last sync without and with error
+    pub last_success: Option<i64>,
+    pub last_failure: Option<i64>,
+    pub error: Option<String>,
+    pub consecutive_failures: u32,
+    pub http_status: Option<u16>,
+    // location the feed URL redirects to (as found by the last check)
+    pub redirect: Option<String>,
+    // unix timestamp of the last check
+    pub checked: Option<i64>,
+    // the last check got an error response or no response at all
+    #[serde(default)]
+    pub check_failed: bool,
+}
+
+impl FeedHealth {
+    pub fn is_failing(&self) -> bool {
+        self.consecutive_failures > 0
+    }
+
+    // the feed URL did not work when it was checked last
+    pub fn is_dead(&self) -> bool {
+        self.check_failed
+    }
+
+    pub fn is_moved(&self) -> bool {
+        self.redirect.is_some()
+    }
+
+    // failing during syncs or dead when checked
+    pub fn has_error(&self) -> bool {
+        self.is_failing() || self.is_dead()
+    }
+
+    // e.g., `failing (3x), HTTP 404 Not Found`, `dead, HTTP 410 Gone`, `moved` or `ok`
+    pub fn status(&self) -> String {
+        let state = if self.is_failing() {
+            format!("failing ({}x)", self.consecutive_failures)
+        } else if self.is_dead() {
+            "dead".to_owned()
+        } else if self.is_moved() {
+            return "moved".to_owned();
+        } else {
+            return "ok".to_owned();
+        };
+
+        match self.http_status {
+            Some(http_status) => format!("{state}, HTTP {}", http_status_text(http_status)),
+            None => state,
+        }
+    }
+
+    // records the result of checking the feed URL; an error found by the last sync is kept
+    // while the feed is failing
+    fn record_check(&mut self, result: CheckResult, now: i64) {
+        self.checked = Some(now);
+        self.redirect = result.redirect;
+        if result.http_status.is_some() {
+            self.http_status = result.http_status;
+        }
+        self.check_failed = result.error.is_some();
+        match result.error {
+            Some(error) => self.error = Some(error),
+            None if !self.is_failing() => self.error = None,
+            None => {}
+        }
+    }
+
+    // error of a failing or dead feed and the new location of a moved feed
+    pub fn details(&self) -> Vec<String> {
+        [
+            self.error
+                .as_ref()
+                .filter(|_| self.has_error())
+                .map(|error| format!("error: {error}")),
+            self.redirect
+                .as_ref()
+                .map(|redirect| format!("moved to: {redirect}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    pub fn last_success_text(&self, date_format: &str) -> String {
+        self.last_success
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            .map(|date| {
+                date.with_timezone(&chrono::Local)
+                    .format(date_format)
+                    .to_string()
+            })
+            .unwrap_or("never".to_owned())
+    }
+}
+
+// result of requesting the feed URL without following redirects
+#[derive(Debug)]
+struct CheckResult {
+    http_status: Option<u16>,
+    redirect: Option<String>,
+    error: Option<String>,
+}
+
+impl CheckResult {
+    // client and server errors make a feed dead, redirects make it moved
+    fn from_response(feed_url: &str, status: StatusCode, redirect: Option<String>) -> Self {
+        Self {
+            http_status: Some(status.as_u16()),
+            redirect: redirect.filter(|_| status.is_redirection()),
+            error: (status.is_client_error() || status.is_server_error())
+                .then(|| format!("{feed_url}: {status}")),
+        }
+    }
+}
+
+pub struct FeedHealthRecords {
+    path: PathBuf,
+    records: HashMap<String, FeedHealth>,
+}
+
+impl FeedHealthRecords {
+    pub fn load(state_dir: &Path) -> Self {
+        let path = state_dir.join(FEED_HEALTH_FILE);
+
+        let records = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                warn!("unable to parse feed health in {path:?}: {err}");
+                Default::default()
+            }),
+            Err(err) => {
+                debug!("no feed health loaded from {path:?}: {err}");
+                Default::default()
+            }
+        };
+
+        Self { path, records }
+    }
+
+    pub fn get(&self, feed_id: &FeedID) -> Option<&FeedHealth> {
+        self.records.get(feed_id.as_str())
+    }
+
+    // feeds with their health: failing and dead feeds first (most failures first), then moved
+    // feeds, then all others
+    pub fn report<'a>(&self, feeds: &'a [Feed]) -> Vec<(&'a Feed, FeedHealth)> {
+        let mut report = feeds
+            .iter()
+            .map(|feed| (feed, self.get(&feed.feed_id).cloned().unwrap_or_default()))
+            .collect::<Vec<(&Feed, FeedHealth)>>();
+
+        report.sort_by(|(feed_1, health_1), (feed_2, health_2)| {
+            health_2
+                .has_error()
+                .cmp(&health_1.has_error())
+                .then(
+                    health_2
+                        .consecutive_failures
+                        .cmp(&health_1.consecutive_failures),
+                )
+                .then(health_2.is_moved().cmp(&health_1.is_moved()))
+                .then(
+                    feed_1
+                        .label
+                        .to_lowercase()
+                        .cmp(&feed_2.label.to_lowercase()),
+                )
+        });
+
+        report
+    }
+
+    // records the outcome of the last sync as reported by news-flash
+    pub fn update_from_sync(&mut self, feeds: &[Feed]) -> color_eyre::Result<()> {
+        let now = chrono::Utc::now().timestamp();
+
+        for feed in feeds {
+            self.record(feed, now);
+        }
+
+        // feeds which have been removed
+        self.records.retain(|feed_id, _| {
+            feeds
+                .iter()
+                .any(|feed| feed.feed_id.as_str() == feed_id.as_str())
+        });
+
+        self.save()
+    }
+
+    // records the outcome of fetching a single feed
+    pub fn update_from_fetch(&mut self, feed: &Feed) -> color_eyre::Result<()> {
+        self.record(feed, chrono::Utc::now().timestamp());
+        self.save()
+    }
+
+    fn record(&mut self, feed: &Feed, now: i64) {
+        let feed_health = self
+            .records
+            .entry(feed.feed_id.as_str().to_owned())
+            .or_default();
+
+        if feed.error_count > 0 {
+            feed_health.last_failure = Some(now);
+            feed_health.consecutive_failures = feed.error_count as u32;
+            feed_health.error = feed.error_message.clone();
+            if let Some(http_status) = feed.error_message.as_deref().and_then(parse_http_status) {
+                feed_health.http_status = Some(http_status);
+            }
+        } else {
+            feed_health.last_success = Some(now);
+            feed_health.consecutive_failures = 0;
+            feed_health.check_failed = false;
+            feed_health.error = None;
+        }
+    }
+
+    // requests the URLs of the feeds (with a client not following redirects) to find moved and
+    // dead feeds
+    pub async fn check(&mut self, feeds: &[Feed], client: &Client) -> color_eyre::Result<()> {
+        let now = chrono::Utc::now().timestamp();
+
+        let feed_urls = feeds
+            .iter()
+            .filter_map(|feed| {
+                feed.feed_url
+                    .as_ref()
+                    .map(|feed_url| (feed.feed_id.clone(), feed_url.to_string()))
+            })
+            .collect::<Vec<(FeedID, String)>>();
+
+        let results = futures::stream::iter(feed_urls)
+            .map(|(feed_id, feed_url)| {
+                let client = client.clone();
+                async move {
+                    let result = Self::check_feed_url(&client, &feed_url).await;
+                    (feed_id, result)
+                }
+            })
+            .buffer_unordered(CONCURRENT_CHECKS)
+            .collect::<Vec<(FeedID, CheckResult)>>()
+            .await;
+
+        for (feed_id, result) in results {
+            self.records
+                .entry(feed_id.as_str().to_owned())
+                .or_default()
+                .record_check(result, now);
+        }
+
+        self.save()
+    }
+
+    async fn check_feed_url(client: &Client, feed_url: &str) -> CheckResult {
+        let response = match client.get(feed_url).send().await {
+            Ok(response) => response,
+            Err(err) => {
+                return CheckResult {
+                    http_status: None,
+                    redirect: None,
+                    error: Some(format!("{feed_url}: {err}")),
+                };
+            }
+        };
+
+        let redirect = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| response.url().join(location).ok())
+            .map(|location| location.to_string());
+
+        CheckResult::from_response(feed_url, response.status(), redirect)
+    }
+
+    fn save(&self) -> color_eyre::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(&self.records)?)?;
+        Ok(())
+    }
+}
+
+// extracts the HTTP status from an error message of news-flash
+fn parse_http_status(message: &str) -> Option<u16> {
+    HTTP_STATUS_REGEX
+        .captures(message)
+        .and_then(|captures| captures[1].parse().ok())
+}
+
+pub fn http_status_text(http_status: u16) -> String {
+    StatusCode::from_u16(http_status)
+        .map(|status| status.to_string())
+        .unwrap_or_else(|_| http_status.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn feed(id: &str, label: &str, error_count: i32, error_message: Option<&str>) -> Feed {
+        Feed {
+            feed_id: FeedID::new(id),
+            label: label.to_owned(),
+            website: None,
+            feed_url: None,
+            icon_url: None,
+            error_count,
+            error_message: error_message.map(str::to_owned),
+        }
+    }
+
+    fn temp_state_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "eilmeldung-feed-health-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_parse_http_status() {
+        assert_eq!(parse_http_status("HTTP status 404 Not Found"), Some(404));
+        assert_eq!(
+            parse_http_status("request failed: 503 Service Unavailable"),
+            Some(503)
+        );
+        assert_eq!(parse_http_status("connection refused"), None);
+        assert_eq!(parse_http_status("got 1234 Items"), None);
+        assert_eq!(parse_http_status("error 404 not found"), None);
+    }
+
+    #[test]
+    fn test_update_from_sync() {
+        let dir = temp_state_dir("sync");
+        let mut records = FeedHealthRecords::load(&dir);
+
+        records
+            .update_from_sync(&[
+                feed("ok", "Ok", 0, None),
+                feed("failing", "Failing", 3, Some("410 Gone")),
+                feed("removed", "Removed", 0, None),
+            ])
+            .unwrap();
+
+        let ok = records.get(&FeedID::new("ok")).unwrap();
+        assert!(!ok.is_failing());
+        assert!(ok.last_success.is_some());
+
+        let failing = records.get(&FeedID::new("failing")).unwrap();
+        assert_eq!(failing.consecutive_failures, 3);
+        assert_eq!(failing.http_status, Some(410));
+        assert_eq!(failing.error.as_deref(), Some("410 Gone"));
+        assert!(failing.last_success.is_none());
+
+        // recovered feeds are reset, removed feeds are dropped
+        records
+            .update_from_sync(&[
+                feed("ok", "Ok", 0, None),
+                feed("failing", "Failing", 0, None),
+            ])
+            .unwrap();
+        let failing = records.get(&FeedID::new("failing")).unwrap();
+        assert!(!failing.is_failing());
+        assert!(failing.error.is_none());
+        assert!(failing.last_success.is_some());
+        assert!(records.get(&FeedID::new("removed")).is_none());
+
+        let reloaded = FeedHealthRecords::load(&dir);
+        assert!(reloaded.get(&FeedID::new("ok")).is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_update_from_fetch_only_touches_fetched_feed() {
+        let dir = temp_state_dir("fetch");
+        let mut records = FeedHealthRecords::load(&dir);
+        records
+            .update_from_sync(&[feed("a", "A", 0, None), feed("b", "B", 0, None)])
+            .unwrap();
+        records.records.get_mut("a").unwrap().last_success = Some(0);
+
+        records
+            .update_from_fetch(&feed("b", "B", 1, Some("500 Internal Server Error")))
+            .unwrap();
+
+        assert_eq!(
+            records.get(&FeedID::new("a")).unwrap().last_success,
+            Some(0)
+        );
+        let b = records.get(&FeedID::new("b")).unwrap();
+        assert_eq!(b.consecutive_failures, 1);
+        assert_eq!(b.http_status, Some(500));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_report_ordering() {
+        let mut records = FeedHealthRecords::load(&temp_state_dir("report"));
+        let failing_once = FeedHealth {
+            consecutive_failures: 1,
+            ..Default::default()
+        };
+        let failing_often = FeedHealth {
+            consecutive_failures: 5,
+            ..Default::default()
+        };
+        let moved = FeedHealth {
+            redirect: Some("https://example.com/new".to_owned()),
+            ..Default::default()
+        };
+        records
+            .records
+            .insert("failing_once".to_owned(), failing_once);
+        records
+            .records
+            .insert("failing_often".to_owned(), failing_often);
+        records.records.insert("moved".to_owned(), moved);
+        records.records.insert(
+            "dead".to_owned(),
+            FeedHealth {
+                check_failed: true,
+                ..Default::default()
+            },
+        );
+
+        let feeds = [
+            feed("ok_b", "beta", 0, None),
+            feed("moved", "Moved", 0, None),
+            feed("ok_a", "Alpha", 0, None),
+            feed("failing_once", "Failing once", 0, None),
+            feed("failing_often", "Failing often", 0, None),
+            feed("dead", "Dead", 0, None),
+        ];
+
+        let order = records
+            .report(&feeds)
+            .into_iter()
+            .map(|(feed, _)| feed.feed_id.as_str().to_owned())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            order,
+            vec![
+                "failing_often",
+                "failing_once",
+                "dead",
+                "moved",
+                "ok_a",
+                "ok_b"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_result() {
+        let url = "https://example.com/feed";
+
+        let dead = CheckResult::from_response(url, StatusCode::NOT_FOUND, None);
+        assert_eq!(dead.http_status, Some(404));
+        assert!(dead.error.is_some());
+
+        let down = CheckResult::from_response(url, StatusCode::SERVICE_UNAVAILABLE, None);
+        assert!(down.error.is_some());
+
+        let moved = CheckResult::from_response(
+            url,
+            StatusCode::MOVED_PERMANENTLY,
+            Some("https://example.com/new".to_owned()),
+        );
+        assert!(moved.error.is_none());
+        assert_eq!(moved.redirect.as_deref(), Some("https://example.com/new"));
+
+        // a location header without redirection is ignored
+        let ok = CheckResult::from_response(url, StatusCode::OK, Some("https://x".to_owned()));
+        assert!(ok.error.is_none());
+        assert!(ok.redirect.is_none());
+    }
+
+    #[test]
+    fn test_record_check() {
+        let url = "https://example.com/feed";
+        let mut health = FeedHealth::default();
+
+        health.record_check(CheckResult::from_response(url, StatusCode::GONE, None), 1);
+        assert!(health.is_dead());
+        assert!(health.has_error());
+        assert_eq!(health.status(), "dead, HTTP 410 Gone");
+        assert_eq!(
+            health.details(),
+            vec!["error: https://example.com/feed: 410 Gone"]
+        );
+
+        // a later successful check clears the error
+        health.record_check(CheckResult::from_response(url, StatusCode::OK, None), 2);
+        assert!(!health.has_error());
+        assert!(health.error.is_none());
+        assert_eq!(health.status(), "ok");
+        assert_eq!(health.checked, Some(2));
+
+        // the error of a failing feed is kept
+        let mut health = FeedHealth {
+            consecutive_failures: 2,
+            error: Some("sync error".to_owned()),
+            ..Default::default()
+        };
+        health.record_check(CheckResult::from_response(url, StatusCode::OK, None), 3);
+        assert_eq!(health.error.as_deref(), Some("sync error"));
+
+        // no response at all
+        health.record_check(
+            CheckResult {
+                http_status: None,
+                redirect: None,
+                error: Some("connection refused".to_owned()),
+            },
+            4,
+        );
+        assert!(health.is_dead());
+    }
+
+    #[test]
+    fn test_status_and_details() {
+        let health = FeedHealth {
+            consecutive_failures: 2,
+            http_status: Some(404),
+            error: Some("gone".to_owned()),
+            redirect: Some("https://example.com".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(health.status(), "failing (2x), HTTP 404 Not Found");
+        assert_eq!(
+            health.details(),
+            vec!["error: gone", "moved to: https://example.com"]
+        );
+        assert_eq!(health.last_success_text("%Y"), "never");
+
+        let recovered = FeedHealth {
+            error: Some("gone".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(recovered.status(), "ok");
+        assert!(recovered.details().is_empty());
+    }
+}
@@ -2,7 +2,6 @@ use std::{cmp::Ordering, fmt::Display};
 
 use crate::prelude::*;
 
-use arboard::Clipboard;
 use color_eyre::eyre::eyre;
 use inquire::{
     Confirm, Password, Select, Text, min_length,
@@ -349,7 +348,7 @@ Create a new *Application* under your account *Settings* with the following conf
         match choice {
             URL_OPEN_IN_BROWSER => webbrowser::open(url.as_str())?,
             URL_COPY_TO_CLIPBOARD => {
-                Clipboard::new()?.set_text(url.to_string())?;
+                copy_to_clipboard(url.as_str(), ClipboardBackend::Auto)?;
             }
             _ => {}
         }
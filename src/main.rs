@@ -1,25 +1,38 @@
+mod accounts;
 mod cli;
+mod clipboard;
 mod config;
 mod connectivity;
+mod downloads;
+mod feed_discovery;
+mod feed_health;
 mod input;
 mod logging;
 mod login;
 mod messages;
 mod newsflash_utils;
+mod pending;
+mod pipe;
 mod query;
+mod reading_stats;
 mod ui;
 mod undo;
 mod utils;
 
-use std::{path::Path, sync::Arc, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use clap::Parser;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use news_flash::{NewsFlash, models::LoginData};
 use ratatui::crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
 };
+use reqwest::Client;
 use tokio::{sync::mpsc::unbounded_channel, task::spawn_blocking};
 
 mod prelude;
@@ -31,13 +44,13 @@ async fn main() -> color_eyre::Result<()> {
 
     let eilmeldung_config_dir = resolve_eilmeldung_config_dir(&cli_args);
 
-    let news_flash_config_dir = cli_args
+    let news_flash_config_dir_base = cli_args
         .news_flash_config_dir()
         .as_ref()
         .map(Path::new)
         .unwrap_or(PROJECT_DIRS.config_dir());
 
-    let state_dir = cli_args
+    let state_dir_base = cli_args
         .news_flash_state_dir()
         .as_ref()
         .map(Path::new)
@@ -48,29 +61,141 @@ async fn main() -> color_eyre::Result<()> {
     debug!("Error handling and logging initialized");
 
     info!("eilmeldung config dir: {eilmeldung_config_dir:?}");
-    info!("newsflash config dir: {news_flash_config_dir:?}");
-    info!("state dir: {state_dir:?}");
+    info!("newsflash config dir: {news_flash_config_dir_base:?}");
+    info!("state dir: {state_dir_base:?}");
+
+    // profile given on the command line, switched profiles replace it
+    let mut profile = cli_args
+        .profile()
+        .clone()
+        .filter(|profile| profile != DEFAULT_PROFILE);
+
+    loop {
+        info!("Loading configuration (profile: {profile:?})");
+        let config = Arc::new(load_config(&eilmeldung_config_dir, profile.as_deref())?);
+
+        let (news_flash_config_dir, state_dir) = resolve_profile_dirs(
+            &config,
+            profile.as_deref(),
+            news_flash_config_dir_base,
+            state_dir_base,
+        )?;
+
+        info!("profile newsflash config dir: {news_flash_config_dir:?}");
+        info!("profile state dir: {state_dir:?}");
+
+        let client = build_client(
+            Duration::from_secs(config.network_timeout_seconds),
+            &config.network,
+        )?;
+
+        let news_flash =
+            init_news_flash(&config, &news_flash_config_dir, &state_dir, &client).await?;
+
+        // execute CLI actions -> if true, exit after execution (CLI only)
+        if execute_cli_actions(&config, &cli_args, &news_flash, &client, &state_dir).await? {
+            return Ok(());
+        }
+
+        let unified_news_flashes =
+            load_unified_news_flashes(&config, news_flash_config_dir_base, state_dir_base, &client)
+                .await?;
+
+        match run_app(config, news_flash, unified_news_flashes, client, &state_dir).await? {
+            Some(next_profile) => {
+                info!("switching to profile {next_profile}");
+                profile = (next_profile != DEFAULT_PROFILE).then_some(next_profile);
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+// returns the news-flash config dir and the state dir of the profile
+fn resolve_profile_dirs(
+    config: &Config,
+    profile: Option<&str>,
+    news_flash_config_dir: &Path,
+    state_dir: &Path,
+) -> color_eyre::Result<(PathBuf, PathBuf)> {
+    Ok(
+        match profile.filter(|profile| *profile != DEFAULT_PROFILE) {
+            Some(profile) => {
+                let profile_config = config.profiles.get(profile).cloned().unwrap_or_default();
+                (
+                    profile_config.resolve_news_flash_config_dir(profile, news_flash_config_dir)?,
+                    profile_config.resolve_state_dir(profile, state_dir)?,
+                )
+            }
+            None => (news_flash_config_dir.to_path_buf(), state_dir.to_path_buf()),
+        },
+    )
+}
+
+async fn refresh_login(news_flash: &NewsFlash, client: &Client) {
+    if let Some(login_data) = news_flash.get_login_data().await {
+        info!("Re-logging in to refresh session");
+        if let Err(e) = news_flash.login(login_data, client).await {
+            error!("Failed to re-login: {}. Session may have expired.", e);
+        }
+    }
+}
+
+// loads the accounts of the profiles shown alongside the active one; profiles without login data
+// are skipped
+async fn load_unified_news_flashes(
+    config: &Config,
+    news_flash_config_dir: &Path,
+    state_dir: &Path,
+    client: &Client,
+) -> color_eyre::Result<Vec<(String, NewsFlash)>> {
+    let active_profile = config.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+    let mut unified_news_flashes = Vec::new();
+
+    for profile in config.unified_profiles.iter() {
+        if profile == active_profile || unified_news_flashes.iter().any(|(name, _)| name == profile)
+        {
+            continue;
+        }
+
+        let (profile_news_flash_config_dir, profile_state_dir) =
+            resolve_profile_dirs(config, Some(profile), news_flash_config_dir, state_dir)?;
+
+        info!("Loading account of profile {profile}");
+        match NewsFlash::builder()
+            .config_dir(&profile_news_flash_config_dir)
+            .data_dir(&profile_state_dir)
+            .try_load()
+        {
+            Ok(news_flash) => {
+                refresh_login(&news_flash, client).await;
+                unified_news_flashes.push((profile.to_owned(), news_flash));
+            }
+            Err(err) => {
+                warn!("unable to load account of profile {profile} (not logged in?): {err}");
+            }
+        }
+    }
 
-    info!("Loading configuration");
-    let config = Arc::new(load_config(&eilmeldung_config_dir)?);
+    Ok(unified_news_flashes)
+}
 
+async fn init_news_flash(
+    config: &Config,
+    news_flash_config_dir: &Path,
+    state_dir: &Path,
+    client: &Client,
+) -> color_eyre::Result<NewsFlash> {
     info!("Initializing NewsFlash");
     let news_flash_attempt = NewsFlash::builder()
         .config_dir(news_flash_config_dir)
         .data_dir(state_dir)
         .try_load();
 
-    let client = build_client(Duration::from_secs(config.network_timeout_seconds))?;
-
-    let news_flash = match news_flash_attempt {
+    Ok(match news_flash_attempt {
         Ok(news_flash) => {
             // Re-login to refresh session token
-            if let Some(login_data) = news_flash.get_login_data().await {
-                info!("Re-logging in to refresh session");
-                if let Err(e) = news_flash.login(login_data, &client).await {
-                    error!("Failed to re-login: {}. Session may have expired.", e);
-                }
-            }
+            refresh_login(&news_flash, client).await;
             news_flash
         }
         Err(_) => {
@@ -106,30 +231,39 @@ async fn main() -> color_eyre::Result<()> {
                     .login_and_initial_sync(
                         news_flash.as_ref().unwrap(),
                         login_data.as_ref().unwrap(),
-                        &client,
+                        client,
                     )
                     .await?;
             }
             news_flash.unwrap()
         }
-    };
-
-    // execute CLI actions -> if true, exit after execution (CLI only)
-    if execute_cli_actions(&config, &cli_args, &news_flash, &client).await? {
-        return Ok(());
-    }
+    })
+}
 
+// runs the application until it is quit; returns the profile to switch to, if requested
+async fn run_app(
+    config: Arc<Config>,
+    news_flash: NewsFlash,
+    unified_news_flashes: Vec<(String, NewsFlash)>,
+    client: Client,
+    state_dir: &Path,
+) -> color_eyre::Result<Option<String>> {
     // setup of things we need in the app
     let (message_sender, message_receiver) = unbounded_channel::<Message>();
     let input_reader_message_sender = message_sender.clone();
     let news_flash_utils = Arc::new(NewsFlashUtils::new(
         news_flash,
+        unified_news_flashes,
         client,
         config.clone(),
         message_sender.clone(),
+        state_dir,
     ));
-    let connectivity_monitor =
-        ConnectivityMonitor::new(news_flash_utils.clone(), message_sender.clone());
+    let connectivity_monitor = ConnectivityMonitor::new(
+        config.clone(),
+        news_flash_utils.clone(),
+        message_sender.clone(),
+    );
 
     // create the main app
     let app = App::new(config.clone(), news_flash_utils.clone(), message_sender);
@@ -143,17 +277,22 @@ async fn main() -> color_eyre::Result<()> {
     }
 
     // startup task which reads the crossterm events
-    let _input_reader_handle = spawn_blocking(move || {
+    let input_reader_handle = spawn_blocking(move || {
         if let Err(err) = input_reader(input_reader_message_sender) {
             error!("input reader got an error: {err}");
         }
     });
 
-    let _connecitivty_monitor_handle = connectivity_monitor.spawn()?;
+    let connectivity_monitor_handle = connectivity_monitor.spawn()?;
 
     info!("Starting application main loop");
     let result = app.run(message_receiver, terminal).await;
 
+    // the input reader stops once the message channel is closed, the next profile
+    // starts its own one
+    connectivity_monitor_handle.abort();
+    let _ = input_reader_handle.await;
+
     if config.mouse_support {
         info!("Disabling mouse capture");
         let _ = execute!(std::io::stdout(), DisableMouseCapture);
@@ -10,7 +10,8 @@ use news_flash::models::{Enclosure, Url};
 pub mod prelude {
     pub use super::parse::CommandParseError;
     pub use super::{
-        ActionScope, Command, CommandSequence, EnclosureType, Panel, PastePosition, PipeTarget,
+        ActionScope, Command, CommandSequence, EnclosureType, Panel, PastePosition, PipeOptions,
+        PipeTarget, ShareOptions,
     };
 }
 
@@ -92,6 +93,14 @@ pub enum ActionScope {
 
     #[strum(serialize = "all", message = "all", detailed_message = "all items")]
     All,
+
+    #[strum(
+        serialize = "group",
+        message = "group",
+        detailed_message = "all articles in the group of the selected item (article list)"
+    )]
+    Group,
+
     #[strum(
         message = "query",
         detailed_message = "all articles defined by a query"
@@ -109,6 +118,7 @@ impl FromStr for ActionScope {
             "%" | "all" => Ok(S::All),
             "above" => Ok(S::Above),
             "below" => Ok(S::Below),
+            "group" => Ok(S::Group),
             _ => Ok(S::Query(ArticleQuery::from_str(s)?)),
         }
     }
@@ -131,6 +141,7 @@ impl Display for ActionScope {
             S::All => write!(f, "all articles")?,
             S::Below => write!(f, "current article and all below")?,
             S::Above => write!(f, "current article and all above")?,
+            S::Group => write!(f, "articles in current group")?,
             S::Query(query) => write!(f, "all articles matching {}", query.query_string())?,
         };
         Ok(())
@@ -159,6 +170,13 @@ pub enum PipeTarget {
 
     #[strum(serialize = "md", message = "md", detailed_message = "markdown")]
     Markdown,
+
+    #[strum(
+        serialize = "json",
+        message = "json",
+        detailed_message = "JSON with title, feed, author, URL, date and content"
+    )]
+    Json,
 }
 
 impl Display for PipeTarget {
@@ -167,6 +185,125 @@ impl Display for PipeTarget {
     }
 }
 
+// options of a single pipe invocation given as `--sh`, `--cache`, `--timeout=<seconds>`,
+// `--each` and `--join`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PipeOptions {
+    // the command is executed by `sh -c`
+    pub shell: bool,
+    // the output is cached for the article
+    pub cache: bool,
+    // overrides the configured timeout; 0 means no timeout
+    pub timeout_seconds: Option<u32>,
+    // the command is executed for each article in scope
+    pub each: bool,
+    // the command is executed once with one document of all articles in scope
+    pub join: bool,
+}
+
+impl PipeOptions {
+    pub fn is_option(word: &str) -> bool {
+        word.starts_with("--")
+    }
+
+    // the command expects a scope before the command
+    pub fn is_scoped(&self) -> bool {
+        self.each || self.join
+    }
+
+    pub fn set(&mut self, option: &str) -> Result<(), CommandParseError> {
+        match option {
+            "--sh" => self.shell = true,
+            "--cache" => self.cache = true,
+            "--each" if !self.join => self.each = true,
+            "--join" if !self.each => self.join = true,
+            _ => {
+                let seconds = option
+                    .strip_prefix("--timeout=")
+                    .ok_or(CommandParseError::PipeOptionExpected)?;
+                self.timeout_seconds = Some(
+                    seconds
+                        .parse()
+                        .map_err(|_| CommandParseError::PipeOptionExpected)?,
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for PipeOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut options = Vec::new();
+        if self.shell {
+            options.push("--sh".to_owned());
+        }
+        if self.cache {
+            options.push("--cache".to_owned());
+        }
+        if let Some(seconds) = self.timeout_seconds {
+            options.push(format!("--timeout={seconds}"));
+        }
+        if self.each {
+            options.push("--each".to_owned());
+        }
+        if self.join {
+            options.push("--join".to_owned());
+        }
+        write!(f, "{}", options.join(" "))
+    }
+}
+
+// options of `share` given as `--each` and `--join`; without them, the clipboard receives all
+// articles at once and the other targets each article
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ShareOptions {
+    // each article is shared on its own
+    pub each: bool,
+    // the articles are shared at once
+    pub join: bool,
+    // opening many browser tabs has been confirmed
+    pub confirmed: bool,
+}
+
+impl ShareOptions {
+    pub fn is_option(word: &str) -> bool {
+        word.starts_with("--")
+    }
+
+    pub fn set(&mut self, option: &str) -> Result<(), CommandParseError> {
+        match option {
+            "--each" if !self.join => self.each = true,
+            "--join" if !self.each => self.join = true,
+            _ => return Err(CommandParseError::ShareOptionExpected),
+        }
+        Ok(())
+    }
+
+    // the mode given by the options or the default mode of the target
+    pub fn mode(&self, target: &ShareTarget) -> ShareMode {
+        if self.each {
+            ShareMode::Each
+        } else if self.join {
+            ShareMode::Join
+        } else {
+            target.default_mode()
+        }
+    }
+}
+
+impl Display for ShareOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.each {
+            write!(f, "--each")
+        } else if self.join {
+            write!(f, "--join")
+        } else {
+            Ok(())
+        }
+    }
+}
+
 #[derive(
     Debug,
     Clone,
@@ -562,6 +699,20 @@ pub enum Command {
     )]
     Undo,
 
+    #[strum(
+        serialize = "pending",
+        message = "pending",
+        detailed_message = "shows the operations made while offline which are not yet synced (all)"
+    )]
+    PendingShow,
+
+    #[strum(
+        serialize = "pendingdrop",
+        message = "pendingdrop <id>|all",
+        detailed_message = "drops a pending operation so that it is not replayed (all)"
+    )]
+    PendingDrop(Option<u64>),
+
     // article list commands
     #[strum(
         serialize = "show",
@@ -577,6 +728,27 @@ pub enum Command {
     )]
     ArticleOpenEnclosure(Option<EnclosureType>),
 
+    #[strum(
+        serialize = "download",
+        message = "download [<enclosure type>] [<scope>]",
+        detailed_message = "downloads the default enclosure or the enclosure of the given type of the articles in scope (article list, article content)"
+    )]
+    ArticleDownload(Option<EnclosureType>, ActionScope),
+
+    #[strum(
+        serialize = "queue",
+        message = "queue [<scope>]",
+        detailed_message = "adds the audio/video enclosures of the articles in scope to the playback queue or shows the queue (all)"
+    )]
+    Queue(Option<ActionScope>),
+
+    #[strum(
+        serialize = "queueremove",
+        message = "queueremove <position>",
+        detailed_message = "removes the entry at the position from the playback queue (all)"
+    )]
+    QueueRemove(usize),
+
     #[strum(
         serialize = "scrape",
         message = "scrape",
@@ -595,21 +767,21 @@ pub enum Command {
     #[strum(
         serialize = "search",
         message = "search <search term>",
-        detailed_message = "search item by search term (feed list)"
+        detailed_message = "search item by search term (feed list, article content)"
     )]
     Search(Option<SearchTerm>),
 
     #[strum(
         serialize = "searchnext",
         message = "searchnext",
-        detailed_message = "search next matching item (feed list, article list)"
+        detailed_message = "search next matching item (feed list, article list, article content)"
     )]
     SearchNext,
 
     #[strum(
         serialize = "searchprev",
         message = "searchprev",
-        detailed_message = "search previous matching item (feed list, article list)"
+        detailed_message = "search previous matching item (feed list, article list, article content)"
     )]
     SearchPrevious,
 
@@ -662,12 +834,81 @@ pub enum Command {
     )]
     ContentShareHint(String, String),
 
+    #[strum(
+        serialize = "toc",
+        message = "toc",
+        detailed_message = "shows the headings of the article for jumping to them (article content)"
+    )]
+    ContentTableOfContents,
+
+    #[strum(
+        serialize = "heading",
+        message = "heading <number>",
+        detailed_message = "jumps to the heading with the given number (article content)"
+    )]
+    ContentGotoHeading(usize),
+
+    #[strum(
+        serialize = "nextheading",
+        message = "nextheading",
+        detailed_message = "jumps to the next heading (article content)"
+    )]
+    ContentNextHeading,
+
+    #[strum(
+        serialize = "prevheading",
+        message = "prevheading",
+        detailed_message = "jumps to the previous heading (article content)"
+    )]
+    ContentPreviousHeading,
+
     #[strum(
         serialize = "pipe",
-        message = "pipe <in> <out> <command>",
-        detailed_message = "pipes the article content through an exernal command (article content)"
+        message = "pipe [--sh] [--cache] [--timeout=<seconds>] [--each|--join] <in> <out> [<scope> --] <command>",
+        detailed_message = "pipes the article content through an exernal command; with `--each` or `--join` the articles in scope are piped and the output is shown in a popup (article content, article list)"
+    )]
+    Pipe(
+        PipeOptions,
+        PipeTarget,
+        PipeTarget,
+        Option<ActionScope>,
+        String,
+    ),
+
+    #[strum(
+        serialize = "pipecancel",
+        message = "pipecancel",
+        detailed_message = "kills the running pipe commands (all)"
+    )]
+    PipeCancel,
+
+    #[strum(
+        serialize = "feedhealth",
+        message = "feedhealth [check]",
+        detailed_message = "shows errors of the feeds during the last syncs; with `check` all feed URLs are checked for errors and redirects first (all)"
+    )]
+    FeedHealth(bool),
+
+    #[strum(
+        serialize = "stats",
+        message = "stats",
+        detailed_message = "shows reading statistics: articles read per day, week, feed and category, feeds never read and tag usage (all)"
+    )]
+    ShowStats,
+
+    #[strum(
+        serialize = "feedinfo",
+        message = "feedinfo",
+        detailed_message = "shows statistics and metadata of the selected feed; select a field to yank it (feed list)"
+    )]
+    FeedListFeedInfo,
+
+    #[strum(
+        serialize = "copy",
+        message = "copy <text>",
+        detailed_message = "copies the given text to the clipboard (all)"
     )]
-    Pipe(PipeTarget, PipeTarget, String),
+    ClipboardCopy(String),
 
     #[strum(
         serialize = "sortfeeds",
@@ -690,6 +931,27 @@ pub enum Command {
     )]
     ArticleListSortClear,
 
+    #[strum(
+        serialize = "groupby",
+        message = "groupby <grouping>",
+        detailed_message = "groups the articles by day, week, feed, category or tag; `none` removes the groups (article list)"
+    )]
+    ArticleListGroupBy(GroupBy),
+
+    #[strum(
+        serialize = "grouptoggle",
+        message = "grouptoggle [all]",
+        detailed_message = "collapses or expands the group of the selected article; with `all` all groups are collapsed or expanded (article list)"
+    )]
+    ArticleListGroupToggle(bool),
+
+    #[strum(
+        serialize = "layout",
+        message = "layout [table|cards]",
+        detailed_message = "shows the articles as table or as cards; without layout the layout is toggled (article list)"
+    )]
+    ArticleListLayout(Option<ListLayout>),
+
     #[strum(
         serialize = "query",
         message = "query <article query>",
@@ -699,10 +961,10 @@ pub enum Command {
 
     #[strum(
         serialize = "share",
-        message = "share <target>",
-        detailed_message = "shares title and url with target (article list, article content)"
+        message = "share [--each|--join] <target> [<scope>]",
+        detailed_message = "shares the current article or the articles in scope with target; with `--each` each article is shared on its own, with `--join` all articles at once (article list, article content)"
     )]
-    ArticleShare(String),
+    ArticleShare(ShareOptions, String, Option<ActionScope>),
 
     #[strum(
         serialize = "importopml",
@@ -718,6 +980,20 @@ pub enum Command {
     )]
     ExportOpml(String),
 
+    #[strum(
+        serialize = "profile",
+        message = "profile [<name>]",
+        detailed_message = "switch to the profile with the given name or show the available profiles (all)"
+    )]
+    SwitchProfile(Option<String>),
+
+    #[strum(
+        serialize = "offline",
+        message = "offline [on|off]",
+        detailed_message = "switches offline mode on/off or toggles it: no reachability checks and no automatic syncs (all)"
+    )]
+    OfflineMode(Option<bool>),
+
     // application
     #[strum(
         serialize = "quit",
@@ -858,10 +1134,42 @@ impl Display for Command {
             ArticleOpenEnclosure(Some(enclosure_type)) => {
                 write!(f, "open {enclosure_type} enclosure")
             }
-            ArticleShare(share_target) => write!(f, "share article to {share_target}"),
-            Pipe(in_target, out_target, command) => {
+            ArticleDownload(None, action_scope) => {
+                write!(f, "download default enclosure of {action_scope}")
+            }
+            ArticleDownload(Some(enclosure_type), action_scope) => {
+                write!(f, "download {enclosure_type} enclosure of {action_scope}")
+            }
+            Queue(None) => write!(f, "show playback queue"),
+            Queue(Some(action_scope)) => write!(f, "queue enclosures of {action_scope}"),
+            QueueRemove(position) => write!(f, "remove entry {position} from playback queue"),
+            ArticleShare(_, share_target, None) => write!(f, "share article to {share_target}"),
+            ArticleShare(options, share_target, Some(action_scope))
+                if options.each || options.join =>
+            {
+                write!(f, "share {action_scope} to {share_target} ({options})")
+            }
+            ArticleShare(_, share_target, Some(action_scope)) => {
+                write!(f, "share {action_scope} to {share_target}")
+            }
+            Pipe(options, in_target, out_target, None, command)
+                if options == PipeOptions::default() =>
+            {
                 write!(f, "pipe {in_target} to {out_target} using {command}")
             }
+            Pipe(options, in_target, out_target, None, command) => {
+                write!(
+                    f,
+                    "pipe {in_target} to {out_target} using {command} ({options})"
+                )
+            }
+            Pipe(options, in_target, out_target, Some(action_scope), command) => {
+                write!(
+                    f,
+                    "pipe {in_target} of {action_scope} to {out_target} using {command} ({options})"
+                )
+            }
+            PipeCancel => write!(f, "cancel pipe commands"),
 
             ArticleCurrentScrape => write!(f, "scrape content"),
 
@@ -902,8 +1210,20 @@ impl Display for Command {
             ArticleListSort(sort_order) => write!(f, "sort article list by {}", sort_order),
             ArticleListSortReverse => write!(f, "reverse current sort order"),
             ArticleListSortClear => write!(f, "clear current sort order"),
+            ArticleListGroupBy(GroupBy::None) => write!(f, "remove article groups"),
+            ArticleListGroupBy(group_by) => write!(f, "group articles by {}", group_by.as_ref()),
+            ArticleListGroupToggle(false) => write!(f, "collapse or expand current group"),
+            ArticleListGroupToggle(true) => write!(f, "collapse or expand all groups"),
+            ArticleListLayout(None) => write!(f, "toggle article list layout"),
+            ArticleListLayout(Some(layout)) => {
+                write!(f, "show articles as {}", layout.as_ref())
+            }
             ContentFollowHint(hint) => write!(f, "open URL behind {hint}"),
             ContentShareHint(target, hint) => write!(f, "share URL behind {hint} to {target}"),
+            ContentTableOfContents => write!(f, "show table of contents"),
+            ContentGotoHeading(number) => write!(f, "jump to heading {number}"),
+            ContentNextHeading => write!(f, "jump to next heading"),
+            ContentPreviousHeading => write!(f, "jump to previous heading"),
 
             FeedListSync => write!(f, "sync all"),
             ActionSetRead(action_scope) => {
@@ -927,10 +1247,23 @@ impl Display for Command {
                 "logout from provider, NOTE: this will remove ALL LOCAL DATA!"
             ),
             HelpInput => write!(f, "show help on input mappings"),
+            SwitchProfile(None) => write!(f, "show available profiles"),
+            SwitchProfile(Some(profile)) => write!(f, "switch to profile {profile}"),
+            FeedHealth(false) => write!(f, "show feed health"),
+            FeedHealth(true) => write!(f, "check feed health"),
+            ShowStats => write!(f, "show reading statistics"),
+            FeedListFeedInfo => write!(f, "show feed info"),
+            ClipboardCopy(text) => write!(f, "copy {text} to clipboard"),
+            OfflineMode(None) => write!(f, "toggle offline mode"),
+            OfflineMode(Some(true)) => write!(f, "switch offline mode on"),
+            OfflineMode(Some(false)) => write!(f, "switch offline mode off"),
             TagAdd(tag_title, _) => {
                 write!(f, "add tag #{}", tag_title)
             }
             Undo => write!(f, "undos the last read/unread operation"),
+            PendingShow => write!(f, "show pending operations"),
+            PendingDrop(None) => write!(f, "drop all pending operations"),
+            PendingDrop(Some(id)) => write!(f, "drop pending operation {id}"),
             CommandConfirm(command) => write!(f, "{}?", command),
             In(panel, command) => write!(f, "{command} in {panel}"),
         }
@@ -953,6 +1286,7 @@ impl Command {
             | C::TagAdd(..)
             | C::ImportOpml(..)
             | C::ExportOpml(..)
+            | C::FeedHealth(true)
             | C::Logout(..) => true,
             C::CommandConfirm(command) => command.is_async(),
             C::In(_, command) => command.is_async(),
@@ -41,9 +41,15 @@ pub enum CommandParseError {
     #[error("expecting share target")]
     ShareTargetExpected,
 
+    #[error("expecting share option `--each` or `--join`")]
+    ShareOptionExpected,
+
     #[error("expecting pipe target")]
     PipeTargetExpected,
 
+    #[error("expecting pipe option `--sh`, `--cache` or `--timeout=<seconds>`")]
+    PipeOptionExpected,
+
     #[error("shell command expected")]
     ShellCommandExpected,
 
@@ -56,6 +62,12 @@ pub enum CommandParseError {
     #[error("enclosure type expected")]
     EnclosureTypeExpected,
 
+    #[error("expecting grouping")]
+    GroupByExpected,
+
+    #[error("expecting layout")]
+    LayoutExpected,
+
     #[error("expecting `NOW` as parameter for confirmation")]
     ConfirmationExpected,
 
@@ -65,6 +77,12 @@ pub enum CommandParseError {
     #[error("URL hint expected")]
     UrlHintExpected,
 
+    #[error("expecting profile name")]
+    ProfileNameExpected,
+
+    #[error("expecting a number")]
+    NumberExpected(#[from] std::num::ParseIntError),
+
     #[error("expecting a word")]
     WordExpected(String),
 
@@ -245,6 +263,8 @@ impl Command {
                 C::FeedListCategoryAdd(expect_something(args, "expecting category name")?)
             }
 
+            C::ClipboardCopy(..) => C::ClipboardCopy(expect_something(args, "expecting text")?),
+
             C::FeedListRenameEntity(..) => {
                 C::FeedListRenameEntity(expect_something(args, "expecting new name")?)
             }
@@ -291,6 +311,26 @@ impl Command {
                 )),
             },
 
+            C::ArticleDownload(..) => {
+                // the enclosure type is optional, the rest is the scope
+                let (word, tail) = args.as_deref().map(split_off_first).unzip();
+                match word.as_deref().map(EnclosureType::from_str) {
+                    Some(Ok(enclosure_type)) => C::ArticleDownload(
+                        Some(enclosure_type),
+                        ActionScope::from_option_string(tail.flatten().as_deref())?,
+                    ),
+                    _ => {
+                        C::ArticleDownload(None, ActionScope::from_option_string(args.as_deref())?)
+                    }
+                }
+            }
+
+            C::Queue(..) => C::Queue(args.as_deref().map(ActionScope::from_str).transpose()?),
+
+            C::QueueRemove(..) => {
+                C::QueueRemove(expect_from_str(&mut args, "expecting queue position")?)
+            }
+
             C::ArticleListSearch(..) => C::ArticleListSearch(ArticleQuery::from_str(
                 expect_something(args, "expecting article query")
                     .map_err(|_| {
@@ -302,10 +342,14 @@ impl Command {
                     .as_str(),
             )?),
 
-            C::Search(..) => C::Search(Some(
-                expect_from_str::<SearchTerm>(&mut args, "expecting search term")
-                    .map_err(|_| E::SearchTermExpected)?,
-            )),
+            C::Search(..) => match args {
+                None if !eager => C::Search(None),
+                None => return Err(E::SearchTermExpected),
+                // the whole argument is the search term as quoted strings may contain spaces
+                Some(args) => C::Search(Some(
+                    SearchTerm::from_str(&args).map_err(|_| E::SearchTermExpected)?,
+                )),
+            },
 
             filter @ (C::ArticleListFilterSet(..) | C::ArticleListFilterSetSticky(..)) => {
                 let query = ArticleQuery::from_str(
@@ -358,22 +402,59 @@ impl Command {
                 C::ContentShareHint(share_target, hint)
             }
 
-            C::ArticleShare(..) => C::ArticleShare(
-                expect_word(&mut args, "expecting share target")
-                    .map_err(|_| E::ShareTargetExpected)?,
-            ),
+            C::ContentGotoHeading(..) => {
+                C::ContentGotoHeading(expect_from_str(&mut args, "expecting heading number")?)
+            }
+
+            C::ArticleShare(..) => {
+                let mut options = ShareOptions::default();
+                while args
+                    .as_deref()
+                    .is_some_and(|args| ShareOptions::is_option(&split_off_first(args).0))
+                {
+                    options.set(&expect_word(&mut args, "expecting share option")?)?;
+                }
+
+                C::ArticleShare(
+                    options,
+                    expect_word(&mut args, "expecting share target")
+                        .map_err(|_| E::ShareTargetExpected)?,
+                    args.as_deref().map(ActionScope::from_str).transpose()?,
+                )
+            }
 
             C::Pipe(..) => {
+                let mut options = PipeOptions::default();
+                while args
+                    .as_deref()
+                    .is_some_and(|args| PipeOptions::is_option(&split_off_first(args).0))
+                {
+                    options.set(&expect_word(&mut args, "expecting pipe option")?)?;
+                }
+
                 let in_target = expect_from_str(&mut args, "in pipe target expected")
                     .map_err(|_| E::PipeTargetExpected)?;
 
                 let out_target = expect_from_str(&mut args, "out pipe target expected")
                     .map_err(|_| E::PipeTargetExpected)?;
 
+                // with `--each` or `--join` the scope is terminated by ` -- `
+                let action_scope = match args.as_deref() {
+                    Some(args_str) if options.is_scoped() => {
+                        let (scope, command) =
+                            args_str.split_once(" -- ").ok_or(E::ActionScopeExpected)?;
+                        let action_scope = ActionScope::from_str(scope.trim())?;
+                        args = Some(command.to_owned());
+                        Some(action_scope)
+                    }
+                    None if options.is_scoped() => return Err(E::ActionScopeExpected),
+                    _ => None,
+                };
+
                 let command = expect_something(args, "expecting shell command")
                     .map_err(|_| E::ShellCommandExpected)?;
 
-                C::Pipe(in_target, out_target, command)
+                C::Pipe(options, in_target, out_target, action_scope, command)
             }
 
             cmd @ (C::ExportOpml(..) | C::ImportOpml(..)) => {
@@ -397,6 +478,79 @@ impl Command {
                 C::Logout(word)
             }
 
+            C::SwitchProfile(..) => match args {
+                None => C::SwitchProfile(None),
+                Some(_) => {
+                    let profile = expect_word(&mut args, "expecting profile name")
+                        .map_err(|_| E::ProfileNameExpected)?;
+                    expect_nothing(args)?;
+                    C::SwitchProfile(Some(profile))
+                }
+            },
+
+            C::ArticleListGroupBy(..) => {
+                let group_by = expect_from_str::<GroupBy>(&mut args, "expecting grouping")
+                    .map_err(|_| E::GroupByExpected)?;
+                expect_nothing(args)?;
+                C::ArticleListGroupBy(group_by)
+            }
+
+            C::ArticleListGroupToggle(..) => match args {
+                None => C::ArticleListGroupToggle(false),
+                Some(_) => {
+                    let word = expect_word(&mut args, "expecting `all`")?;
+                    expect_nothing(args)?;
+                    if word != "all" {
+                        return Err(E::WordExpected("expecting `all`".to_owned()));
+                    }
+                    C::ArticleListGroupToggle(true)
+                }
+            },
+
+            C::ArticleListLayout(..) => match args {
+                None => C::ArticleListLayout(None),
+                Some(_) => {
+                    let layout = expect_from_str::<ListLayout>(&mut args, "expecting layout")
+                        .map_err(|_| E::LayoutExpected)?;
+                    expect_nothing(args)?;
+                    C::ArticleListLayout(Some(layout))
+                }
+            },
+
+            C::FeedHealth(..) => match args {
+                None => C::FeedHealth(false),
+                Some(_) => {
+                    let word = expect_word(&mut args, "expecting `check`")?;
+                    expect_nothing(args)?;
+                    if word != "check" {
+                        return Err(E::WordExpected("expecting `check`".to_owned()));
+                    }
+                    C::FeedHealth(true)
+                }
+            },
+
+            C::OfflineMode(..) => match args {
+                None => C::OfflineMode(None),
+                Some(_) => {
+                    let word = expect_word(&mut args, "expecting `on` or `off`")?;
+                    expect_nothing(args)?;
+                    match word.as_str() {
+                        "on" => C::OfflineMode(Some(true)),
+                        "off" => C::OfflineMode(Some(false)),
+                        _ => return Err(E::WordExpected("expecting `on` or `off`".to_owned())),
+                    }
+                }
+            },
+
+            C::PendingDrop(..) => {
+                let word = expect_word(&mut args, "expecting id of pending operation or `all`")?;
+                expect_nothing(args)?;
+                match word.as_str() {
+                    "all" => C::PendingDrop(None),
+                    id => C::PendingDrop(Some(id.parse()?)),
+                }
+            }
+
             C::CommandLineOpen(..) => C::CommandLineOpen(args),
 
             command_without_args => command_without_args,
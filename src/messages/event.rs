@@ -1,8 +1,8 @@
-use std::{collections::HashMap, process::ExitStatus};
+use std::{collections::HashMap, path::PathBuf, process::ExitStatus};
 
 use news_flash::{
     error::NewsFlashError,
-    models::{ArticleID, Category, FatArticle, Feed, FeedID, Tag, Thumbnail},
+    models::{ArticleID, Category, CategoryID, FatArticle, Feed, FeedID, Tag, Thumbnail, Url},
 };
 use ratatui::{
     crossterm::event::{KeyEvent, MouseEvent},
@@ -35,8 +35,12 @@ pub enum Event {
     AsyncArticleFatFetch,
     AsyncArticleFatFetchFinished(FatArticle),
 
+    AsyncContentImageFetchFinished(String, Option<Vec<u8>>),
+    AsyncCardThumbnailFetchFinished(String, Option<Vec<u8>>),
+
     AsyncPipeArticle,
     AsyncPipeArticleFinished(ArticleID, ExitStatus, Option<String>, Option<String>),
+    AsyncPipeArticlesFinished(Vec<PipeResult>),
 
     AsyncArticlesMark,
     AsyncArticlesMarkFinished,
@@ -53,6 +57,9 @@ pub enum Event {
     AsyncTagRemove,
     AsyncTagRemoveFinished,
 
+    AsyncFeedDiscovery,
+    AsyncFeedDiscoveryFinished(Url, Option<String>, Option<CategoryID>, Vec<FeedCandidate>), // url, name, category, candidates (empty if the url is added directly)
+
     AsyncFeedAdd,
     AsyncFeedAddFinished(Feed),
 
@@ -112,6 +119,26 @@ pub enum Event {
     AsyncLogout,
     AsyncLogoutFinished,
 
+    AsyncReplayPending,
+    AsyncReplayPendingFinished(usize, usize, usize), // replayed, skipped, failed
+
+    AsyncFeedHealthCheck,
+    AsyncFeedHealthCheckFinished,
+
+    AsyncShareWebhook,
+    AsyncShareWebhookFinished(String, usize, reqwest::StatusCode), // share target, number of shared items, last response status
+
+    AsyncDownloadsQueued(usize),
+    AsyncDownloadFinished(ArticleID, PathBuf),
+    AsyncDownloadFailed(String, String), // URL, error
+
+    // playback queue; entries are identified by the playlist entry ID of the player
+    PlaybackQueueAppend(Vec<QueueEntry>),
+    PlaybackStarted(u64),
+    PlaybackFinished(u64),
+    PlayerExited,
+    PlayerFailed(String),
+
     Tick, // general tick for animations and regular updates
 
     // messaging/status
@@ -120,8 +147,19 @@ pub enum Event {
     // help popup
     ShowHelpPopup(String, Text<'static>),
     ShowModalHelpPopup(String, Text<'static>),
+    // modal popup in which each line of the text selects the command at the same index
+    ShowSelectionPopup(String, Text<'static>, Vec<Command>),
     HideHelpPopup,
 
+    // dialog for adding one of the discovered feeds: name, candidates, categories (with their
+    // path) and the index of the preselected category
+    ShowFeedAddPopup(
+        Option<String>,
+        Vec<FeedCandidate>,
+        Vec<(Option<CategoryID>, String)>,
+        usize,
+    ),
+
     // application
     ApplicationStarted,
     ApplicationStateChanged(AppState),
@@ -179,7 +217,26 @@ impl Event {
                 | AsyncCategorySetReadFinished
                 | AsyncTagSetReadFinished
                 | AsyncArticlesSetReadFinished
-                | AsyncImportOpmlFinished,
+                | AsyncImportOpmlFinished
+                | AsyncReplayPendingFinished(..)
+                | AsyncDownloadFinished(..),
+        )
+    }
+
+    // whether articles may have been added or removed (and the duplicates must be searched again)
+    pub fn changed_articles(&self) -> bool {
+        use Event::*;
+
+        matches!(
+            self,
+            AsyncSyncFinished(_)
+                | AsyncFeedAddFinished(_)
+                | AsyncFeedFetchFinished(..)
+                | AsyncFeedRemoveFinished
+                | AsyncCategoryRemoveFinished
+                | AsyncSetOfflineFinished(_)
+                | AsyncImportOpmlFinished
+                | AsyncReplayPendingFinished(..),
         )
     }
 }
@@ -1,46 +1,103 @@
 use crate::{messages::event::AsyncOperationError, prelude::*};
 use std::{
-    collections::HashMap, error::Error, hash::Hash, process::Stdio, str::FromStr, sync::Arc,
+    collections::HashMap,
+    error::Error,
+    hash::Hash,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
     time::Duration,
 };
 
-use htmd::HtmlToMarkdown;
 use news_flash::{
     NewsFlash,
     error::NewsFlashError,
     models::{
-        ArticleFilter, ArticleID, Category, CategoryID, CategoryMapping, Feed, FeedID, FeedMapping,
-        Marked, Read, Tag, TagID, Url,
+        Article, ArticleFilter, ArticleID, Category, CategoryID, CategoryMapping, Feed, FeedID,
+        FeedMapping, Marked, Read, Tag, TagID, Url,
     },
 };
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use ratatui::{
     style::Color,
     text::{Line, Span},
 };
-use reqwest::{Client, ClientBuilder};
-use tokio::sync::{Mutex, RwLock, mpsc::UnboundedSender};
+use reqwest::{
+    Certificate, Client, ClientBuilder, NoProxy, Proxy,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use tokio::sync::{Mutex, RwLock, RwLockWriteGuard, mpsc::UnboundedSender, watch};
+
+// downloads are aborted after this time even if data is still received
+const DOWNLOAD_TIMEOUT_SECONDS: u64 = 6 * 60 * 60;
 
 #[derive(Clone)]
 pub struct NewsFlashUtils {
     pub news_flash_lock: Arc<RwLock<NewsFlash>>,
     client_lock: Arc<RwLock<Client>>,
     undo_stack_lock: Arc<RwLock<Vec<UndoOperation>>>,
+    pending_operations_lock: Arc<RwLock<PendingOperations>>,
+    feed_health_lock: Arc<RwLock<FeedHealthRecords>>,
+    reading_events: Arc<ReadingEvents>,
+    downloads: Arc<Downloads>,
+    accounts: Arc<Accounts>,
+    // offline mode set by the user: no reachability checks and no automatic syncs
+    offline_mode: Arc<AtomicBool>,
     config: Arc<Config>,
     command_sender: UnboundedSender<Message>,
+    state_dir: PathBuf,
 
     async_operation_mutex: Arc<Mutex<()>>,
+    // pipe commands run outside of the async operation mutex; increasing the generation kills them
+    running_pipes: Arc<AtomicUsize>,
+    pipe_generation: Arc<watch::Sender<u64>>,
 }
 
 // macro to wrap news flash async calls into spawns and send messages at the beginning and end
+// state shared by the async operations
+struct OperationContext<'a> {
+    undo_stack: RwLockWriteGuard<'a, Vec<UndoOperation>>,
+    accounts: &'a Accounts,
+    pending: &'a RwLock<PendingOperations>,
+    reading_events: &'a ReadingEvents,
+}
+
+// counts a running pipe command until dropped, also if the task fails or panics
+struct RunningPipe(Arc<AtomicUsize>);
+
+impl RunningPipe {
+    fn new(running_pipes: &Arc<AtomicUsize>) -> Self {
+        running_pipes.fetch_add(1, Ordering::Relaxed);
+        Self(running_pipes.clone())
+    }
+}
+
+impl Drop for RunningPipe {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl OperationContext<'_> {
+    // the operation has already been applied, so a failing journal only loses the pending entry
+    async fn push_pending(&self, action: PendingAction) {
+        if let Err(err) = self.pending.write().await.push(action) {
+            error!("unable to save pending operation: {err}");
+        }
+    }
+}
+
 macro_rules! gen_async_call {
     {
         method_name: $method_name:ident,
         params: ($($param:ident: $param_type:ty),*),
         news_flash_var: $news_flash_var:ident,
         client_var: $client_var:ident,
-        undo_stack_var: $undo_stack_var:ident,
+        context_var: $context_var:ident,
         start_event: $start_event:expr,
         operation: $operation:stmt,
         success_event: $success_event:expr,
@@ -49,6 +106,9 @@ macro_rules! gen_async_call {
             let news_flash_lock = self.news_flash_lock.clone();
             let client_lock = self.client_lock.clone();
             let undo_stack_lock = self.undo_stack_lock.clone();
+            let accounts = self.accounts.clone();
+            let pending_operations_lock = self.pending_operations_lock.clone();
+            let reading_events = self.reading_events.clone();
             let command_sender = self.command_sender.clone();
             let async_operation_mutex = self.async_operation_mutex.clone();
 
@@ -61,7 +121,13 @@ macro_rules! gen_async_call {
 
                     let $news_flash_var = news_flash_lock.read().await;
                     let $client_var = client_lock.read().await;
-                    let mut $undo_stack_var = undo_stack_lock.write().await;
+                    #[allow(unused_mut)]
+                    let mut $context_var = OperationContext {
+                        undo_stack: undo_stack_lock.write().await,
+                        accounts: &accounts,
+                        pending: &pending_operations_lock,
+                        reading_events: &reading_events,
+                    };
 
                     $operation
 
@@ -81,44 +147,231 @@ macro_rules! gen_async_call {
 
 }
 
-pub fn build_client(timeout: Duration) -> color_eyre::Result<Client> {
-    let user_agent = format!(
-        "eilmeldung/{} (RSS reader; +https://github.com/christo-auer/eilmeldung",
-        env!("CARGO_PKG_VERSION")
-    );
-    let builder = ClientBuilder::new()
+pub fn build_client(timeout: Duration, network: &NetworkConfig) -> color_eyre::Result<Client> {
+    Ok(client_builder(timeout, network)?.build()?)
+}
+
+pub fn client_builder(
+    timeout: Duration,
+    network: &NetworkConfig,
+) -> color_eyre::Result<ClientBuilder> {
+    let user_agent = network.user_agent.clone().unwrap_or_else(|| {
+        format!(
+            "eilmeldung/{} (RSS reader; +https://github.com/christo-auer/eilmeldung",
+            env!("CARGO_PKG_VERSION")
+        )
+    });
+
+    let mut builder = ClientBuilder::new()
         .user_agent(user_agent.as_str())
         .hickory_dns(false)
         .gzip(true)
         .brotli(true)
         .timeout(timeout);
 
-    Ok(builder.build()?)
+    if let Some(proxy) = network.proxy.as_ref() {
+        let no_proxy = NoProxy::from_string(&network.no_proxy.join(","));
+        builder = builder.proxy(
+            Proxy::all(proxy)
+                .map_err(|err| color_eyre::eyre::eyre!("invalid proxy {proxy}: {err}"))?
+                .no_proxy(no_proxy),
+        );
+    }
+
+    if !network.headers.is_empty() {
+        let mut headers = HeaderMap::new();
+        for (name, value) in network.headers.iter() {
+            headers.insert(
+                HeaderName::from_str(name)
+                    .map_err(|err| color_eyre::eyre::eyre!("invalid header name {name}: {err}"))?,
+                HeaderValue::from_str(value).map_err(|err| {
+                    color_eyre::eyre::eyre!("invalid value for header {name}: {err}")
+                })?,
+            );
+        }
+        builder = builder.default_headers(headers);
+    }
+
+    for path in network.ca_certificates.iter() {
+        let path = shellexpand::full(path)?;
+        let pem = std::fs::read(path.as_ref()).map_err(|err| {
+            color_eyre::eyre::eyre!("unable to read CA certificates {path}: {err}")
+        })?;
+        for certificate in Certificate::from_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
 }
 
-#[rustfmt::skip]        
+#[rustfmt::skip]
 impl NewsFlashUtils {
     pub fn new(
         news_flash: NewsFlash,
+        unified_news_flashes: Vec<(String, NewsFlash)>,
         client: Client,
         config: Arc<Config>,
         command_sender: UnboundedSender<Message>,
+        state_dir: &Path,
     ) -> Self {
         debug!("Creating NewsFlashUtils");
+        let news_flash_lock = Arc::new(RwLock::new(news_flash));
+        let accounts = Accounts::new(
+            config.profile.as_deref().unwrap_or(DEFAULT_PROFILE),
+            news_flash_lock.clone(),
+            unified_news_flashes,
+        );
+        let downloads = Arc::new(Downloads::load(state_dir, config.download_max_concurrent));
+        let reading_events = ReadingEvents::new(state_dir);
+        // loading prunes the old events, so that the journal does not grow without bounds
+        reading_events.load();
         Self {
-            news_flash_lock: Arc::new(RwLock::new(news_flash)),
+            news_flash_lock,
+            accounts: Arc::new(accounts),
             client_lock: Arc::new(RwLock::new(client)),
             config,
             command_sender,
+            state_dir: state_dir.to_path_buf(),
             undo_stack_lock: Default::default(),
+            pending_operations_lock: Arc::new(RwLock::new(PendingOperations::load(state_dir))),
+            feed_health_lock: Arc::new(RwLock::new(FeedHealthRecords::load(state_dir))),
+            reading_events: Arc::new(reading_events),
+            downloads,
+            offline_mode: Default::default(),
             async_operation_mutex: Arc::new(Mutex::new(())),
+            running_pipes: Default::default(),
+            pipe_generation: Arc::new(watch::Sender::new(0)),
         }
     }
 
+    pub fn state_dir(&self) -> &Path {
+        &self.state_dir
+    }
+
+    pub fn accounts(&self) -> &Accounts {
+        &self.accounts
+    }
+
+    pub fn pending_operations(&self) -> &RwLock<PendingOperations> {
+        &self.pending_operations_lock
+    }
+
+    pub fn feed_health(&self) -> &RwLock<FeedHealthRecords> {
+        &self.feed_health_lock
+    }
+
+    pub fn reading_events(&self) -> &ReadingEvents {
+        &self.reading_events
+    }
+
+    pub fn downloads(&self) -> &Downloads {
+        &self.downloads
+    }
+
+    // records the errors news-flash reported for the feeds during the last sync
+    pub async fn update_feed_health(&self) -> color_eyre::Result<()> {
+        let (feeds, _) = self.accounts.get_feeds().await?;
+        self.feed_health_lock.write().await.update_from_sync(&feeds)
+    }
+
+    // records the error news-flash reported for the feed after fetching it
+    pub async fn update_feed_health_of(&self, feed_id: &FeedID) -> color_eyre::Result<()> {
+        let (feeds, _) = self.accounts.get_feeds().await?;
+        match feeds.iter().find(|feed| &feed.feed_id == feed_id) {
+            Some(feed) => self.feed_health_lock.write().await.update_from_fetch(feed),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_offline_mode(&self) -> bool {
+        self.offline_mode.load(Ordering::Relaxed)
+    }
+
+    pub fn set_offline_mode(&self, offline_mode: bool) {
+        self.offline_mode.store(offline_mode, Ordering::Relaxed);
+    }
+
+    // returns the account of the feed mapping and the mapping as known to the account
+    fn local_feed_mapping(
+        accounts: &Accounts,
+        feed_mapping: FeedMapping,
+    ) -> color_eyre::Result<(&Account, FeedMapping)> {
+        let (account, feed_id) = accounts.owner(&feed_mapping.feed_id);
+        let (category_account, category_id) = accounts.owner(&feed_mapping.category_id);
+        if !std::ptr::eq(account, category_account) {
+            return Err(color_eyre::eyre::eyre!("feeds cannot be moved to another account"));
+        }
+        Ok((account, FeedMapping { feed_id, category_id, ..feed_mapping }))
+    }
+
+    // replays a pending operation against the freshly synced state: articles which are gone or
+    // already in the target state are skipped; returns false if there was nothing left to do
+    async fn replay_pending_action(
+        accounts: &Accounts,
+        client: &Client,
+        action: &PendingAction,
+    ) -> color_eyre::Result<bool> {
+        let mut applied = false;
+        for (account, article_ids) in accounts.group(action.article_ids()) {
+            let news_flash = account.news_flash().await;
+            if news_flash.is_offline() {
+                return Err(NewsFlashError::Offline.into());
+            }
+
+            let articles = news_flash.get_articles(ArticleFilter::ids(article_ids))?;
+
+            match action {
+                PendingAction::SetRead(_, read) => {
+                    let article_ids = articles.into_iter()
+                        .filter(|article| action.changes(article.unread, article.marked, false))
+                        .map(|article| article.article_id)
+                        .collect::<Vec<ArticleID>>();
+                    if !article_ids.is_empty() {
+                        news_flash.set_article_read(&article_ids, *read, client).await?;
+                        applied = true;
+                    }
+                }
+                PendingAction::SetMarked(_, marked) => {
+                    let article_ids = articles.into_iter()
+                        .filter(|article| action.changes(article.unread, article.marked, false))
+                        .map(|article| article.article_id)
+                        .collect::<Vec<ArticleID>>();
+                    if !article_ids.is_empty() {
+                        news_flash.set_article_marked(&article_ids, *marked, client).await?;
+                        applied = true;
+                    }
+                }
+                PendingAction::AddTag(_, tag_id) | PendingAction::RemoveTag(_, tag_id) => {
+                    let Some(account_tag_id) = accounts.tag_in_account(tag_id, account).await? else {
+                        continue;
+                    };
+                    let (_, taggings) = news_flash.get_tags()?;
+                    let add = matches!(action, PendingAction::AddTag(..));
+                    for article in articles {
+                        let is_tagged = taggings.iter().any(|tagging| {
+                            tagging.article_id == article.article_id && tagging.tag_id == account_tag_id
+                        });
+                        if !action.changes(article.unread, article.marked, is_tagged) {
+                            continue;
+                        }
+                        if add {
+                            news_flash.tag_article(&article.article_id, &account_tag_id, client).await?;
+                        } else {
+                            news_flash.untag_article(&article.article_id, &account_tag_id, client).await?;
+                        }
+                        applied = true;
+                    }
+                }
+            }
+        }
+        Ok(applied)
+    }
+
     pub async fn rebuild_client(&self) -> color_eyre::Result<()>{
         info!("rebuilding reqwest client");
         let mut client = self.client_lock.write().await;
-        *client = build_client(Duration::from_secs(self.config.network_timeout_seconds))?;
+        *client = build_client(Duration::from_secs(self.config.network_timeout_seconds), &self.config.network)?;
         Ok(())
     }
 
@@ -147,56 +400,112 @@ impl NewsFlashUtils {
 
     // for polling
     pub fn is_async_operation_running(&self) -> bool {
-        self.async_operation_mutex.try_lock().is_err()
+        self.async_operation_mutex.try_lock().is_err() || self.running_pipes.load(Ordering::Relaxed) > 0
+    }
+
+    // kills all running pipe commands and returns their number
+    pub fn cancel_pipes(&self) -> usize {
+        self.pipe_generation.send_modify(|generation| *generation += 1);
+        self.running_pipes.load(Ordering::Relaxed)
     }
 
     gen_async_call! {
         method_name: set_offline,
         params: (offline: bool),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncSetOffline,
-        operation: news_flash.set_offline(offline, &client).await?,
+        operation: for account in context.accounts.iter() {
+            account.news_flash().await.set_offline(offline, &client).await?;
+        },
         success_event: Event::AsyncSetOfflineFinished(offline),
     }
 
     gen_async_call! {
         method_name: sync,
         params: (),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncSync,
-        operation: let new_articles = news_flash.sync(&client, Default::default()).await?,
+        operation: let new_articles = {
+            let mut new_articles = HashMap::new();
+            for account in context.accounts.iter() {
+                let news_flash = account.news_flash().await;
+                new_articles.extend(news_flash.sync(&client, Default::default()).await?.qualify(account));
+            }
+            new_articles
+        },
         success_event: Event::AsyncSyncFinished(new_articles),
     }
 
+    gen_async_call! {
+        method_name: replay_pending_operations,
+        params: (),
+        news_flash_var: _news_flash,
+        client_var: client,
+        context_var: context,
+        start_event: Event::AsyncReplayPending,
+        operation: let (replayed, skipped, failed) = {
+            // sync first so that the pending operations are compared with the current state
+            for account in context.accounts.iter() {
+                account.news_flash().await.sync(&client, Default::default()).await?;
+            }
+
+            let operations = context.pending.read().await.operations().to_vec();
+            let (mut replayed, mut skipped, mut failed) = (0, 0, 0);
+            let mut done = Vec::new();
+            for operation in operations {
+                match Self::replay_pending_action(context.accounts, &client, &operation.action).await {
+                    Ok(true) => replayed += 1,
+                    Ok(false) => skipped += 1,
+                    Err(err) => {
+                        error!("unable to replay pending operation {} ({}): {err}", operation.id, operation.action);
+                        failed += 1;
+                        continue;
+                    }
+                }
+                done.push(operation.id);
+            }
+            context.pending.write().await.remove(&done)?;
+            (replayed, skipped, failed)
+        },
+        success_event: Event::AsyncReplayPendingFinished(replayed, skipped, failed),
+    }
+
     gen_async_call! {
         method_name: fetch_thumbnail,
         params: (article_id: ArticleID),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncArticleThumbnailFetch,
-        operation: let thumbnail = news_flash.get_article_thumbnail(&article_id, &client).await?,
+        operation: let thumbnail = {
+            let (account, article_id) = context.accounts.owner(&article_id);
+            let news_flash = account.news_flash().await;
+            news_flash.get_article_thumbnail(&article_id, &client).await?.qualify(account)
+        },
         success_event: Event::AsyncArticleThumbnailFetchFinished(thumbnail),
     }
 
     gen_async_call! {
         method_name: fetch_fat_article,
         params: (article_id: ArticleID),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncArticleFatFetch,
         operation: let fat_article = {
             // Temporarily redirect stderr to suppress libxml xpath errors that would mess up the TUI
             let _stderr_redirect = crate::utils::prelude::StderrRedirect::new();
             
+            let (account, article_id) = context.accounts.owner(&article_id);
+            let news_flash = account.news_flash().await;
             news_flash
                 .scrap_content_article(&article_id, &client)
                 .await?
+                .qualify(account)
         },
         success_event: Event::AsyncArticleFatFetchFinished(fat_article),
     }
@@ -204,15 +513,34 @@ impl NewsFlashUtils {
     gen_async_call! {
         method_name: set_article_status,
         params: (article_ids: Vec<ArticleID>, read: Read, undoable: bool),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: undo_stack,
+        context_var: context,
         start_event: Event::AsyncArticlesSetRead,
         operation: {
-            news_flash.set_article_read(&article_ids, read, &client).await?;
+            // articles which are actually read now (for the reading statistics)
+            let read_articles = match read {
+                Read::Read => context.accounts.get_articles(ArticleFilter {
+                    ids: Some(article_ids.clone()),
+                    unread: Some(Read::Unread),
+                    ..Default::default()
+                }).await?,
+                Read::Unread => Vec::new(),
+            };
+
+            for (account, account_article_ids) in context.accounts.group(&article_ids) {
+                let news_flash = account.news_flash().await;
+                news_flash.set_article_read(&account_article_ids, read, &client).await?;
+                if news_flash.is_offline() {
+                    context.push_pending(PendingAction::SetRead(account_article_ids.qualify(account), read)).await;
+                }
+            }
+            if let Err(err) = context.reading_events.record_read(&read_articles) {
+                error!("unable to record reading events: {err}");
+            }
 
             if undoable {
-                undo_stack.push(UndoOperation::ChangeRead(article_ids, read));
+                context.undo_stack.push(UndoOperation::ChangeRead(article_ids, read));
             }
         },
 
@@ -222,15 +550,21 @@ impl NewsFlashUtils {
     gen_async_call! {
         method_name: set_article_marked,
         params: (article_ids: Vec<ArticleID>, marked: Marked, undoable: bool),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: undo_stack,
+        context_var: context,
         start_event: Event::AsyncArticlesSetRead,
         operation: {
-            news_flash.set_article_marked(&article_ids, marked, &client).await?;
+            for (account, account_article_ids) in context.accounts.group(&article_ids) {
+                let news_flash = account.news_flash().await;
+                news_flash.set_article_marked(&account_article_ids, marked, &client).await?;
+                if news_flash.is_offline() {
+                    context.push_pending(PendingAction::SetMarked(account_article_ids.qualify(account), marked)).await;
+                }
+            }
 
             if undoable {
-                undo_stack.push(
+                context.undo_stack.push(
                     UndoOperation::ChangeMarked(article_ids, marked)
                     );
             }
@@ -243,19 +577,41 @@ impl NewsFlashUtils {
     gen_async_call! {
         method_name: tag_articles,
         params: (article_ids: Vec<ArticleID>, tag_id: TagID, undoable: bool),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: undo_stack,
+        context_var: context,
         start_event: Event::AsyncArticleTag,
         operation: {
             let mut tagged_articles: Vec<ArticleID> = Default::default();
-            for article_id in article_ids {
-                    news_flash.tag_article(&article_id, &tag_id, &client).await?;
-                    tagged_articles.push(article_id);
+            for (account, account_article_ids) in context.accounts.group(&article_ids) {
+                // articles of other accounts get the tag with the same label
+                let Some(account_tag_id) = context.accounts.tag_in_account(&tag_id, account).await? else {
+                    info!("account {} has no tag {tag_id}", account.name());
+                    continue;
+                };
+                let news_flash = account.news_flash().await;
+                // articles tagged before an error are still journaled
+                let mut result = Ok(());
+                let mut offline_article_ids: Vec<ArticleID> = Default::default();
+                for article_id in account_article_ids {
+                    if let Err(err) = news_flash.tag_article(&article_id, &account_tag_id, &client).await {
+                        result = Err(err);
+                        break;
+                    }
+                    if news_flash.is_offline() {
+                        offline_article_ids.push(article_id.clone().qualify(account));
+                    }
+                    tagged_articles.push(article_id.qualify(account));
+                }
+                context.push_pending(PendingAction::AddTag(offline_article_ids, tag_id.clone())).await;
+                result?;
+            }
+            if let Err(err) = context.reading_events.record_tagged(&tagged_articles, &tag_id) {
+                error!("unable to record reading events: {err}");
             }
 
             if undoable {
-                undo_stack.push(UndoOperation::AddTag(tagged_articles, tag_id));
+                context.undo_stack.push(UndoOperation::AddTag(tagged_articles, tag_id));
             }
 
         },
@@ -265,19 +621,36 @@ impl NewsFlashUtils {
     gen_async_call! {
         method_name: untag_articles,
         params: (article_ids: Vec<ArticleID>, tag_id: TagID, undoable: bool),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: undo_stack,
+        context_var: context,
         start_event: Event::AsyncArticleUntag,
         operation:{
             let mut untagged_articles: Vec<ArticleID> = Default::default();
-            for article_id in article_ids {
-                    news_flash.untag_article(&article_id, &tag_id, &client).await?;
-                    untagged_articles.push(article_id);
+            for (account, account_article_ids) in context.accounts.group(&article_ids) {
+                let Some(account_tag_id) = context.accounts.tag_in_account(&tag_id, account).await? else {
+                    info!("account {} has no tag {tag_id}", account.name());
+                    continue;
+                };
+                let news_flash = account.news_flash().await;
+                let mut result = Ok(());
+                let mut offline_article_ids: Vec<ArticleID> = Default::default();
+                for article_id in account_article_ids {
+                    if let Err(err) = news_flash.untag_article(&article_id, &account_tag_id, &client).await {
+                        result = Err(err);
+                        break;
+                    }
+                    if news_flash.is_offline() {
+                        offline_article_ids.push(article_id.clone().qualify(account));
+                    }
+                    untagged_articles.push(article_id.qualify(account));
+                }
+                context.push_pending(PendingAction::RemoveTag(offline_article_ids, tag_id.clone())).await;
+                result?;
             }
 
             if undoable {
-                undo_stack.push(UndoOperation::RemoveTag(untagged_articles, tag_id));
+                context.undo_stack.push(UndoOperation::RemoveTag(untagged_articles, tag_id));
             }
 
         },
@@ -289,7 +662,7 @@ impl NewsFlashUtils {
         params: (tag_title: String, color: Option<Color>),
         news_flash_var: news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: _context,
         start_event: Event::AsyncTagAdd,
         operation: let tag = news_flash.add_tag( tag_title.as_str(), color.map(|color| color.to_string()), &client).await?,
         success_event: Event::AsyncTagAddFinished(tag),
@@ -298,38 +671,52 @@ impl NewsFlashUtils {
     gen_async_call! {
         method_name: remove_tag,
         params: (tag_id: TagID),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncTagRemove,
-        operation: news_flash.remove_tag(&tag_id, &client).await?,
+        operation: {
+            let (account, tag_id) = context.accounts.owner(&tag_id);
+            account.news_flash().await.remove_tag(&tag_id, &client).await?;
+        },
         success_event: Event::AsyncTagRemoveFinished,
     }
 
     gen_async_call! {
         method_name: edit_tag,
         params: (tag_id: TagID, new_tag_title: String, color: Option<Color>),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncTagEdit,
-        operation:
-            let tag = news_flash.edit_tag( &tag_id, new_tag_title.as_str(), &color.map(|color| color.to_string()), &client).await?,
+        operation: let tag = {
+            let (account, tag_id) = context.accounts.owner(&tag_id);
+            let news_flash = account.news_flash().await;
+            news_flash.edit_tag( &tag_id, new_tag_title.as_str(), &color.map(|color| color.to_string()), &client).await?.qualify(account)
+        },
         success_event: Event::AsyncTagEditFinished(tag),
     }
 
     gen_async_call! {
         method_name: set_all_read,
         params: (),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: undo_stack,
+        context_var: context,
         start_event: Event::AsyncFeedSetRead,
         operation: {
-            let article_ids = news_flash.get_articles(
-                        ArticleFilter::all_unread())?.into_iter().map(|article| article.article_id).collect();
-            news_flash.set_all_read(&client).await?;
-            undo_stack.push(UndoOperation::ChangeRead(article_ids, Read::Read));
+            let mut article_ids = Vec::new();
+            for account in context.accounts.iter() {
+                let news_flash = account.news_flash().await;
+                let account_article_ids: Vec<ArticleID> = news_flash.get_articles(
+                        ArticleFilter::all_unread())?.into_iter().map(|article| article.article_id.qualify(account)).collect();
+                news_flash.set_all_read(&client).await?;
+                if news_flash.is_offline() {
+                    context.push_pending(PendingAction::SetRead(account_article_ids.clone(), Read::Read)).await;
+                }
+                article_ids.extend(account_article_ids);
+            }
+            context.undo_stack.push(UndoOperation::ChangeRead(article_ids, Read::Read));
         },
         success_event: Event::AsyncSetAllReadFinished,
     }
@@ -337,17 +724,22 @@ impl NewsFlashUtils {
     gen_async_call! {
         method_name: set_feed_read,
         params: (feed_id: FeedID),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: undo_stack,
+        context_var: context,
         start_event: Event::AsyncFeedSetRead,
         operation: {
+            let (account, feed_id) = context.accounts.owner(&feed_id);
+            let news_flash = account.news_flash().await;
             let feed_id = [feed_id];
-            let article_ids = news_flash.get_articles(
+            let article_ids: Vec<ArticleID> = news_flash.get_articles(
                         ArticleFilter::feed_unread(&feed_id[0]))?
-                .into_iter().map(|article| article.article_id).collect();
+                .into_iter().map(|article| article.article_id.qualify(account)).collect();
             news_flash.set_feed_read(&feed_id, &client).await?;
-            undo_stack.push(UndoOperation::ChangeRead(
+            if news_flash.is_offline() {
+                context.push_pending(PendingAction::SetRead(article_ids.clone(), Read::Read)).await;
+            }
+            context.undo_stack.push(UndoOperation::ChangeRead(
                     article_ids, Read::Read,));
         }, 
         success_event: Event::AsyncFeedSetReadFinished,
@@ -356,16 +748,21 @@ impl NewsFlashUtils {
     gen_async_call! {
         method_name: set_category_read,
         params: (category_id: CategoryID),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: undo_stack,
+        context_var: context,
         start_event: Event::AsyncCategorySetRead,
         operation: {
+            let (account, category_id) = context.accounts.owner(&category_id);
+            let news_flash = account.news_flash().await;
             let category_id = [category_id];
-            let article_ids = news_flash.get_articles(
-                        ArticleFilter::category_unread(&category_id[0]))?.into_iter().map(|article| article.article_id).collect();
+            let article_ids: Vec<ArticleID> = news_flash.get_articles(
+                        ArticleFilter::category_unread(&category_id[0]))?.into_iter().map(|article| article.article_id.qualify(account)).collect();
             news_flash.set_category_read(&category_id, &client).await?;
-            undo_stack.push(UndoOperation::ChangeRead(
+            if news_flash.is_offline() {
+                context.push_pending(PendingAction::SetRead(article_ids.clone(), Read::Read)).await;
+            }
+            context.undo_stack.push(UndoOperation::ChangeRead(
                     article_ids, Read::Read,));
 
         },
@@ -375,18 +772,23 @@ impl NewsFlashUtils {
     gen_async_call! {
         method_name: set_tag_read,
         params: (tag_id: TagID),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: undo_stack,
+        context_var: context,
         start_event: Event::AsyncTagSetRead,
         operation: {
 
+            let (account, tag_id) = context.accounts.owner(&tag_id);
+            let news_flash = account.news_flash().await;
             let tag_id = [tag_id];
-            let article_ids = news_flash.get_articles(
-                        ArticleFilter::tag_unread(&tag_id[0]))?.into_iter().map(|article| article.article_id).collect();
+            let article_ids: Vec<ArticleID> = news_flash.get_articles(
+                        ArticleFilter::tag_unread(&tag_id[0]))?.into_iter().map(|article| article.article_id.qualify(account)).collect();
             news_flash.set_tag_read(&tag_id, &client).await?;
+            if news_flash.is_offline() {
+                context.push_pending(PendingAction::SetRead(article_ids.clone(), Read::Read)).await;
+            }
 
-            undo_stack.push(UndoOperation::ChangeRead(
+            context.undo_stack.push(UndoOperation::ChangeRead(
                     article_ids, Read::Read,));
 
 
@@ -395,113 +797,207 @@ impl NewsFlashUtils {
         success_event: Event::AsyncTagSetReadFinished,
     }
 
+    gen_async_call! {
+        method_name: discover_feeds,
+        params: (url: Url, name: Option<String>, category_id: Option<CategoryID>),
+        news_flash_var: _news_flash,
+        client_var: client,
+        context_var: _context,
+        start_event: Event::AsyncFeedDiscovery,
+        operation: let (url, candidates) = match crate::feed_discovery::discover_feeds(&url, &client).await {
+            Ok(Discovery::Feed(feed_url)) => (Url::new(feed_url), Vec::new()),
+            Ok(Discovery::Candidates(candidates)) => (url, candidates),
+            // the feed may still be added by the provider (e.g., intranet feeds)
+            Err(err) => {
+                info!("feed discovery at {url} failed, adding it directly: {err}");
+                (url, Vec::new())
+            }
+        },
+        success_event: Event::AsyncFeedDiscoveryFinished(url, name, category_id, candidates),
+    }
+
+    gen_async_call! {
+        method_name: share_to_webhook,
+        params: (webhook: Box<Webhook>, items: Vec<ShareItem>),
+        news_flash_var: _news_flash,
+        client_var: client,
+        context_var: _context,
+        start_event: Event::AsyncShareWebhook,
+        operation: let (shared, status) = {
+            // stops at the first item which is not accepted
+            let mut shared = 0;
+            let mut status = reqwest::StatusCode::OK;
+            for item in items.iter() {
+                status = webhook.send(item, &client).await?;
+                if !status.is_success() {
+                    break;
+                }
+                shared += 1;
+            }
+            (shared, status)
+        },
+        success_event: Event::AsyncShareWebhookFinished(webhook.name.to_owned(), shared, status),
+    }
+
     gen_async_call! {
         method_name: add_feed,
         params: (url: Url, title: Option<String>, category_id: Option<CategoryID>),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncFeedAdd,
-        operation: let (feed, .. ) = news_flash.add_feed(&url, title, category_id, &client).await?,
+        operation: let feed = {
+            // the feed is added to the account of the category
+            let (account, category_id) = match category_id.as_ref().map(|category_id| context.accounts.owner(category_id)) {
+                Some((account, category_id)) => (account, Some(category_id)),
+                None => (context.accounts.primary(), None),
+            };
+            let news_flash = account.news_flash().await;
+            let (feed, .. ) = news_flash.add_feed(&url, title, category_id, &client).await?;
+            feed.qualify(account)
+        },
         success_event: Event::AsyncFeedAddFinished(feed),
     }
 
     gen_async_call! {
         method_name: fetch_feed,
         params: (feed_id: FeedID),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncFeedFetch,
-        operation: let fetched = news_flash.fetch_feed(&feed_id, &client, Default::default()).await?,
+        operation: let fetched = {
+            let (account, local_feed_id) = context.accounts.owner(&feed_id);
+            account.news_flash().await.fetch_feed(&local_feed_id, &client, Default::default()).await?
+        },
         success_event: Event::AsyncFeedFetchFinished(feed_id, fetched),
     }
 
     gen_async_call! {
         method_name: add_category,
         params: (title: String, parent : Option<CategoryID>),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncCategoryAdd,
-        operation: let (category, .. ) = news_flash.add_category(&title, parent.as_ref(), &client).await?,
+        operation: let category = {
+            let (account, parent) = match parent.as_ref().map(|parent| context.accounts.owner(parent)) {
+                Some((account, parent)) => (account, Some(parent)),
+                None => (context.accounts.primary(), None),
+            };
+            let news_flash = account.news_flash().await;
+            let (category, .. ) = news_flash.add_category(&title, parent.as_ref(), &client).await?;
+            category.qualify(account)
+        },
         success_event: Event::AsyncCategoryAddFinished(category),
     }
 
     gen_async_call! {
         method_name: rename_feed,
         params: (feed_id: FeedID, title: String),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncFeedRename,
-        operation: let feed = news_flash.rename_feed(&feed_id, title.as_str(), &client).await?,
+        operation: let feed = {
+            let (account, feed_id) = context.accounts.owner(&feed_id);
+            let news_flash = account.news_flash().await;
+            news_flash.rename_feed(&feed_id, title.as_str(), &client).await?.qualify(account)
+        },
         success_event: Event::AsyncRenameFeedFinished(feed),
     }
 
     gen_async_call! {
         method_name: rename_category,
         params: (category_id: CategoryID, title: String),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncCategoryRename,
-        operation: let category = news_flash.rename_category(&category_id, title.as_str(), &client).await?,
+        operation: let category = {
+            let (account, category_id) = context.accounts.owner(&category_id);
+            let news_flash = account.news_flash().await;
+            news_flash.rename_category(&category_id, title.as_str(), &client).await?.qualify(account)
+        },
         success_event: Event::AsyncCategoryRenameFinished(category),
     }
 
     gen_async_call! {
         method_name: remove_category,
         params: (category_id: CategoryID, remove_children: bool),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncCategoryRemove,
-        operation: news_flash.remove_category(&category_id, remove_children, &client).await?,
+        operation: {
+            let (account, category_id) = context.accounts.owner(&category_id);
+            account.news_flash().await.remove_category(&category_id, remove_children, &client).await?;
+        },
         success_event: Event::AsyncCategoryRemoveFinished,
     }
 
     gen_async_call! {
         method_name: remove_feed,
         params: (feed_id: FeedID),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncFeedRemove,
-        operation: news_flash.remove_feed(&feed_id, &client).await?,
+        operation: {
+            let (account, feed_id) = context.accounts.owner(&feed_id);
+            account.news_flash().await.remove_feed(&feed_id, &client).await?;
+        },
         success_event: Event::AsyncFeedRemoveFinished,
     }
 
     gen_async_call! {
         method_name: edit_feed_url,
         params: (feed_id: FeedID, new_url: String),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncFeedUrlChange,
-        operation: news_flash.edit_feed_url(&feed_id, &new_url, &client).await?,
+        operation: {
+            let (account, feed_id) = context.accounts.owner(&feed_id);
+            account.news_flash().await.edit_feed_url(&feed_id, &new_url, &client).await?;
+        },
         success_event: Event::AsyncFeedUrlChangeFinished,
     }
 
     gen_async_call! {
         method_name: move_feed,
         params: (from_feed_mapping: FeedMapping, to_feed_mapping: FeedMapping),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncFeedMove,
-        operation: news_flash.move_feed(&from_feed_mapping, &to_feed_mapping, &client).await?,
+        operation: {
+            let (account, from_feed_mapping) = Self::local_feed_mapping(context.accounts, from_feed_mapping)?;
+            let (to_account, to_feed_mapping) = Self::local_feed_mapping(context.accounts, to_feed_mapping)?;
+            if !std::ptr::eq(account, to_account) {
+                return Err(color_eyre::eyre::eyre!("feeds cannot be moved to another account").into());
+            }
+            account.news_flash().await.move_feed(&from_feed_mapping, &to_feed_mapping, &client).await?;
+        },
         success_event: Event::AsyncFeedMoveFinished,
     }
 
     gen_async_call! {
         method_name: move_category,
         params: (category_mapping: CategoryMapping),
-        news_flash_var: news_flash,
+        news_flash_var: _news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: context,
         start_event: Event::AsyncCategoryMove,
-        operation: news_flash.move_category(&category_mapping, &client).await?,
+        operation: {
+            let (account, category_id) = context.accounts.owner(&category_mapping.category_id);
+            let (parent_account, parent_id) = context.accounts.owner(&category_mapping.parent_id);
+            if !std::ptr::eq(account, parent_account) {
+                return Err(color_eyre::eyre::eyre!("categories cannot be moved to another account").into());
+            }
+            let category_mapping = CategoryMapping { category_id, parent_id, ..category_mapping };
+            account.news_flash().await.move_category(&category_mapping, &client).await?;
+        },
         success_event: Event::AsyncCategoryMoveFinished,
     }
 
@@ -510,7 +1006,7 @@ impl NewsFlashUtils {
         params: (opml: String, parse_all_feeds: bool),
         news_flash_var: news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: _context,
         start_event: Event::AsyncImportOpml,
         operation: news_flash.import_opml(&opml, parse_all_feeds, &client).await?,
         success_event: Event::AsyncImportOpmlFinished,
@@ -521,68 +1017,41 @@ impl NewsFlashUtils {
         params: (),
         news_flash_var: news_flash,
         client_var: client,
-        undo_stack_var: _undo_stack,
+        context_var: _context,
         start_event: Event::AsyncLogout,
         operation: news_flash.logout(&client).await?,
         success_event: Event::AsyncLogoutFinished,
     }
 
-    pub fn pipe(&self, article: news_flash::models::Article, fat_article: Option<news_flash::models::FatArticle>, in_target: PipeTarget, out_target: PipeTarget, command: String)  {
-        let news_flash_lock = self.news_flash_lock.clone();
+    pub fn pipe(&self, mut article: PipeArticle, options: PipeOptions, in_target: PipeTarget, out_target: PipeTarget, command: String)  {
+        let accounts = self.accounts.clone();
         let client_lock = self.client_lock.clone();
         let command_sender = self.command_sender.clone();
         let async_operation_mutex = self.async_operation_mutex.clone();
+        let running_pipe = RunningPipe::new(&self.running_pipes);
+        let mut pipe_generation = self.pipe_generation.subscribe();
+        let timeout_seconds = options.timeout_seconds.map(u64::from).unwrap_or(self.config.pipe_timeout_seconds);
             tokio::spawn(async move {
-                let _lock = async_operation_mutex.lock().await;
+                let _running_pipe = running_pipe;
                 if let Err(e) = async {
                      command_sender.send(Message::Event(Event::AsyncPipeArticle)).map_err(|send_error|
                          color_eyre::eyre::eyre!(send_error))?;
-                
-                     let client = client_lock.read().await;
-
-                     let (command, args) = prepare_command(&command)?;
 
-                     let input = match in_target {
-                         PipeTarget::Null => "".into(),
-                         target @ (PipeTarget::Html | PipeTarget::Markdown) => {
-                             let fat_article = match fat_article {
-                                 Some(fat_article) => fat_article,
-                                 None => {
-                                     let news_flash = news_flash_lock.read().await;
-                                     let _stderr_redirect = crate::utils::prelude::StderrRedirect::new();
-                                     news_flash.scrap_content_article(&article.article_id, &client).await?
-                                 }
-
-                             };
+                     if !matches!(in_target, PipeTarget::Null) {
+                         // only scraping is an operation on news flash, the command itself does
+                         // not block other async operations
+                         let _lock = async_operation_mutex.lock().await;
+                         let client = client_lock.read().await;
+                         article.load_content(&accounts, &client).await?;
+                     }
 
-                             let html = fat_article.scraped_content.as_deref().unwrap_or("no scraped content available").to_string();
+                     let input = pipe_input(in_target, &article)?;
+                     let command = article.fill_in(&command, options.shell);
 
-                             if matches!(target, PipeTarget::Html) {
-                                 html
-                             } else {
-                                 htmd::HtmlToMarkdown::builder().build().convert(&html).map_err(|e| AsyncOperationError::Report(color_eyre::eyre::eyre!("Unable to convert HTML to Markdown: {e}")))?
-                             }
-                         },
+                     let Some(result) = run_pipe_command(&command, options.shell, input, timeout_seconds, &mut pipe_generation).await? else {
+                         return Ok(());
                      };
 
-                     let mut child = tokio::process::Command::new(command)
-                         .args(args)
-                         .stdin(Stdio::piped())
-                         .stdout(Stdio::piped())
-                         .stderr(Stdio::piped())
-                         .spawn().map_err(|e| AsyncOperationError::Report(color_eyre::eyre::eyre!("Could not execute pipe command: {e}")))?;
-
-                     // Write input to stdin
-                     if let Some(mut stdin) = child.stdin.take() {
-                         use tokio::io::AsyncWriteExt;
-                         stdin.write_all(input.as_bytes()).await.map_err(|e| AsyncOperationError::Report(color_eyre::eyre::eyre!("Failed to write to stdin: {e}")))?;
-                         // Drop stdin to signal EOF
-                         drop(stdin);
-                     }
-
-                     // Wait for the command to finish and capture output
-                     let result = child.wait_with_output().await.map_err(|e| AsyncOperationError::Report(color_eyre::eyre::eyre!("Failed to wait for command: {e}")))?;
-
                      let output = String::from_utf8_lossy(&result.stdout).to_string();
                      let error = String::from_utf8_lossy(&result.stderr).to_string();
 
@@ -592,11 +1061,7 @@ impl NewsFlashUtils {
                          Some(error)
                      };
 
-                     let markdown = match out_target {
-                         PipeTarget::Null => None,
-                         PipeTarget::Html => Some(HtmlToMarkdown::new().convert(&output).map_err(|conversion_error| color_eyre::eyre::eyre!(conversion_error))?),
-                         PipeTarget::Markdown => Some(output),
-                     };
+                     let markdown = pipe_output(out_target, output)?;
                 
                      command_sender.send(Message::Event(Event::AsyncPipeArticleFinished(article.article_id, result.status, markdown, error))).map_err(|send_error| color_eyre::eyre::eyre!(send_error))?;
                      Ok::<(), AsyncOperationError>(())
@@ -609,6 +1074,211 @@ impl NewsFlashUtils {
 
     }
 
+    // pipes the articles through the command, either one after the other or joined into one
+    // document; the outputs are shown in a popup
+    pub fn pipe_articles(&self, articles: Vec<Article>, options: PipeOptions, in_target: PipeTarget, out_target: PipeTarget, command: String)  {
+        let accounts = self.accounts.clone();
+        let client_lock = self.client_lock.clone();
+        let command_sender = self.command_sender.clone();
+        let async_operation_mutex = self.async_operation_mutex.clone();
+        let running_pipe = RunningPipe::new(&self.running_pipes);
+        let config = self.config.clone();
+        let mut pipe_generation = self.pipe_generation.subscribe();
+        let timeout_seconds = options.timeout_seconds.map(u64::from).unwrap_or(self.config.pipe_timeout_seconds);
+            tokio::spawn(async move {
+                let _running_pipe = running_pipe;
+                if let Err(e) = async {
+                     command_sender.send(Message::Event(Event::AsyncPipeArticle)).map_err(|send_error|
+                         color_eyre::eyre::eyre!(send_error))?;
+
+                     let (feeds, _) = accounts.get_feeds().await?;
+                     let feed_map = NewsFlashUtils::generate_id_map(&feeds, |feed| feed.feed_id.clone());
+
+                     let mut pipe_articles = articles.iter().map(|article| PipeArticle::new(article, feed_map.get(&article.feed_id), None, &config.date_format)).collect::<Vec<PipeArticle>>();
+
+                     if !matches!(in_target, PipeTarget::Null) {
+                         for article in pipe_articles.iter_mut() {
+                             // other async operations may run between the articles
+                             let _lock = async_operation_mutex.lock().await;
+                             let client = client_lock.read().await;
+                             // the article is piped without scraped content
+                             if let Err(err) = article.load_content(&accounts, &client).await {
+                                 warn!("unable to load content of article {}: {err}", article.title);
+                             }
+                         }
+                     }
+
+                     // each article on its own or all articles as one document
+                     let invocations = if options.join {
+                         vec![(format!("{} articles", pipe_articles.len()), pipe_joined_input(in_target, &pipe_articles)?, command.to_owned())]
+                     } else {
+                         pipe_articles.iter().map(|article| Ok((article.title.to_owned(), pipe_input(in_target, article)?, article.fill_in(&command, options.shell)))).collect::<color_eyre::Result<Vec<_>>>()?
+                     };
+
+                     let mut results = Vec::new();
+                     for (title, input, command) in invocations {
+                         // when cancelled, the results so far are shown
+                         let Some(result) = run_pipe_command(&command, options.shell, input, timeout_seconds, &mut pipe_generation).await? else {
+                             break;
+                         };
+
+                         let error = String::from_utf8_lossy(&result.stderr).to_string();
+                         results.push(PipeResult {
+                             title,
+                             status: result.status,
+                             markdown: pipe_output(out_target, String::from_utf8_lossy(&result.stdout).to_string())?,
+                             error: if error.is_empty() { None } else { Some(error) },
+                         });
+                     }
+
+                     if !results.is_empty() {
+                         command_sender.send(Message::Event(Event::AsyncPipeArticlesFinished(results))).map_err(|send_error| color_eyre::eyre::eyre!(send_error))?;
+                     }
+                     Ok::<(), AsyncOperationError>(())
+                }.await{
+                     error!("Async call pipe failed: {e}");
+                     let _ = command_sender.send(Message::Event(Event::AsyncOperationFailed( e,
+                                 Box::new(Event::AsyncPipeArticle),)));
+                }
+            });
+
+    }
+
+    // checks the URLs of all feeds for errors and redirects
+    pub fn check_feed_health(&self) {
+        let accounts = self.accounts.clone();
+        let feed_health_lock = self.feed_health_lock.clone();
+        let config = self.config.clone();
+        let command_sender = self.command_sender.clone();
+        let async_operation_mutex = self.async_operation_mutex.clone();
+
+        tokio::spawn(async move {
+            let _lock = async_operation_mutex.lock().await;
+            if let Err(e) = async {
+                command_sender.send(Message::Event(Event::AsyncFeedHealthCheck)).map_err(|send_error|
+                    color_eyre::eyre::eyre!(send_error))?;
+
+                // redirects are not followed so that moved feeds are detected
+                let client = client_builder(Duration::from_secs(config.network_timeout_seconds), &config.network)?
+                    .redirect(reqwest::redirect::Policy::none())
+                    .build()
+                    .map_err(color_eyre::Report::from)?;
+                let (feeds, _) = accounts.get_feeds().await?;
+                feed_health_lock.write().await.check(&feeds, &client).await?;
+
+                command_sender.send(Message::Event(Event::AsyncFeedHealthCheckFinished)).map_err(|send_error|
+                    color_eyre::eyre::eyre!(send_error))?;
+                Ok::<(), AsyncOperationError>(())
+            }.await {
+                error!("Async call check_feed_health failed: {e}");
+                let _ = command_sender.send(Message::Event(Event::AsyncOperationFailed(e,
+                            Box::new(Event::AsyncFeedHealthCheck),)));
+            }
+        });
+    }
+
+    // enclosures are downloaded independently of the other async operations; the number of
+    // concurrent downloads is limited by the download manager
+    pub fn download_enclosures(&self, articles: Vec<Article>, enclosure_type: Option<EnclosureType>) {
+        let accounts = self.accounts.clone();
+        let downloads = self.downloads.clone();
+        let config = self.config.clone();
+        let command_sender = self.command_sender.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = async {
+                // downloads may take much longer than the network timeout but must not stall
+                let client = client_builder(Duration::from_secs(DOWNLOAD_TIMEOUT_SECONDS), &config.network)?
+                    .read_timeout(Duration::from_secs(config.network_timeout_seconds))
+                    .build()
+                    .map_err(color_eyre::Report::from)?;
+                let (feeds, _) = accounts.get_feeds().await?;
+                let feed_map = NewsFlashUtils::generate_id_map(&feeds, |feed| feed.feed_id.clone());
+
+                let mut queued = 0;
+                for article in articles.iter() {
+                    let enclosures = accounts.get_enclosures(&article.article_id).await?;
+                    let Some(enclosure) = default_enclosure(&enclosures, enclosure_type) else {
+                        continue;
+                    };
+                    let url = enclosure.url.to_string();
+                    if !downloads.enqueue(&url) {
+                        continue;
+                    }
+                    queued += 1;
+
+                    let path = download_path(&config, article, feed_map.get(&article.feed_id), enclosure);
+                    let client = client.clone();
+                    let downloads = downloads.clone();
+                    let command_sender = command_sender.clone();
+                    let article_id = article.article_id.clone();
+                    tokio::spawn(async move {
+                        let result = match path {
+                            Ok(path) => downloads.download(&client, &article_id, &url, &path).await,
+                            Err(err) => {
+                                downloads.cancel(&url);
+                                Err(err)
+                            }
+                        };
+                        let _ = command_sender.send(Message::Event(match result {
+                            Ok(path) => Event::AsyncDownloadFinished(article_id, path),
+                            Err(err) => {
+                                error!("download of {url} failed: {err}");
+                                Event::AsyncDownloadFailed(url, err.to_string())
+                            }
+                        }));
+                    });
+                }
+
+                command_sender.send(Message::Event(Event::AsyncDownloadsQueued(queued))).map_err(|send_error|
+                    color_eyre::eyre::eyre!(send_error))?;
+                Ok::<(), AsyncOperationError>(())
+            }.await {
+                error!("Async call download_enclosures failed: {e}");
+                let _ = command_sender.send(Message::Event(Event::AsyncOperationFailed(e,
+                            Box::new(Event::AsyncDownloadsQueued(0)),)));
+            }
+        });
+    }
+
+    // images in the article content are fetched independently of the other async operations
+    pub fn fetch_content_image(&self, url: String, max_bytes: u64) {
+        self.fetch_image(url, max_bytes, Event::AsyncContentImageFetchFinished);
+    }
+
+    // thumbnails of the article cards in the article list
+    pub fn fetch_card_thumbnail(&self, url: String, max_bytes: u64) {
+        self.fetch_image(url, max_bytes, Event::AsyncCardThumbnailFetchFinished);
+    }
+
+    fn fetch_image(&self, url: String, max_bytes: u64, finished: fn(String, Option<Vec<u8>>) -> Event) {
+        let client_lock = self.client_lock.clone();
+        let command_sender = self.command_sender.clone();
+
+        tokio::spawn(async move {
+            let result = async {
+                // the client is cheap to clone; the lock is not held during the download
+                let client = client_lock.read().await.clone();
+                let mut response = client.get(&url).send().await?.error_for_status()?;
+
+                let mut data = Vec::new();
+                while let Some(chunk) = response.chunk().await? {
+                    data.extend_from_slice(&chunk);
+                    if data.len() as u64 > max_bytes {
+                        return Err(color_eyre::eyre::eyre!("image exceeds {max_bytes} bytes"));
+                    }
+                }
+                Ok::<Vec<u8>, color_eyre::Report>(data)
+            }.await;
+
+            let data = result
+                .inspect_err(|err| debug!("unable to fetch image {url}: {err}"))
+                .ok();
+
+            let _ = command_sender.send(Message::Event(finished(url, data)));
+        });
+    }
+
     pub async fn undo_last_operation(&self) -> Option<UndoOperation> {
 
         let last_operation = {
@@ -754,37 +1424,33 @@ impl NewsFlashUtils {
 
 #[allow(clippy::type_complexity)]
 pub fn get_feeds_and_categories(
-    news_flash: &NewsFlash,
-) -> Result<
-    (
-        Vec<Feed>,
-        std::collections::HashMap<news_flash::models::FeedID, Feed>,
-        std::collections::HashMap<news_flash::models::FeedID, news_flash::models::FeedMapping>,
-        Vec<Category>,
-        std::collections::HashMap<CategoryID, Category>,
-        std::collections::HashMap<CategoryID, news_flash::models::CategoryMapping>,
-    ),
-    color_eyre::eyre::Error,
-> {
-    let (feeds, feed_mapping) = news_flash.get_feeds()?;
+    (feeds, feed_mapping): (Vec<Feed>, Vec<news_flash::models::FeedMapping>),
+    (categories, category_mapping): (Vec<Category>, Vec<news_flash::models::CategoryMapping>),
+) -> (
+    Vec<Feed>,
+    std::collections::HashMap<news_flash::models::FeedID, Feed>,
+    std::collections::HashMap<news_flash::models::FeedID, news_flash::models::FeedMapping>,
+    Vec<Category>,
+    std::collections::HashMap<CategoryID, Category>,
+    std::collections::HashMap<CategoryID, news_flash::models::CategoryMapping>,
+) {
     let feed_for_feed_id = NewsFlashUtils::generate_id_map(&feeds, |feed| feed.feed_id.to_owned());
     let feed_mapping_for_feed_id =
         NewsFlashUtils::generate_id_map(&feed_mapping, |mapping| mapping.feed_id.to_owned());
-    let (categories, category_mapping) = news_flash.get_categories()?;
     let category_for_category_id =
         NewsFlashUtils::generate_id_map(&categories, |category| category.category_id.to_owned());
     let category_mapping_for_category_id =
         NewsFlashUtils::generate_id_map(&category_mapping, |category_mapping| {
             category_mapping.category_id.to_owned()
         });
-    Ok((
+    (
         feeds,
         feed_for_feed_id,
         feed_mapping_for_feed_id,
         categories,
         category_for_category_id,
         category_mapping_for_category_id,
-    ))
+    )
 }
 
 pub fn sort_feeds_and_categories(
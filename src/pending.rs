@@ -0,0 +1,264 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use log::{debug, warn};
+use news_flash::models::{ArticleID, Marked, Read, TagID};
+use serde::{Deserialize, Serialize};
+
+pub mod prelude {
+    pub use super::{PendingAction, PendingOperations};
+}
+
+const PENDING_OPERATIONS_FILE: &str = "pending_operations.json";
+
+// state changes made while offline, replayed once the connection is back
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum PendingAction {
+    SetRead(Vec<ArticleID>, Read),
+    SetMarked(Vec<ArticleID>, Marked),
+    AddTag(Vec<ArticleID>, TagID),
+    RemoveTag(Vec<ArticleID>, TagID),
+}
+
+impl PendingAction {
+    pub fn article_ids(&self) -> &[ArticleID] {
+        use PendingAction as A;
+        match self {
+            A::SetRead(article_ids, _)
+            | A::SetMarked(article_ids, _)
+            | A::AddTag(article_ids, _)
+            | A::RemoveTag(article_ids, _) => article_ids,
+        }
+    }
+
+    // whether replaying the action would still change an article in the given state
+    pub fn changes(&self, read: Read, marked: Marked, is_tagged: bool) -> bool {
+        use PendingAction as A;
+        match self {
+            A::SetRead(_, target) => read != *target,
+            A::SetMarked(_, target) => marked != *target,
+            A::AddTag(..) => !is_tagged,
+            A::RemoveTag(..) => is_tagged,
+        }
+    }
+}
+
+impl Display for PendingAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PendingAction::SetRead(article_ids, read) => {
+                let read_str = if matches!(read, Read::Read) {
+                    "read"
+                } else {
+                    "unread"
+                };
+                write!(f, "set {} articles as {read_str}", article_ids.len())
+            }
+            PendingAction::SetMarked(article_ids, marked) => {
+                let marked_str = if matches!(marked, Marked::Marked) {
+                    "marked"
+                } else {
+                    "unmarked"
+                };
+                write!(f, "set {} articles as {marked_str}", article_ids.len())
+            }
+            PendingAction::AddTag(article_ids, _) => {
+                write!(f, "add tag to {} articles", article_ids.len())
+            }
+            PendingAction::RemoveTag(article_ids, _) => {
+                write!(f, "remove tag from {} articles", article_ids.len())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingOperation {
+    pub id: u64,
+    // unix timestamp of when the operation was queued
+    pub queued: i64,
+    pub action: PendingAction,
+}
+
+// journal of the pending operations; it is written on every change so that nothing is lost
+// when eilmeldung is quit (or crashes) while offline
+pub struct PendingOperations {
+    path: PathBuf,
+    operations: Vec<PendingOperation>,
+}
+
+impl PendingOperations {
+    pub fn load(state_dir: &Path) -> Self {
+        let path = state_dir.join(PENDING_OPERATIONS_FILE);
+
+        let operations = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                warn!("unable to parse pending operations in {path:?}: {err}");
+                Default::default()
+            }),
+            Err(err) => {
+                debug!("no pending operations loaded from {path:?}: {err}");
+                Default::default()
+            }
+        };
+
+        Self { path, operations }
+    }
+
+    pub fn operations(&self) -> &[PendingOperation] {
+        &self.operations
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    pub fn push(&mut self, action: PendingAction) -> color_eyre::Result<()> {
+        if action.article_ids().is_empty() {
+            return Ok(());
+        }
+
+        let id = self
+            .operations
+            .iter()
+            .map(|operation| operation.id)
+            .max()
+            .unwrap_or_default()
+            + 1;
+
+        self.operations.push(PendingOperation {
+            id,
+            queued: chrono::Utc::now().timestamp(),
+            action,
+        });
+        self.save()
+    }
+
+    // removes the operations with the given ids, returns the number of removed operations
+    pub fn remove(&mut self, ids: &[u64]) -> color_eyre::Result<usize> {
+        let len_before = self.operations.len();
+        self.operations
+            .retain(|operation| !ids.contains(&operation.id));
+        let removed = len_before - self.operations.len();
+
+        if removed > 0 {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    pub fn clear(&mut self) -> color_eyre::Result<usize> {
+        let removed = self.operations.len();
+        self.operations.clear();
+        self.save()?;
+        Ok(removed)
+    }
+
+    fn save(&self) -> color_eyre::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(&self.operations)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_state_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("eilmeldung-pending-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn ids(ids: &[&str]) -> Vec<ArticleID> {
+        ids.iter().map(|id| ArticleID::new(id)).collect()
+    }
+
+    #[test]
+    fn test_push_assigns_ids_and_persists() {
+        let dir = temp_state_dir("push");
+        let mut pending = PendingOperations::load(&dir);
+        assert!(pending.is_empty());
+
+        pending
+            .push(PendingAction::SetRead(ids(&["a", "b"]), Read::Read))
+            .unwrap();
+        pending
+            .push(PendingAction::SetMarked(ids(&["c"]), Marked::Marked))
+            .unwrap();
+        // empty actions are not queued
+        pending
+            .push(PendingAction::SetRead(Vec::new(), Read::Unread))
+            .unwrap();
+
+        let ids = pending
+            .operations()
+            .iter()
+            .map(|operation| operation.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2]);
+
+        let reloaded = PendingOperations::load(&dir);
+        assert_eq!(reloaded.len(), 2);
+        assert!(matches!(
+            reloaded.operations()[1].action,
+            PendingAction::SetMarked(_, Marked::Marked)
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove() {
+        let dir = temp_state_dir("remove");
+        let mut pending = PendingOperations::load(&dir);
+        for id in ["a", "b", "c"] {
+            pending
+                .push(PendingAction::SetRead(ids(&[id]), Read::Read))
+                .unwrap();
+        }
+
+        assert_eq!(pending.remove(&[1, 3, 42]).unwrap(), 2);
+        assert_eq!(pending.remove(&[42]).unwrap(), 0);
+        assert_eq!(pending.operations()[0].id, 2);
+
+        // ids continue after the highest remaining id
+        pending
+            .push(PendingAction::SetRead(ids(&["d"]), Read::Read))
+            .unwrap();
+        assert_eq!(pending.operations()[1].id, 3);
+
+        assert_eq!(PendingOperations::load(&dir).len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_changes_skips_articles_in_target_state() {
+        let set_read = PendingAction::SetRead(ids(&["a"]), Read::Read);
+        assert!(set_read.changes(Read::Unread, Marked::Unmarked, false));
+        assert!(!set_read.changes(Read::Read, Marked::Unmarked, false));
+
+        let set_unmarked = PendingAction::SetMarked(ids(&["a"]), Marked::Unmarked);
+        assert!(set_unmarked.changes(Read::Read, Marked::Marked, false));
+        assert!(!set_unmarked.changes(Read::Read, Marked::Unmarked, false));
+
+        let add_tag = PendingAction::AddTag(ids(&["a"]), TagID::new("t"));
+        assert!(add_tag.changes(Read::Read, Marked::Unmarked, false));
+        assert!(!add_tag.changes(Read::Read, Marked::Unmarked, true));
+
+        let remove_tag = PendingAction::RemoveTag(ids(&["a"]), TagID::new("t"));
+        assert!(remove_tag.changes(Read::Read, Marked::Unmarked, true));
+        assert!(!remove_tag.changes(Read::Read, Marked::Unmarked, false));
+    }
+}
@@ -0,0 +1,337 @@
+use std::{
+    process::{ExitStatus, Output, Stdio},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use htmd::HtmlToMarkdown;
+use log::info;
+use news_flash::models::{Article, ArticleID, FatArticle, Feed};
+use reqwest::Client;
+use tokio::sync::watch;
+
+use crate::{messages::event::AsyncOperationError, prelude::*};
+
+pub mod prelude {
+    pub use super::{
+        PipeArticle, PipeResult, pipe_input, pipe_joined_input, pipe_output, run_pipe_command,
+    };
+}
+
+// an article as it is piped through an external command; the content is read from the database
+// or scraped when the command is executed
+#[derive(Debug, Clone)]
+pub struct PipeArticle {
+    pub article_id: ArticleID,
+    pub title: String,
+    pub feed: String,
+    pub author: String,
+    pub url: String,
+    pub date: String,
+    pub published: DateTime<Utc>,
+    pub html: Option<String>,
+}
+
+// output of the command for an article (or for all articles if they were joined)
+#[derive(Debug, Clone)]
+pub struct PipeResult {
+    pub title: String,
+    pub status: ExitStatus,
+    pub markdown: Option<String>,
+    pub error: Option<String>,
+}
+
+impl PipeArticle {
+    pub fn new(
+        article: &Article,
+        feed: Option<&Feed>,
+        fat_article: Option<&FatArticle>,
+        date_format: &str,
+    ) -> Self {
+        Self {
+            article_id: article.article_id.to_owned(),
+            title: article.title.to_owned().unwrap_or_default(),
+            feed: feed.map(|feed| feed.label.to_owned()).unwrap_or_default(),
+            author: article.author.to_owned().unwrap_or_default(),
+            url: article
+                .url
+                .as_ref()
+                .map(|url| url.to_string())
+                .unwrap_or_default(),
+            date: article
+                .date
+                .with_timezone(&chrono::Local)
+                .format(date_format)
+                .to_string(),
+            published: article.date,
+            html: fat_article.and_then(|fat_article| fat_article.scraped_content.to_owned()),
+        }
+    }
+
+    // fills in the placeholders; in shell mode the values are quoted so that they are not
+    // interpreted by the shell
+    pub fn fill_in(&self, command: &str, shell: bool) -> String {
+        let escape = |value: &str| {
+            if shell {
+                shell_words::quote(value).into_owned()
+            } else {
+                value.to_owned()
+            }
+        };
+
+        command
+            .replace("{date}", &escape(&self.date))
+            .replace("{title}", &escape(&self.title))
+            .replace("{url}", &escape(&self.url))
+            .replace("{author}", &escape(&self.author))
+            .replace("{feed}", &escape(&self.feed))
+    }
+
+    // reads the scraped content from the database and scrapes the article if there is none yet
+    pub async fn load_content(
+        &mut self,
+        accounts: &Accounts,
+        client: &Client,
+    ) -> Result<(), AsyncOperationError> {
+        if self.html.is_some() {
+            return Ok(());
+        }
+
+        let (account, article_id) = accounts.owner(&self.article_id);
+        let news_flash = account.news_flash().await;
+        self.html = news_flash.get_fat_article(&article_id)?.scraped_content;
+
+        if self.html.is_none() {
+            let _stderr_redirect = crate::utils::prelude::StderrRedirect::new();
+            self.html = news_flash
+                .scrap_content_article(&article_id, client)
+                .await?
+                .scraped_content;
+        }
+
+        Ok(())
+    }
+
+    fn html(&self) -> &str {
+        self.html
+            .as_deref()
+            .unwrap_or("no scraped content available")
+    }
+
+    fn markdown(&self) -> color_eyre::Result<String> {
+        HtmlToMarkdown::builder()
+            .build()
+            .convert(self.html())
+            .map_err(|e| color_eyre::eyre::eyre!("Unable to convert HTML to Markdown: {e}"))
+    }
+
+    fn metadata(&self) -> String {
+        [&self.feed, &self.author, &self.date, &self.url]
+            .into_iter()
+            .filter(|value| !value.is_empty())
+            .map(String::as_str)
+            .collect::<Vec<&str>>()
+            .join(" · ")
+    }
+
+    fn to_json(&self) -> color_eyre::Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "title": self.title,
+            "feed": self.feed,
+            "author": self.author,
+            "url": self.url,
+            "date": self.published.to_rfc3339(),
+            "content": self.markdown()?,
+        }))
+    }
+}
+
+// the content of a single article as input of the command
+pub fn pipe_input(target: PipeTarget, article: &PipeArticle) -> color_eyre::Result<String> {
+    Ok(match target {
+        PipeTarget::Null => String::new(),
+        PipeTarget::Html => article.html().to_owned(),
+        PipeTarget::Markdown => article.markdown()?,
+        PipeTarget::Json => serde_json::to_string_pretty(&article.to_json()?)?,
+    })
+}
+
+// one document with all articles: a section with title and metadata per article or a JSON array
+pub fn pipe_joined_input(
+    target: PipeTarget,
+    articles: &[PipeArticle],
+) -> color_eyre::Result<String> {
+    Ok(match target {
+        PipeTarget::Null => String::new(),
+        PipeTarget::Markdown => articles
+            .iter()
+            .map(|article| {
+                Ok(format!(
+                    "# {}\n\n*{}*\n\n{}\n",
+                    article.title,
+                    article.metadata(),
+                    article.markdown()?
+                ))
+            })
+            .collect::<color_eyre::Result<Vec<String>>>()?
+            .join("\n---\n\n"),
+        PipeTarget::Html => format!(
+            "<!DOCTYPE html>\n<html>\n<body>\n{}</body>\n</html>\n",
+            articles
+                .iter()
+                .map(|article| format!(
+                    "<article>\n<h1>{}</h1>\n<p><em>{}</em></p>\n{}\n</article>\n",
+                    escape_html(&article.title),
+                    escape_html(&article.metadata()),
+                    article.html()
+                ))
+                .collect::<String>()
+        ),
+        PipeTarget::Json => serde_json::to_string_pretty(
+            &articles
+                .iter()
+                .map(PipeArticle::to_json)
+                .collect::<color_eyre::Result<Vec<serde_json::Value>>>()?,
+        )?,
+    })
+}
+
+// the output of the command as markdown; None if the output is discarded
+pub fn pipe_output(target: PipeTarget, output: String) -> color_eyre::Result<Option<String>> {
+    Ok(match target {
+        PipeTarget::Null => None,
+        PipeTarget::Html => Some(
+            HtmlToMarkdown::new()
+                .convert(&output)
+                .map_err(|conversion_error| color_eyre::eyre::eyre!(conversion_error))?,
+        ),
+        PipeTarget::Markdown => Some(output),
+        PipeTarget::Json => Some(format!("```json\n{}\n```", output.trim_end())),
+    })
+}
+
+// runs the command with the input on stdin and returns its output; None if the command was
+// cancelled. The child is killed on timeout or cancellation (0 seconds means no timeout)
+pub async fn run_pipe_command(
+    command: &str,
+    shell: bool,
+    input: String,
+    timeout_seconds: u64,
+    cancelled: &mut watch::Receiver<u64>,
+) -> Result<Option<Output>, AsyncOperationError> {
+    let mut process = if shell {
+        let mut process = tokio::process::Command::new("sh");
+        process.arg("-c").arg(command);
+        process
+    } else {
+        let (command, args) = prepare_command(command)?;
+        let mut process = tokio::process::Command::new(command);
+        process.args(args);
+        process
+    };
+
+    let mut child = process
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| color_eyre::eyre::eyre!("Could not execute pipe command: {e}"))?;
+
+    let stdin = child.stdin.take();
+    let execution = async move {
+        // write input to stdin while the output is read; dropping stdin signals EOF
+        let write_input = async move {
+            if let Some(mut stdin) = stdin {
+                use tokio::io::AsyncWriteExt;
+                stdin
+                    .write_all(input.as_bytes())
+                    .await
+                    .map_err(|e| color_eyre::eyre::eyre!("Failed to write to stdin: {e}"))?;
+            }
+            Ok::<(), color_eyre::Report>(())
+        };
+        let (write_result, result) = tokio::join!(write_input, child.wait_with_output());
+        write_result?;
+        result.map_err(|e| color_eyre::eyre::eyre!("Failed to wait for command: {e}"))
+    };
+
+    let timeout = async {
+        match timeout_seconds {
+            0 => std::future::pending().await,
+            seconds => tokio::time::sleep(Duration::from_secs(seconds)).await,
+        }
+    };
+
+    tokio::select! {
+        result = execution => Ok(Some(result?)),
+        _ = timeout => Err(color_eyre::eyre::eyre!(
+            "pipe command timed out after {timeout_seconds} seconds"
+        )
+        .into()),
+        _ = cancelled.changed() => {
+            info!("pipe command cancelled");
+            Ok(None)
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pipe_article(title: &str, html: &str) -> PipeArticle {
+        PipeArticle {
+            article_id: ArticleID::new(title),
+            title: title.to_owned(),
+            feed: "LWN".to_owned(),
+            author: String::new(),
+            url: "https://lwn.net/1".to_owned(),
+            date: "2026-10-18".to_owned(),
+            published: DateTime::default(),
+            html: Some(html.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_pipe_joined_input() {
+        let articles = [
+            pipe_article("Rust <2024>", "<p>first</p>"),
+            pipe_article("Kernel", "<p>second</p>"),
+        ];
+
+        let markdown = pipe_joined_input(PipeTarget::Markdown, &articles).unwrap();
+        assert_eq!(
+            markdown,
+            "# Rust <2024>\n\n*LWN · 2026-10-18 · https://lwn.net/1*\n\nfirst\n\
+             \n---\n\n\
+             # Kernel\n\n*LWN · 2026-10-18 · https://lwn.net/1*\n\nsecond\n"
+        );
+
+        let html = pipe_joined_input(PipeTarget::Html, &articles).unwrap();
+        assert!(html.contains("<h1>Rust &lt;2024&gt;</h1>"));
+        assert_eq!(html.matches("<article>").count(), 2);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&pipe_joined_input(PipeTarget::Json, &articles).unwrap()).unwrap();
+        assert_eq!(json[1]["title"], "Kernel");
+        assert_eq!(json[1]["content"], "second");
+    }
+
+    #[test]
+    fn test_fill_in_shell() {
+        let article = pipe_article("it's new", "");
+        assert_eq!(
+            article.fill_in("echo {title} {feed}", true),
+            r#"echo 'it'\''s new' LWN"#
+        );
+        assert_eq!(article.fill_in("echo {title}", false), "echo it's new");
+    }
+}
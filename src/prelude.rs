@@ -1,5 +1,13 @@
+pub use super::accounts::prelude::*;
+pub use super::clipboard::prelude::*;
 pub use super::config::prelude::*;
+pub use super::downloads::prelude::*;
+pub use super::feed_discovery::prelude::*;
+pub use super::feed_health::prelude::*;
 pub use super::input::prelude::*;
+pub use super::pending::prelude::*;
+pub use super::pipe::prelude::*;
+pub use super::reading_stats::prelude::*;
 pub use super::ui::prelude::*;
 pub use super::undo::prelude::*;
 pub use super::utils::prelude::*;
@@ -10,7 +18,8 @@ pub use super::cli::{CliArgs, execute_cli_actions};
 
 pub use super::messages::prelude::*;
 pub use super::newsflash_utils::{
-    NewsFlashUtils, build_client, get_feeds_and_categories, sort_feeds_and_categories,
+    NewsFlashUtils, build_client, client_builder, get_feeds_and_categories,
+    sort_feeds_and_categories,
 };
 pub use super::query::prelude::*;
 
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use news_flash::models::{Article, ArticleID};
+
+// query parameters which only track where a link was clicked
+const TRACKING_PARAMS: [&str; 14] = [
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_ga", "_hsenc",
+    "_hsmi", "ref", "ref_src", "cmpid",
+];
+
+// titles with fewer words are too generic to detect duplicates
+const MIN_TITLE_WORDS: usize = 3;
+
+// groups of articles which are copies of each other: the same link (ignoring tracking
+// parameters) or, optionally, the same normalized titles in different feeds
+#[derive(Default, Debug)]
+pub struct DuplicateIndex {
+    group_for_article: HashMap<ArticleID, usize>,
+    groups: Vec<Vec<ArticleID>>,
+}
+
+impl DuplicateIndex {
+    pub fn new(articles: &[Article], compare_titles: bool) -> Self {
+        // union-find over the positions of the articles
+        fn root(parents: &mut [usize], mut index: usize) -> usize {
+            while parents[index] != index {
+                parents[index] = parents[parents[index]];
+                index = parents[index];
+            }
+            index
+        }
+        let mut parents = (0..articles.len()).collect::<Vec<usize>>();
+
+        let mut first_with_url: HashMap<String, usize> = HashMap::new();
+        let mut first_with_title: HashMap<String, usize> = HashMap::new();
+        for (index, article) in articles.iter().enumerate() {
+            let mut same = Vec::new();
+
+            if let Some(url) = article.url.as_ref().map(|url| canonical_url(url)) {
+                same.push(*first_with_url.entry(url).or_insert(index));
+            }
+
+            if compare_titles
+                && let Some(title) = article.title.as_deref().and_then(normalized_title)
+            {
+                let other = *first_with_title.entry(title).or_insert(index);
+                // the same title in the same feed is usually a recurring post
+                if articles[other].feed_id != article.feed_id {
+                    same.push(other);
+                }
+            }
+
+            for other in same {
+                let (root_1, root_2) = (root(&mut parents, index), root(&mut parents, other));
+                parents[root_1] = root_2;
+            }
+        }
+
+        let mut index = DuplicateIndex::default();
+        let mut group_for_root: HashMap<usize, Vec<ArticleID>> = HashMap::new();
+        for (position, article) in articles.iter().enumerate() {
+            group_for_root
+                .entry(root(&mut parents, position))
+                .or_default()
+                .push(article.article_id.to_owned());
+        }
+
+        for group in group_for_root.into_values().filter(|group| group.len() > 1) {
+            for article_id in group.iter() {
+                index
+                    .group_for_article
+                    .insert(article_id.to_owned(), index.groups.len());
+            }
+            index.groups.push(group);
+        }
+
+        index
+    }
+
+    pub fn is_duplicate(&self, article_id: &ArticleID) -> bool {
+        self.group_for_article.contains_key(article_id)
+    }
+
+    // the other copies of the given article
+    pub fn duplicates_of<'a>(
+        &'a self,
+        article_id: &'a ArticleID,
+    ) -> impl Iterator<Item = &'a ArticleID> {
+        self.group_for_article
+            .get(article_id)
+            .map(|group| self.groups[*group].as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter(move |other| *other != article_id)
+    }
+
+    // an identifier shared by all copies of an article
+    pub fn group_of(&self, article_id: &ArticleID) -> Option<usize> {
+        self.group_for_article.get(article_id).copied()
+    }
+}
+
+// the URL without scheme, `www.`, fragment, tracking parameters and trailing slash; the remaining
+// query parameters are sorted
+pub fn canonical_url(url: &url::Url) -> String {
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    let mut params = url
+        .query_pairs()
+        .filter(|(key, _)| {
+            let key = key.to_lowercase();
+            !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_str())
+        })
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<String>>();
+    params.sort();
+
+    let mut canonical = format!(
+        "{host}{}{}",
+        url.port()
+            .map(|port| format!(":{port}"))
+            .unwrap_or_default(),
+        url.path().trim_end_matches('/')
+    );
+    if !params.is_empty() {
+        canonical.push('?');
+        canonical.push_str(&params.join("&"));
+    }
+    canonical
+}
+
+// lower case words of the title without punctuation
+fn normalized_title(title: &str) -> Option<String> {
+    let words = title
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>();
+
+    (words.len() >= MIN_TITLE_WORDS).then(|| words.join(" "))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn article(id: &str, feed_id: &str, url: Option<&str>, title: Option<&str>) -> Article {
+        Article {
+            article_id: ArticleID::new(id),
+            title: title.map(str::to_owned),
+            author: None,
+            feed_id: news_flash::models::FeedID::new(feed_id),
+            url: url.map(|url| news_flash::models::Url::parse(url).unwrap()),
+            date: chrono::Utc::now(),
+            synced: chrono::Utc::now(),
+            summary: None,
+            direction: None,
+            unread: news_flash::models::Read::Unread,
+            marked: news_flash::models::Marked::Unmarked,
+            thumbnail_url: None,
+            updated: None,
+        }
+    }
+
+    fn sorted_duplicates_of(index: &DuplicateIndex, id: &str) -> Vec<String> {
+        let article_id = ArticleID::new(id);
+        let mut duplicates = index
+            .duplicates_of(&article_id)
+            .map(|article_id| article_id.as_str().to_owned())
+            .collect::<Vec<String>>();
+        duplicates.sort();
+        duplicates
+    }
+
+    #[test]
+    fn test_duplicates_by_url_and_title() {
+        let articles = [
+            article(
+                "a",
+                "feed_1",
+                Some("https://example.com/post?utm_source=x"),
+                None,
+            ),
+            article(
+                "b",
+                "feed_2",
+                Some("https://www.example.com/post/"),
+                Some("Big News Happened Today"),
+            ),
+            // same title as b in another feed: joins the group of a and b
+            article(
+                "c",
+                "feed_3",
+                Some("https://other.com/1"),
+                Some("big news happened today!"),
+            ),
+            // same link as c: joins the group as well (transitively)
+            article("d", "feed_4", Some("https://other.com/1#top"), None),
+            article(
+                "e",
+                "feed_1",
+                Some("https://example.com/unrelated"),
+                Some("Something else entirely"),
+            ),
+        ];
+
+        let index = DuplicateIndex::new(&articles, true);
+
+        assert_eq!(sorted_duplicates_of(&index, "a"), vec!["b", "c", "d"]);
+        assert_eq!(sorted_duplicates_of(&index, "d"), vec!["a", "b", "c"]);
+        assert_eq!(
+            index.group_of(&ArticleID::new("a")),
+            index.group_of(&ArticleID::new("d"))
+        );
+        assert!(!index.is_duplicate(&ArticleID::new("e")));
+        assert_eq!(index.group_of(&ArticleID::new("e")), None);
+
+        // without comparing titles, only the links count
+        let index = DuplicateIndex::new(&articles, false);
+        assert_eq!(sorted_duplicates_of(&index, "a"), vec!["b"]);
+        assert_eq!(sorted_duplicates_of(&index, "c"), vec!["d"]);
+        assert_ne!(
+            index.group_of(&ArticleID::new("a")),
+            index.group_of(&ArticleID::new("c"))
+        );
+    }
+
+    #[test]
+    fn test_same_title_in_same_feed_is_no_duplicate() {
+        let articles = [
+            article(
+                "a",
+                "feed_1",
+                Some("https://example.com/1"),
+                Some("Weekly Links and Notes"),
+            ),
+            article(
+                "b",
+                "feed_1",
+                Some("https://example.com/2"),
+                Some("Weekly Links and Notes"),
+            ),
+            article("c", "feed_2", None, Some("Short title")),
+            article("d", "feed_3", None, Some("Short title")),
+        ];
+
+        let index = DuplicateIndex::new(&articles, true);
+
+        // recurring posts of the same feed and too generic titles
+        for id in ["a", "b", "c", "d"] {
+            assert!(!index.is_duplicate(&ArticleID::new(id)), "{id}");
+        }
+    }
+
+    fn canonical(url: &str) -> String {
+        canonical_url(&url::Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        assert_eq!(
+            canonical("https://www.Example.com/post/1/?utm_source=rss&utm_medium=feed#comments"),
+            "example.com/post/1"
+        );
+        assert_eq!(
+            canonical("http://example.com/post?id=3&fbclid=abc&a=1"),
+            "example.com/post?a=1&id=3"
+        );
+        assert_ne!(
+            canonical("https://example.com/post?id=3"),
+            canonical("https://example.com/post?id=4")
+        );
+    }
+
+    #[test]
+    fn test_normalized_title() {
+        assert_eq!(
+            normalized_title("Rust 2.0 Released!"),
+            normalized_title("rust 2.0 – released")
+        );
+        assert_eq!(normalized_title("Weekly update"), None);
+    }
+}
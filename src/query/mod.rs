@@ -1,8 +1,10 @@
+mod duplicates;
 mod parse;
 mod search_term;
 mod sort_order;
 
 pub mod prelude {
+    pub use super::duplicates::DuplicateIndex;
     pub use super::parse::{QueryParseError, QueryToken, strip_first_and_last};
     pub use super::search_term::{SearchTerm, to_search_term};
     pub use super::sort_order::{SortDirection, SortKey, SortOrder, SortOrderParseError};
@@ -34,6 +36,8 @@ pub(super) enum QueryAtom {
     Tag(Vec<String>),
     Tagged,
     Flagged,
+    Duplicate,
+    Downloaded,
     LastSync,
     Newer(DateTime<Utc>),
     Older(DateTime<Utc>),
@@ -55,15 +59,12 @@ impl QueryClause {
         feed: Option<&Feed>,
         category: Option<&Category>,
         tags: Option<&HashSet<String>>,
-        last_sync: &DateTime<Utc>,
-        flagged_articles: &HashSet<ArticleID>,
+        context: &ArticleQueryContext,
     ) -> bool {
         match self {
-            QueryClause::Id(query_atom) => {
-                query_atom.test(article, feed, category, tags, last_sync, flagged_articles)
-            }
+            QueryClause::Id(query_atom) => query_atom.test(article, feed, category, tags, context),
             QueryClause::Not(query_atom) => {
-                !query_atom.test(article, feed, category, tags, last_sync, flagged_articles)
+                !query_atom.test(article, feed, category, tags, context)
             }
         }
     }
@@ -84,9 +85,21 @@ pub struct ArticleQueryContext<'a> {
     pub tag_map: &'a HashMap<TagID, Tag>,
     pub last_sync: &'a DateTime<Utc>,
     pub flagged: &'a HashSet<ArticleID>,
+    pub duplicates: &'a DuplicateIndex,
+    pub downloaded: &'a HashSet<ArticleID>,
 }
 
 impl ArticleQuery {
+    // whether the query needs the duplicate index
+    pub fn uses_duplicates(&self) -> bool {
+        self.query.iter().any(|query_clause| {
+            matches!(
+                query_clause,
+                QueryClause::Id(QueryAtom::Duplicate) | QueryClause::Not(QueryAtom::Duplicate)
+            )
+        })
+    }
+
     #[inline(always)]
     pub fn filter(&self, articles: &[Article], context: &ArticleQueryContext) -> Vec<Article> {
         articles
@@ -114,16 +127,9 @@ impl ArticleQuery {
                     .collect::<HashSet<String>>()
             });
 
-        self.query.iter().all(|query_clause| {
-            query_clause.test(
-                article,
-                feed,
-                category,
-                tags.as_ref(),
-                context.last_sync,
-                context.flagged,
-            )
-        })
+        self.query
+            .iter()
+            .all(|query_clause| query_clause.test(article, feed, category, tags.as_ref(), context))
     }
 }
 
@@ -135,8 +141,7 @@ impl QueryAtom {
         feed: Option<&Feed>,
         category: Option<&Category>,
         tags: Option<&HashSet<String>>,
-        last_sync: &DateTime<Utc>,
-        flagged_articles: &HashSet<ArticleID>,
+        context: &ArticleQueryContext,
     ) -> bool {
         use QueryAtom as A;
         match self {
@@ -146,7 +151,11 @@ impl QueryAtom {
 
             A::Tagged => !tags.map(|tags| tags.is_empty()).unwrap_or(true),
 
-            A::Flagged => flagged_articles.contains(&article.article_id),
+            A::Flagged => context.flagged.contains(&article.article_id),
+
+            A::Duplicate => context.duplicates.is_duplicate(&article.article_id),
+
+            A::Downloaded => context.downloaded.contains(&article.article_id),
 
             A::Feed(search_term)
             | A::Category(search_term)
@@ -168,7 +177,7 @@ impl QueryAtom {
             A::Newer(date_time) => article.date > *date_time,
             A::SyncedAfter(date_time) => article.synced > *date_time,
             A::SyncedBefore(date_time) => article.synced < *date_time,
-            A::LastSync => article.synced >= *last_sync,
+            A::LastSync => article.synced >= *context.last_sync,
         }
     }
 
@@ -132,6 +132,22 @@ pub enum QueryToken {
     )]
     KeyFlagged,
 
+    #[token("duplicate", priority = 2)]
+    #[strum(
+        serialize = "duplicate",
+        message = "duplicate",
+        detailed_message = "articles of which other copies exist (same link or similar title)"
+    )]
+    KeyDuplicate,
+
+    #[token("downloaded", priority = 2)]
+    #[strum(
+        serialize = "downloaded",
+        message = "downloaded",
+        detailed_message = "articles with downloaded enclosures"
+    )]
+    KeyDownloaded,
+
     #[token("newer:")]
     #[strum(
         serialize = "newer:",
@@ -350,6 +366,8 @@ fn parse_query(
             T::KeyTagged => Some(QueryAtom::Tagged),
             T::KeyLastSync => Some(QueryAtom::LastSync),
             T::KeyFlagged => Some(QueryAtom::Flagged),
+            T::KeyDuplicate => Some(QueryAtom::Duplicate),
+            T::KeyDownloaded => Some(QueryAtom::Downloaded),
 
             key @ (T::KeyTitle
             | T::KeySummary
@@ -48,6 +48,18 @@ impl SearchTerm {
         }
     }
 
+    // regex matching the same strings as `test`, used for locating hits inside a text
+    pub fn to_regex(&self) -> Regex {
+        match self {
+            SearchTerm::Regex(regex) => regex.clone(),
+            SearchTerm::Verbatim(term) => Regex::new(&regex::escape(term)).unwrap(), // unwrap is safe: escaped
+            SearchTerm::Word(word) => regex::RegexBuilder::new(&regex::escape(word))
+                .case_insensitive(true)
+                .build()
+                .unwrap(), // unwrap is safe: escaped
+        }
+    }
+
     // helper for texts
     pub fn test_text(&self, text: &Text) -> bool {
         text.lines.iter().any(|line| {
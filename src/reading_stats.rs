@@ -0,0 +1,658 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use indexmap::IndexMap;
+use log::{debug, warn};
+use news_flash::models::{
+    Article, ArticleID, Category, CategoryID, Feed, FeedID, FeedMapping, Marked, Read, Tag, TagID,
+};
+use serde::{Deserialize, Serialize};
+
+pub mod prelude {
+    pub use super::{ReadingEvents, ReadingStats};
+}
+
+const READING_EVENTS_FILE: &str = "reading_events.jsonl";
+
+// number of days and weeks shown in the read statistics
+const STATS_DAYS: i64 = 14;
+const STATS_WEEKS: i64 = 12;
+
+// number of feeds shown in the per-feed statistics
+const STATS_TOP_FEEDS: usize = 20;
+
+// width of the longest bar in the per-day and per-week statistics
+const BAR_WIDTH: usize = 30;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ReadingEvent {
+    // an article was set to read; `synced` is when the article was synced
+    Read {
+        at: i64,
+        article_id: ArticleID,
+        feed_id: FeedID,
+        synced: i64,
+    },
+    Tagged {
+        at: i64,
+        article_id: ArticleID,
+        tag_id: TagID,
+    },
+}
+
+impl ReadingEvent {
+    fn at(&self) -> i64 {
+        match self {
+            ReadingEvent::Read { at, .. } | ReadingEvent::Tagged { at, .. } => *at,
+        }
+    }
+}
+
+// journal of read and tag events for the reading statistics; the events are appended, one JSON
+// object per line, and events older than the statistics are pruned when loading
+pub struct ReadingEvents {
+    path: PathBuf,
+}
+
+impl ReadingEvents {
+    pub fn new(state_dir: &Path) -> Self {
+        Self {
+            path: state_dir.join(READING_EVENTS_FILE),
+        }
+    }
+
+    pub fn load(&self) -> Vec<ReadingEvent> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) => {
+                debug!("no reading events loaded from {:?}: {err}", self.path);
+                return Vec::default();
+            }
+        };
+
+        let mut events: Vec<ReadingEvent> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                serde_json::from_str(line)
+                    .inspect_err(|err| {
+                        warn!("unable to parse reading event in {:?}: {err}", self.path)
+                    })
+                    .ok()
+            })
+            .collect();
+
+        // events before the oldest week shown are of no use
+        let threshold = (Utc::now() - TimeDelta::weeks(STATS_WEEKS)).timestamp();
+        let event_count = events.len();
+        events.retain(|event| event.at() >= threshold);
+
+        if events.len() < event_count
+            && let Err(err) = self.write(&events)
+        {
+            warn!("unable to prune reading events in {:?}: {err}", self.path);
+        }
+
+        events
+    }
+
+    // records the given articles (which were unread before) as read now; setting whole feeds,
+    // categories or tags to read is catching up rather than reading and thus not recorded
+    pub fn record_read(&self, articles: &[Article]) -> color_eyre::Result<()> {
+        let at = Utc::now().timestamp();
+        self.append(
+            articles
+                .iter()
+                .map(|article| ReadingEvent::Read {
+                    at,
+                    article_id: article.article_id.to_owned(),
+                    feed_id: article.feed_id.to_owned(),
+                    synced: article.synced.timestamp(),
+                })
+                .collect(),
+        )
+    }
+
+    pub fn record_tagged(
+        &self,
+        article_ids: &[ArticleID],
+        tag_id: &TagID,
+    ) -> color_eyre::Result<()> {
+        let at = Utc::now().timestamp();
+        self.append(
+            article_ids
+                .iter()
+                .map(|article_id| ReadingEvent::Tagged {
+                    at,
+                    article_id: article_id.to_owned(),
+                    tag_id: tag_id.to_owned(),
+                })
+                .collect(),
+        )
+    }
+
+    fn write(&self, events: &[ReadingEvent]) -> color_eyre::Result<()> {
+        let mut lines = String::new();
+        for event in events {
+            lines.push_str(&serde_json::to_string(event)?);
+            lines.push('\n');
+        }
+        std::fs::write(&self.path, lines)?;
+        Ok(())
+    }
+
+    fn append(&self, events: Vec<ReadingEvent>) -> color_eyre::Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut lines = String::new();
+        for event in events {
+            lines.push_str(&serde_json::to_string(&event)?);
+            lines.push('\n');
+        }
+
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(lines.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedReadingStats {
+    pub feed: String,
+    pub category: Option<String>,
+    pub articles: usize,
+    pub unread: usize,
+    pub read: usize,
+}
+
+// reading statistics computed from the current articles and the recorded reading events
+#[derive(Debug, Default, Serialize)]
+pub struct ReadingStats {
+    pub articles: usize,
+    pub unread: usize,
+    pub marked: usize,
+    pub average_minutes_to_read: Option<f64>,
+    pub read_per_day: IndexMap<String, usize>,
+    pub read_per_week: IndexMap<String, usize>,
+    // feeds with most articles read first
+    pub feeds: Vec<FeedReadingStats>,
+    pub categories: IndexMap<String, usize>,
+    // feeds with articles none of which has been read
+    pub never_read: Vec<String>,
+    pub tags_per_week: IndexMap<String, IndexMap<String, usize>>,
+}
+
+impl ReadingStats {
+    pub fn compute(
+        events: &[ReadingEvent],
+        articles: &[Article],
+        feeds: &[Feed],
+        feed_mappings: &[FeedMapping],
+        categories: &[Category],
+        tags: &[Tag],
+    ) -> Self {
+        let today = Local::now().date_naive();
+        let day_of = |timestamp: i64| {
+            DateTime::from_timestamp(timestamp, 0)
+                .map(|date| date.with_timezone(&Local).date_naive())
+        };
+
+        let category_labels = categories
+            .iter()
+            .map(|category| (&category.category_id, category.label.as_str()))
+            .collect::<HashMap<&CategoryID, &str>>();
+        let category_of = feed_mappings
+            .iter()
+            .filter_map(|feed_mapping| {
+                category_labels
+                    .get(&feed_mapping.category_id)
+                    .map(|label| (&feed_mapping.feed_id, *label))
+            })
+            .collect::<HashMap<&FeedID, &str>>();
+        let tag_labels = tags
+            .iter()
+            .map(|tag| (&tag.tag_id, tag.label.as_str()))
+            .collect::<HashMap<&TagID, &str>>();
+
+        let mut stats = ReadingStats {
+            articles: articles.len(),
+            unread: articles
+                .iter()
+                .filter(|article| article.unread == Read::Unread)
+                .count(),
+            marked: articles
+                .iter()
+                .filter(|article| article.marked == Marked::Marked)
+                .count(),
+            ..Default::default()
+        };
+
+        // empty days and weeks are shown as well
+        for days_ago in (0..STATS_DAYS).rev() {
+            let day = today - TimeDelta::days(days_ago);
+            stats
+                .read_per_day
+                .insert(day.format("%Y-%m-%d").to_string(), 0);
+        }
+        for weeks_ago in (0..STATS_WEEKS).rev() {
+            let day = today - TimeDelta::weeks(weeks_ago);
+            stats
+                .read_per_week
+                .insert(day.format("%G-W%V").to_string(), 0);
+        }
+
+        let mut read_per_feed: HashMap<&FeedID, usize> = HashMap::new();
+        let mut minutes_to_read = Vec::new();
+        for event in events {
+            match event {
+                ReadingEvent::Read {
+                    at,
+                    feed_id,
+                    synced,
+                    ..
+                } => {
+                    if let Some(day) = day_of(*at) {
+                        if let Some(count) = stats
+                            .read_per_day
+                            .get_mut(&day.format("%Y-%m-%d").to_string())
+                        {
+                            *count += 1;
+                        }
+                        if let Some(count) = stats
+                            .read_per_week
+                            .get_mut(&day.format("%G-W%V").to_string())
+                        {
+                            *count += 1;
+                        }
+                    }
+                    *read_per_feed.entry(feed_id).or_default() += 1;
+                    *stats
+                        .categories
+                        .entry(category_of.get(feed_id).unwrap_or(&"none").to_string())
+                        .or_default() += 1;
+                    minutes_to_read.push((at - synced).max(0) as f64 / 60.0);
+                }
+                ReadingEvent::Tagged { at, tag_id, .. } => {
+                    let Some(week) = day_of(*at).map(|day| day.format("%G-W%V").to_string()) else {
+                        continue;
+                    };
+                    let tag = tag_labels.get(tag_id).unwrap_or(&"removed tag").to_string();
+                    *stats
+                        .tags_per_week
+                        .entry(week)
+                        .or_default()
+                        .entry(tag)
+                        .or_default() += 1;
+                }
+            }
+        }
+
+        stats.average_minutes_to_read = (!minutes_to_read.is_empty())
+            .then(|| minutes_to_read.iter().sum::<f64>() / minutes_to_read.len() as f64);
+        stats
+            .categories
+            .sort_by(|_, count_1, _, count_2| count_2.cmp(count_1));
+        stats.tags_per_week.sort_keys();
+
+        let mut articles_per_feed: HashMap<&FeedID, (usize, usize)> = HashMap::new();
+        for article in articles {
+            let (total, unread) = articles_per_feed.entry(&article.feed_id).or_default();
+            *total += 1;
+            if article.unread == Read::Unread {
+                *unread += 1;
+            }
+        }
+
+        stats.feeds = feeds
+            .iter()
+            .map(|feed| {
+                let (articles, unread) = articles_per_feed
+                    .get(&feed.feed_id)
+                    .copied()
+                    .unwrap_or_default();
+                FeedReadingStats {
+                    feed: feed.label.to_owned(),
+                    category: category_of
+                        .get(&feed.feed_id)
+                        .map(|label| label.to_string()),
+                    articles,
+                    unread,
+                    read: read_per_feed
+                        .get(&feed.feed_id)
+                        .copied()
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        stats.feeds.sort_by(|feed_1, feed_2| {
+            feed_2
+                .read
+                .cmp(&feed_1.read)
+                .then(feed_1.feed.cmp(&feed_2.feed))
+        });
+
+        stats.never_read = stats
+            .feeds
+            .iter()
+            .filter(|feed| feed.articles > 0 && feed.unread == feed.articles && feed.read == 0)
+            .map(|feed| feed.feed.to_owned())
+            .collect();
+
+        stats
+    }
+
+    // the statistics as sections of label/value rows (for the popup and the CLI)
+    pub fn sections(&self) -> Vec<(String, Vec<(String, String)>)> {
+        let bars = |counts: &IndexMap<String, usize>| {
+            let max = counts.values().max().copied().unwrap_or_default().max(1);
+            counts
+                .iter()
+                .map(|(label, count)| {
+                    (
+                        label.to_owned(),
+                        format!("{count:>4} {}", "▪".repeat(count * BAR_WIDTH / max)),
+                    )
+                })
+                .collect::<Vec<(String, String)>>()
+        };
+
+        let mut sections = vec![
+            (
+                "Articles".to_owned(),
+                vec![
+                    ("Articles".to_owned(), self.articles.to_string()),
+                    ("Unread".to_owned(), self.unread.to_string()),
+                    ("Marked".to_owned(), self.marked.to_string()),
+                    (
+                        "Read after sync".to_owned(),
+                        self.average_minutes_to_read
+                            .map(format_minutes)
+                            .unwrap_or("-".to_owned()),
+                    ),
+                ],
+            ),
+            ("Read per Day".to_owned(), bars(&self.read_per_day)),
+            ("Read per Week".to_owned(), bars(&self.read_per_week)),
+            (
+                "Read per Feed".to_owned(),
+                self.feeds
+                    .iter()
+                    .filter(|feed| feed.read > 0)
+                    .take(STATS_TOP_FEEDS)
+                    .map(|feed| {
+                        (
+                            feed.feed.to_owned(),
+                            format!(
+                                "{} read, {} of {} articles unread",
+                                feed.read, feed.unread, feed.articles
+                            ),
+                        )
+                    })
+                    .collect(),
+            ),
+            (
+                "Read per Category".to_owned(),
+                self.categories
+                    .iter()
+                    .map(|(category, count)| (category.to_owned(), format!("{count} read")))
+                    .collect(),
+            ),
+            (
+                "Never Read".to_owned(),
+                self.feeds
+                    .iter()
+                    .filter(|feed| self.never_read.contains(&feed.feed))
+                    .map(|feed| (feed.feed.to_owned(), format!("{} articles", feed.articles)))
+                    .collect(),
+            ),
+            (
+                "Tags per Week".to_owned(),
+                self.tags_per_week
+                    .iter()
+                    .map(|(week, tags)| {
+                        (
+                            week.to_owned(),
+                            tags.iter()
+                                .map(|(tag, count)| format!("#{tag} {count}"))
+                                .collect::<Vec<String>>()
+                                .join(", "),
+                        )
+                    })
+                    .collect(),
+            ),
+        ];
+
+        sections.retain(|(_, rows)| !rows.is_empty());
+        sections
+    }
+}
+
+fn format_minutes(minutes: f64) -> String {
+    if minutes < 60.0 {
+        format!("{minutes:.0} minutes")
+    } else if minutes < 48.0 * 60.0 {
+        format!("{:.1} hours", minutes / 60.0)
+    } else {
+        format!("{:.1} days", minutes / (24.0 * 60.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // noon (local time) of the given number of days ago, so that the day does not depend on the
+    // time of the test run
+    fn days_ago(days: i64) -> i64 {
+        (Local::now().date_naive() - TimeDelta::days(days))
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .earliest()
+            .unwrap()
+            .timestamp()
+    }
+
+    fn day_label(days: i64) -> String {
+        (Local::now().date_naive() - TimeDelta::days(days))
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    fn week_label(days: i64) -> String {
+        (Local::now().date_naive() - TimeDelta::days(days))
+            .format("%G-W%V")
+            .to_string()
+    }
+
+    fn read(feed_id: &str, at: i64, minutes_after_sync: i64) -> ReadingEvent {
+        ReadingEvent::Read {
+            at,
+            article_id: ArticleID::new(&format!("{feed_id}-{at}")),
+            feed_id: FeedID::new(feed_id),
+            synced: at - minutes_after_sync * 60,
+        }
+    }
+
+    fn article(id: &str, feed_id: &str, unread: Read) -> Article {
+        Article {
+            article_id: ArticleID::new(id),
+            title: None,
+            author: None,
+            feed_id: FeedID::new(feed_id),
+            url: None,
+            date: Utc::now(),
+            synced: Utc::now(),
+            summary: None,
+            direction: None,
+            unread,
+            marked: Marked::Unmarked,
+            thumbnail_url: None,
+            updated: None,
+        }
+    }
+
+    fn feed(id: &str, label: &str) -> Feed {
+        Feed {
+            feed_id: FeedID::new(id),
+            label: label.to_owned(),
+            website: None,
+            feed_url: None,
+            icon_url: None,
+            error_count: 0,
+            error_message: None,
+        }
+    }
+
+    #[test]
+    fn test_day_and_week_buckets() {
+        let events = [
+            read("a", days_ago(0), 10),
+            read("a", days_ago(0), 10),
+            read("b", days_ago(2), 10),
+            // only counted per week
+            read("b", days_ago(20), 10),
+            // too old for both
+            read("b", days_ago(200), 10),
+        ];
+
+        let stats = ReadingStats::compute(&events, &[], &[], &[], &[], &[]);
+
+        assert_eq!(stats.read_per_day.len(), STATS_DAYS as usize);
+        assert_eq!(stats.read_per_week.len(), STATS_WEEKS as usize);
+        // oldest first, empty days included
+        assert_eq!(stats.read_per_day.keys().last(), Some(&day_label(0)));
+        assert_eq!(stats.read_per_day[&day_label(0)], 2);
+        assert_eq!(stats.read_per_day[&day_label(1)], 0);
+        assert_eq!(stats.read_per_day[&day_label(2)], 1);
+        assert!(!stats.read_per_day.contains_key(&day_label(20)));
+        assert_eq!(stats.read_per_week[&week_label(20)], 1);
+        assert_eq!(stats.read_per_week.values().sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_feeds_and_never_read() {
+        let events = [read("a", days_ago(0), 10), read("a", days_ago(1), 10)];
+        let articles = [
+            article("a1", "a", Read::Read),
+            article("a2", "a", Read::Unread),
+            article("b1", "b", Read::Unread),
+            article("b2", "b", Read::Unread),
+            // read elsewhere (e.g., in the web interface), so it has been read
+            article("c1", "c", Read::Read),
+        ];
+        let feeds = [
+            feed("b", "Beta"),
+            feed("a", "Alpha"),
+            feed("c", "Gamma"),
+            feed("d", "Empty"),
+        ];
+        let categories = [Category {
+            category_id: CategoryID::new("news"),
+            label: "News".to_owned(),
+        }];
+        let feed_mappings = [FeedMapping {
+            feed_id: FeedID::new("a"),
+            category_id: CategoryID::new("news"),
+            sort_index: None,
+        }];
+
+        let stats =
+            ReadingStats::compute(&events, &articles, &feeds, &feed_mappings, &categories, &[]);
+
+        assert_eq!(stats.articles, 5);
+        assert_eq!(stats.unread, 3);
+        let feed_order = stats
+            .feeds
+            .iter()
+            .map(|feed| feed.feed.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(feed_order, vec!["Alpha", "Beta", "Empty", "Gamma"]);
+        assert_eq!(stats.feeds[0].read, 2);
+        assert_eq!(stats.feeds[0].category.as_deref(), Some("News"));
+        assert_eq!(stats.categories["News"], 2);
+        assert_eq!(stats.never_read, vec!["Beta"]);
+    }
+
+    #[test]
+    fn test_average_minutes_to_read() {
+        let stats = ReadingStats::compute(&[], &[], &[], &[], &[], &[]);
+        assert_eq!(stats.average_minutes_to_read, None);
+
+        let events = [
+            read("a", days_ago(0), 10),
+            read("a", days_ago(0), 50),
+            // read before it was synced (e.g., clock skew) counts as zero
+            read("a", days_ago(0), -30),
+        ];
+        let stats = ReadingStats::compute(&events, &[], &[], &[], &[], &[]);
+        assert_eq!(stats.average_minutes_to_read, Some(20.0));
+    }
+
+    #[test]
+    fn test_tags_per_week() {
+        let tags = [Tag {
+            tag_id: TagID::new("later"),
+            label: "later".to_owned(),
+            color: None,
+            sort_index: None,
+        }];
+        let tagged = |tag_id: &str, at: i64| ReadingEvent::Tagged {
+            at,
+            article_id: ArticleID::new("a1"),
+            tag_id: TagID::new(tag_id),
+        };
+        let events = [
+            tagged("later", days_ago(0)),
+            tagged("later", days_ago(0)),
+            tagged("gone", days_ago(0)),
+        ];
+
+        let stats = ReadingStats::compute(&events, &[], &[], &[], &[], &tags);
+
+        let this_week = &stats.tags_per_week[&week_label(0)];
+        assert_eq!(this_week["later"], 2);
+        assert_eq!(this_week["removed tag"], 1);
+    }
+
+    #[test]
+    fn test_prune_old_events() {
+        let state_dir = std::env::temp_dir().join(format!(
+            "eilmeldung-reading-events-prune-{}",
+            std::process::id()
+        ));
+        let reading_events = ReadingEvents::new(&state_dir);
+        reading_events
+            .append(vec![
+                read("old", days_ago(STATS_WEEKS * 7 + 1), 10),
+                read("recent", days_ago(1), 10),
+            ])
+            .unwrap();
+
+        let events = reading_events.load();
+        assert_eq!(events.len(), 1);
+        assert!(
+            matches!(&events[0], ReadingEvent::Read { feed_id, .. } if feed_id.as_str() == "recent")
+        );
+
+        // the old event has been removed from the file as well
+        let content = std::fs::read_to_string(state_dir.join(READING_EVENTS_FILE)).unwrap();
+        assert_eq!(content.lines().count(), 1);
+
+        std::fs::remove_dir_all(&state_dir).unwrap();
+    }
+}
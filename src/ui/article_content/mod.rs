@@ -1,4 +1,6 @@
 mod model;
+mod reading_position;
+mod syntax_highlight;
 mod view;
 
 pub mod prelude {
@@ -6,6 +8,7 @@ pub mod prelude {
 }
 
 use model::ArticleContentModelData;
+use reading_position::ReadingPositions;
 use tokio::sync::mpsc::UnboundedSender;
 use url::Url;
 use view::ArticleContentViewData;
@@ -13,7 +16,7 @@ use view::ArticleContentViewData;
 use crate::prelude::*;
 use std::sync::Arc;
 
-use news_flash::models::{ArticleID, Enclosure, Thumbnail};
+use news_flash::models::{ArticleID, Read, Thumbnail};
 
 #[derive(getset::CopyGetters)]
 pub struct ArticleContent {
@@ -22,6 +25,9 @@ pub struct ArticleContent {
     view_data: ArticleContentViewData,
     model_data: ArticleContentModelData,
 
+    reading_positions: ReadingPositions,
+    read_at_end_sent: bool,
+
     message_sender: UnboundedSender<Message>,
 
     #[getset(get_copy = "pub")]
@@ -36,6 +42,11 @@ impl ArticleContent {
         message_sender: UnboundedSender<Message>,
     ) -> Self {
         Self {
+            reading_positions: ReadingPositions::load(
+                news_flash_utils.state_dir(),
+                config.keep_articles_days,
+            ),
+            read_at_end_sent: false,
             config,
             view_data: ArticleContentViewData::default(),
             model_data: ArticleContentModelData::new(news_flash_utils),
@@ -49,14 +60,146 @@ impl ArticleContent {
         &mut self,
         article_id: Option<&ArticleID>,
     ) -> color_eyre::Result<bool> {
+        self.remember_reading_position();
         let article_changed = self.model_data.on_article_selected(article_id).await?;
+        if article_changed {
+            self.read_at_end_sent = false;
+        }
         self.view_data.clear_image();
-        self.view_data.scroll_to_top();
+        let reading_position = article_id
+            .and_then(|article_id| self.reading_positions.get(article_id))
+            .copied();
+        self.view_data.restore_reading_position(
+            reading_position.map(|reading_position| reading_position.position),
+            reading_position.map(|reading_position| reading_position.progress),
+        );
         self.view_data.update(&self.model_data, self.config.clone());
         self.update_thumbnail_fetching_state()?;
         Ok(article_changed)
     }
 
+    fn remember_reading_position(&mut self) {
+        if let Some(article) = self.model_data.article()
+            && let Some(position) = self.view_data.reading_position()
+        {
+            let progress = self.view_data.progress().unwrap_or(position);
+            self.reading_positions
+                .update(&article.article_id, position, progress);
+        }
+    }
+
+    fn save_reading_positions(&mut self, force: bool) {
+        let result = if force {
+            self.reading_positions.save()
+        } else {
+            self.reading_positions.save_if_due()
+        };
+        if let Err(err) = result {
+            log::warn!("unable to save reading positions: {err}");
+        }
+    }
+
+    fn update_read_at_end(&mut self) -> color_eyre::Result<()> {
+        if !self.config.content_read_at_end || self.read_at_end_sent || !self.view_data.is_at_end()
+        {
+            return Ok(());
+        }
+
+        if let Some(article) = self.model_data.article()
+            && article.unread == Read::Unread
+        {
+            self.message_sender.send(Message::Command(Command::In(
+                Panel::ArticleList,
+                Box::new(Command::ActionSetRead(ActionScope::Current)),
+            )))?;
+        }
+        self.read_at_end_sent = true;
+
+        Ok(())
+    }
+
+    fn search_next(&mut self, reverse: bool) -> color_eyre::Result<()> {
+        if !self.view_data.has_search_term() {
+            return tooltip(
+                &self.message_sender,
+                "no search term",
+                TooltipFlavor::Warning,
+            );
+        }
+
+        if self.view_data.num_search_matches() == 0 {
+            return tooltip(
+                &self.message_sender,
+                "no matches in article",
+                TooltipFlavor::Warning,
+            );
+        }
+
+        if self.view_data.search_next(reverse) {
+            tooltip(
+                &self.message_sender,
+                if reverse {
+                    "top reached, starting from bottom"
+                } else {
+                    "bottom reached, starting from top"
+                },
+                TooltipFlavor::Info,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn show_table_of_contents(&self) -> color_eyre::Result<()> {
+        let headings = self.view_data.headings();
+        if headings.is_empty() {
+            return tooltip(
+                &self.message_sender,
+                "no headings in article",
+                TooltipFlavor::Warning,
+            );
+        }
+
+        let lines = headings
+            .iter()
+            .enumerate()
+            .map(|(index, (level, title, _))| {
+                Line::from(vec![
+                    Span::styled(format!("{:>3} ", index + 1), self.config.theme.inactive()),
+                    Span::styled(
+                        format!(
+                            "{}{title}",
+                            "  ".repeat((*level as usize).saturating_sub(1))
+                        ),
+                        if *level == 1 {
+                            self.config.theme.header()
+                        } else {
+                            self.config.theme.paragraph()
+                        },
+                    ),
+                ])
+            })
+            .collect::<Vec<Line<'static>>>();
+
+        let commands = (1..=headings.len())
+            .map(|number| {
+                Command::In(
+                    Panel::ArticleContent,
+                    Box::new(Command::ContentGotoHeading(number)),
+                )
+            })
+            .collect();
+
+        self.message_sender
+            .send(Message::Event(Event::ShowSelectionPopup(
+                "Table of Contents".to_owned(),
+                Text::from(lines),
+                commands,
+            )))?;
+
+        Ok(())
+    }
+
     fn prepare_thumbnail(&mut self, thumbnail: &Thumbnail) -> color_eyre::Result<()> {
         let image = self
             .model_data
@@ -94,37 +237,18 @@ impl ArticleContent {
     }
 
     fn share_url(&self, target_str: &str, title: &str, url: &Url) -> color_eyre::Result<()> {
-        let Some(target) = self
-            .config
-            .share_targets
-            .iter()
-            .find(|target| target.as_ref() == target_str)
-        else {
-            tooltip(
-                &self.message_sender,
-                &*format!("unknown share target {target_str}"),
-                TooltipFlavor::Error,
-            )?;
-            return Ok(());
-        };
-
-        match target.share(title, url) {
-            Ok(()) => tooltip(
-                &self.message_sender,
-                &*format!("shared with {}", target),
-                TooltipFlavor::Info,
-            )?,
-            Err(error) => tooltip(
-                &self.message_sender,
-                &*format!("unable to shared with {}: {}", target, error),
-                TooltipFlavor::Error,
-            )?,
-        }
-
-        Ok(())
+        share_with_target(
+            &self.config,
+            self.model_data.news_flash_utils(),
+            &self.message_sender,
+            ShareOptions::default(),
+            target_str,
+            None,
+            vec![ShareItem::new(title, url)],
+        )
     }
 
-    fn share_article(&self, target_str: &str) -> color_eyre::Result<()> {
+    fn share_article(&self, options: ShareOptions, target_str: &str) -> color_eyre::Result<()> {
         let Some(article) = self.model_data.article() else {
             tooltip(
                 &self.message_sender,
@@ -134,31 +258,42 @@ impl ArticleContent {
             return Ok(());
         };
 
-        let Some(url) = article.url.as_ref() else {
-            tooltip(
-                &self.message_sender,
-                "article has no URL",
-                TooltipFlavor::Warning,
-            )?;
-            return Ok(());
-        };
-
-        let title: &str = article.title.as_deref().unwrap_or("no title");
-
-        self.share_url(target_str, title, url.as_ref())?;
+        let tags = self
+            .model_data
+            .tags()
+            .iter()
+            .flatten()
+            .map(|tag| tag.label.as_str())
+            .collect();
 
-        Ok(())
+        share_with_target(
+            &self.config,
+            self.model_data.news_flash_utils(),
+            &self.message_sender,
+            options,
+            target_str,
+            None,
+            ShareItem::from_article(
+                article,
+                self.model_data.feed().as_ref(),
+                tags,
+                &self.config.date_format,
+            )
+            .into_iter()
+            .collect(),
+        )
     }
 
     fn pipe(
-        &self,
+        &mut self,
+        options: PipeOptions,
         in_target: PipeTarget,
         out_target: PipeTarget,
         command: &str,
     ) -> color_eyre::Result<()> {
-        if let Err(error) = self
-            .model_data
-            .pipe(&self.config, in_target, out_target, command)
+        if let Err(error) =
+            self.model_data
+                .pipe(&self.config, options, in_target, out_target, command)
         {
             tooltip(
                 &self.message_sender,
@@ -170,7 +305,7 @@ impl ArticleContent {
 
         tooltip(
             &self.message_sender,
-            &*Command::Pipe(in_target, out_target, command.into()).to_string(),
+            &*Command::Pipe(options, in_target, out_target, None, command.into()).to_string(),
             TooltipFlavor::Info,
         )?;
 
@@ -202,21 +337,7 @@ impl ArticleContent {
             return Ok(());
         };
 
-        let enclosures_matching_type = enclosures
-            .iter()
-            .filter(|enclosure| {
-                enclosure_type
-                    .map(|enclosure_type| enclosure_type == (*enclosure).into())
-                    .unwrap_or(true)
-            })
-            .collect::<Vec<&Enclosure>>();
-
-        let matching_enclosure = enclosures_matching_type
-            .iter()
-            .find(|enclosure| enclosure.is_default)
-            .or_else(|| enclosures_matching_type.first());
-
-        let Some(matching_enclosure) = matching_enclosure else {
+        let Some(matching_enclosure) = default_enclosure(enclosures, enclosure_type) else {
             tooltip(
                 &self.message_sender,
                 "no matching enclosure found",
@@ -284,7 +405,8 @@ impl ArticleContent {
 
         match command {
             Command::ContentFollowHint(..) => {
-                if let Err(err) = webbrowser::open(url.as_ref()) {
+                let item = ShareItem::new(&title, &url);
+                if let Err(err) = open_in_browser(&self.config, &[item]) {
                     tooltip(
                         &self.message_sender,
                         &*format!("unable to open webbrowser: {err}"),
@@ -332,6 +454,12 @@ impl crate::messages::MessageReceiver for ArticleContent {
                 C::NavigateUp if handle_command => {
                     self.view_data.scroll_up();
                 }
+                C::NavigateLeft if handle_command => {
+                    self.view_data.scroll_left();
+                }
+                C::NavigateRight if handle_command => {
+                    self.view_data.scroll_right();
+                }
                 C::NavigatePageUp if handle_command => {
                     self.view_data
                         .scroll_page_up(self.config.input_config.scroll_amount as u16);
@@ -347,22 +475,85 @@ impl crate::messages::MessageReceiver for ArticleContent {
                     self.view_data.scroll_to_bottom();
                 }
 
+                C::InputSearch if handle_command => {
+                    self.message_sender
+                        .send(Message::Command(Command::CommandLineOpen(Some(
+                            "search".to_owned(),
+                        ))))?;
+                }
+
+                C::Search(search_term) if handle_command => {
+                    self.view_data.set_search_term(search_term);
+                }
+
+                C::SearchNext if handle_command => {
+                    self.search_next(false)?;
+                }
+
+                C::SearchPrevious if handle_command => {
+                    self.search_next(true)?;
+                }
+
+                C::ContentTableOfContents if handle_command => {
+                    self.show_table_of_contents()?;
+                }
+
+                C::ContentGotoHeading(number) => {
+                    if !self.view_data.goto_heading(number.saturating_sub(1)) {
+                        tooltip(
+                            &self.message_sender,
+                            &*format!("no heading with number {number}"),
+                            TooltipFlavor::Warning,
+                        )?;
+                    }
+                }
+
+                C::ContentNextHeading if handle_command => {
+                    if !self.view_data.next_heading(false) {
+                        tooltip(&self.message_sender, "no next heading", TooltipFlavor::Info)?;
+                    }
+                }
+
+                C::ContentPreviousHeading if handle_command => {
+                    if !self.view_data.next_heading(true) {
+                        tooltip(
+                            &self.message_sender,
+                            "no previous heading",
+                            TooltipFlavor::Info,
+                        )?;
+                    }
+                }
+
                 C::ArticleCurrentScrape => {
                     self.scrape_article()?;
                 }
 
-                C::ArticleShare(target) => {
-                    self.share_article(&target)?;
+                C::ArticleShare(options, target, None) => {
+                    self.share_article(options, &target)?;
                 }
 
-                C::Pipe(in_target, out_target, command) => {
-                    self.pipe(in_target, out_target, &command)?
+                C::Pipe(options, in_target, out_target, None, command) => {
+                    self.pipe(options, in_target, out_target, &command)?
+                }
+
+                C::PipeCancel => {
+                    let message = match self.model_data.news_flash_utils().cancel_pipes() {
+                        0 => "no pipe command running".to_owned(),
+                        count => format!("{count} pipe command(s) cancelled"),
+                    };
+                    tooltip(&self.message_sender, &*message, TooltipFlavor::Info)?;
                 }
 
                 C::Refresh => {
+                    self.view_data.retry_failed_content_images();
                     view_needs_update = true;
                 }
 
+                C::ApplicationQuit => {
+                    self.remember_reading_position();
+                    self.save_reading_positions(true);
+                }
+
                 C::ArticleOpenEnclosure(enclosure_type) => {
                     self.open_enclosure(enclosure_type).await?;
                 }
@@ -412,6 +603,13 @@ impl crate::messages::MessageReceiver for ArticleContent {
                     view_needs_update = true;
                 }
 
+                AsyncContentImageFetchFinished(url, data) => {
+                    self.view_data
+                        .on_content_image_fetched(url, data.as_deref());
+                    self.message_sender
+                        .send(Message::Command(Command::Redraw))?;
+                }
+
                 AsyncOperationFailed(err, reason) => {
                     if let Event::AsyncArticleThumbnailFetch = *reason.as_ref() {
                         log::debug!("fetching thumbnail not successful: {err}");
@@ -453,6 +651,13 @@ impl crate::messages::MessageReceiver for ArticleContent {
                 }
 
                 Tick => {
+                    self.remember_reading_position();
+                    self.save_reading_positions(false);
+                    self.update_read_at_end()?;
+                    self.model_data.fetch_content_images(
+                        self.view_data.take_content_images_to_fetch(),
+                        self.config.content_image_max_kilobytes * 1024,
+                    );
                     view_needs_update = self.update_thumbnail_fetching_state()?;
                 }
 
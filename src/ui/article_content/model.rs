@@ -1,6 +1,7 @@
 use crate::prelude::*;
 
 use std::{
+    collections::{HashMap, HashSet},
     io::Cursor,
     process::Stdio,
     sync::Arc,
@@ -16,7 +17,6 @@ use ratatui_image::{picker::Picker, protocol::StatefulProtocol};
 #[derive(Getters)]
 #[getset(get = "pub(super)")]
 pub struct ArticleContentModelData {
-    #[getset(skip)]
     news_flash_utils: Arc<NewsFlashUtils>,
 
     // Core article data
@@ -32,6 +32,10 @@ pub struct ArticleContentModelData {
     // Filtered content
     filtered_markdown_content: Option<String>,
 
+    // outputs of pipe commands which survive reselection of the article
+    cached_pipe_outputs: HashMap<ArticleID, String>,
+    articles_with_cached_pipe: HashSet<ArticleID>,
+
     // Thumbnail data and state
     thumbnail_fetch_successful: Option<bool>,
     thumbnail_fetch_running: bool,
@@ -53,6 +57,8 @@ impl ArticleContentModelData {
             fat_article: None,
             markdown_content: None,
             filtered_markdown_content: None,
+            cached_pipe_outputs: HashMap::new(),
+            articles_with_cached_pipe: HashSet::new(),
             thumbnail_fetch_successful: None,
             thumbnail_fetch_running: false,
             thumbnail: None,
@@ -80,21 +86,23 @@ impl ArticleContentModelData {
         self.thumbnail = None;
         self.fat_article = None;
         self.markdown_content = None;
-        self.filtered_markdown_content = None;
+        self.filtered_markdown_content =
+            article_id.and_then(|article_id| self.cached_pipe_outputs.get(article_id).cloned());
         self.feed = None;
         self.tags = None;
 
         match article_id {
             Some(article_id) => {
                 let article = {
-                    let news_flash = self.news_flash_utils.news_flash_lock.read().await;
-                    let article = news_flash.get_article(article_id)?;
-                    self.feed = news_flash
-                        .get_feeds()?
+                    let accounts = self.news_flash_utils.accounts();
+                    let article = accounts.get_article(article_id).await?;
+                    self.feed = accounts
+                        .get_feeds()
+                        .await?
                         .0
                         .into_iter()
                         .find(|feed| feed.feed_id == article.feed_id);
-                    self.enclosures = Some(news_flash.get_enclosures(article_id)?);
+                    self.enclosures = Some(accounts.get_enclosures(article_id).await?);
                     article
                 };
 
@@ -112,8 +120,7 @@ impl ArticleContentModelData {
 
     pub(super) async fn update_article_tags(&mut self) -> color_eyre::Result<()> {
         if let Some(article_id) = self.article.as_ref().map(|article| &article.article_id) {
-            let news_flash = self.news_flash_utils.news_flash_lock.read().await;
-            let (tags, taggings) = news_flash.get_tags()?;
+            let (tags, taggings) = self.news_flash_utils.accounts().get_tags().await?;
             let mut tag_for_tag_id =
                 NewsFlashUtils::generate_id_map(&tags, |tag| tag.tag_id.clone());
             self.tags = Some(
@@ -251,6 +258,12 @@ impl ArticleContentModelData {
         Ok(())
     }
 
+    pub(super) fn fetch_content_images(&self, urls: Vec<String>, max_bytes: u64) {
+        for url in urls {
+            self.news_flash_utils.fetch_content_image(url, max_bytes);
+        }
+    }
+
     pub(super) fn on_thumbnail_fetch_finished(&mut self, thumbnail: Option<&Thumbnail>) {
         self.thumbnail_fetch_running = false;
         match thumbnail {
@@ -303,8 +316,9 @@ impl ArticleContentModelData {
     }
 
     pub(crate) fn pipe(
-        &self,
+        &mut self,
         config: &Config,
+        options: PipeOptions,
         in_target: PipeTarget,
         out_target: PipeTarget,
         command: &str,
@@ -313,36 +327,19 @@ impl ArticleContentModelData {
             return Err(color_eyre::eyre::eyre!("no article selected"));
         };
 
-        let command = command
-            .replace(
-                "{date}",
-                &article
-                    .date
-                    .with_timezone(&chrono::Local)
-                    .format(&config.date_format)
-                    .to_string(),
-            )
-            .replace("{title}", article.title.as_deref().unwrap_or_default())
-            .replace(
-                "{url}",
-                article
-                    .url
-                    .as_ref()
-                    .map(|url| url.as_str())
-                    .unwrap_or_default(),
-            )
-            .replace("{author}", article.author.as_deref().unwrap_or_default())
-            .replace(
-                "{feed}",
-                self.feed
-                    .as_ref()
-                    .map(|feed| &*feed.label)
-                    .unwrap_or_default(),
-            );
+        if options.cache || config.pipe_cache {
+            self.articles_with_cached_pipe
+                .insert(article.article_id.to_owned());
+        }
 
         self.news_flash_utils.pipe(
-            article.to_owned(),
-            self.fat_article.to_owned(),
+            PipeArticle::new(
+                article,
+                self.feed.as_ref(),
+                self.fat_article.as_ref(),
+                &config.date_format,
+            ),
+            options,
             in_target,
             out_target,
             command.to_string(),
@@ -358,9 +355,7 @@ impl ArticleContentModelData {
         markdown: &Option<String>,
         error: &Option<String>,
     ) {
-        if self.article.as_ref().map(|article| &article.article_id) != Some(article_id) {
-            return;
-        }
+        let cache = self.articles_with_cached_pipe.remove(article_id);
 
         if markdown.is_none()
             && exit_status.success()
@@ -397,6 +392,16 @@ Process returned with error ({})
             ));
         }
 
+        // only successful outputs are cached, also if a different article is selected by now
+        if cache && exit_status.success() && markdown.is_some() {
+            self.cached_pipe_outputs
+                .insert(article_id.to_owned(), markdown_content.to_owned());
+        }
+
+        if self.article.as_ref().map(|article| &article.article_id) != Some(article_id) {
+            return;
+        }
+
         self.filtered_markdown_content.replace(markdown_content);
     }
 }
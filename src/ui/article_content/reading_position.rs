@@ -0,0 +1,203 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use log::{debug, warn};
+use news_flash::models::ArticleID;
+
+const READING_POSITIONS_FILE: &str = "reading_positions.json";
+
+// changed positions are written at most this often while reading (and always on quit)
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub(super) struct ReadingPosition {
+    // relative scroll position (0.0 is the top, 1.0 the end of the content)
+    pub position: f32,
+    // furthest relative position reached so far
+    pub progress: f32,
+    // unix timestamp of the last update, used for pruning old entries
+    pub updated: i64,
+}
+
+pub(super) struct ReadingPositions {
+    path: PathBuf,
+    positions: HashMap<String, ReadingPosition>,
+    is_dirty: bool,
+    saved_at: Instant,
+}
+
+impl ReadingPositions {
+    pub(super) fn load(state_dir: &Path, keep_days: u16) -> Self {
+        let path = state_dir.join(READING_POSITIONS_FILE);
+
+        let mut positions: HashMap<String, ReadingPosition> = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                warn!("unable to parse reading positions in {path:?}: {err}");
+                Default::default()
+            }),
+            Err(err) => {
+                debug!("no reading positions loaded from {path:?}: {err}");
+                Default::default()
+            }
+        };
+
+        // positions of articles which have been purged from the database are of no use
+        let threshold = chrono::Utc::now().timestamp() - i64::from(keep_days) * 24 * 60 * 60;
+        positions.retain(|_, reading_position| reading_position.updated >= threshold);
+
+        Self {
+            path,
+            positions,
+            is_dirty: false,
+            saved_at: Instant::now(),
+        }
+    }
+
+    pub(super) fn get(&self, article_id: &ArticleID) -> Option<&ReadingPosition> {
+        self.positions.get(article_id.as_str())
+    }
+
+    pub(super) fn update(&mut self, article_id: &ArticleID, position: f32, progress: f32) {
+        let reading_position = self
+            .positions
+            .entry(article_id.as_str().to_owned())
+            .or_default();
+
+        if reading_position.position == position && reading_position.progress == progress {
+            return;
+        }
+
+        reading_position.position = position;
+        reading_position.progress = progress;
+        reading_position.updated = chrono::Utc::now().timestamp();
+        self.is_dirty = true;
+    }
+
+    pub(super) fn save(&mut self) -> color_eyre::Result<()> {
+        if !self.is_dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(&self.positions)?)?;
+        self.is_dirty = false;
+        self.saved_at = Instant::now();
+
+        Ok(())
+    }
+
+    pub(super) fn save_if_due(&mut self) -> color_eyre::Result<()> {
+        if self.saved_at.elapsed() < SAVE_INTERVAL {
+            return Ok(());
+        }
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn state_dir(name: &str) -> PathBuf {
+        let state_dir = std::env::temp_dir().join(format!(
+            "eilmeldung-reading-positions-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&state_dir);
+        state_dir
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let state_dir = state_dir("save");
+        let article_id = ArticleID::new("article");
+
+        let mut reading_positions = ReadingPositions::load(&state_dir, 30);
+        assert!(reading_positions.get(&article_id).is_none());
+
+        reading_positions.update(&article_id, 0.25, 0.5);
+        reading_positions.save().unwrap();
+        assert!(!reading_positions.is_dirty);
+
+        let reading_positions = ReadingPositions::load(&state_dir, 30);
+        let reading_position = reading_positions.get(&article_id).unwrap();
+        assert_eq!(reading_position.position, 0.25);
+        assert_eq!(reading_position.progress, 0.5);
+
+        std::fs::remove_dir_all(&state_dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_prunes_old_positions() {
+        let state_dir = state_dir("prune");
+        std::fs::create_dir_all(&state_dir).unwrap();
+
+        let now = chrono::Utc::now().timestamp();
+        let positions = HashMap::from([
+            (
+                "recent".to_owned(),
+                ReadingPosition {
+                    position: 0.1,
+                    progress: 0.1,
+                    updated: now - 24 * 60 * 60,
+                },
+            ),
+            (
+                "old".to_owned(),
+                ReadingPosition {
+                    position: 0.9,
+                    progress: 0.9,
+                    updated: now - 10 * 24 * 60 * 60,
+                },
+            ),
+        ]);
+        std::fs::write(
+            state_dir.join(READING_POSITIONS_FILE),
+            serde_json::to_string(&positions).unwrap(),
+        )
+        .unwrap();
+
+        let reading_positions = ReadingPositions::load(&state_dir, 7);
+        assert!(reading_positions.get(&ArticleID::new("recent")).is_some());
+        assert!(reading_positions.get(&ArticleID::new("old")).is_none());
+
+        std::fs::remove_dir_all(&state_dir).unwrap();
+    }
+
+    #[test]
+    fn test_unchanged_position_is_not_saved() {
+        let state_dir = state_dir("unchanged");
+        let article_id = ArticleID::new("article");
+
+        let mut reading_positions = ReadingPositions::load(&state_dir, 30);
+        reading_positions.update(&article_id, 0.5, 0.5);
+        reading_positions.save().unwrap();
+
+        reading_positions.update(&article_id, 0.5, 0.5);
+        assert!(!reading_positions.is_dirty);
+
+        std::fs::remove_dir_all(&state_dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_if_due() {
+        let state_dir = state_dir("due");
+        let article_id = ArticleID::new("article");
+
+        let mut reading_positions = ReadingPositions::load(&state_dir, 30);
+        reading_positions.update(&article_id, 0.25, 0.25);
+        reading_positions.save_if_due().unwrap();
+        assert!(reading_positions.is_dirty);
+
+        reading_positions.saved_at -= SAVE_INTERVAL;
+        reading_positions.save_if_due().unwrap();
+        assert!(!reading_positions.is_dirty);
+
+        std::fs::remove_dir_all(&state_dir).unwrap();
+    }
+}
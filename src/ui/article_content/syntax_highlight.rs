@@ -0,0 +1,149 @@
+use std::sync::LazyLock;
+
+use log::debug;
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use syntect::{
+    parsing::{ParseState, Scope, ScopeStack, SyntaxSet},
+    util::LinesWithEndings,
+};
+
+use crate::prelude::*;
+
+const TAB_WIDTH: usize = 4;
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+#[derive(Debug, Clone, Copy)]
+enum TokenKind {
+    Keyword,
+    String,
+    Comment,
+    Number,
+    Type,
+    Function,
+}
+
+// scope prefixes mapped to token kinds, more specific scopes come first
+static TOKEN_SCOPES: LazyLock<Vec<(Scope, TokenKind)>> = LazyLock::new(|| {
+    use TokenKind as T;
+    [
+        ("comment", T::Comment),
+        ("string", T::String),
+        ("constant.character", T::String),
+        ("constant.numeric", T::Number),
+        ("constant.language", T::Number),
+        ("entity.name.type", T::Type),
+        ("entity.name.class", T::Type),
+        ("entity.name.struct", T::Type),
+        ("entity.name.enum", T::Type),
+        ("support.type", T::Type),
+        ("support.class", T::Type),
+        ("entity.name.function", T::Function),
+        ("support.function", T::Function),
+        ("variable.function", T::Function),
+        ("keyword", T::Keyword),
+        ("storage", T::Keyword),
+    ]
+    .into_iter()
+    .filter_map(|(scope, kind)| Scope::new(scope).ok().map(|scope| (scope, kind)))
+    .collect()
+});
+
+fn token_style(scope_stack: &ScopeStack, theme: &Theme) -> Style {
+    let kind = scope_stack.as_slice().iter().rev().find_map(|scope| {
+        TOKEN_SCOPES
+            .iter()
+            .find(|(prefix, _)| prefix.is_prefix_of(*scope))
+            .map(|(_, kind)| *kind)
+    });
+
+    use TokenKind as T;
+    match kind {
+        Some(T::Keyword) => theme.code_keyword(),
+        Some(T::String) => theme.code_string(),
+        Some(T::Comment) => theme.code_comment(),
+        Some(T::Number) => theme.code_number(),
+        Some(T::Type) => theme.code_type(),
+        Some(T::Function) => theme.code_function(),
+        None => theme.code(),
+    }
+}
+
+fn expand_tabs(code_line: &str) -> String {
+    code_line.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+fn plain_lines(content: &str, theme: &Theme) -> Vec<Line<'static>> {
+    content
+        .lines()
+        .map(|code_line| Line::from(Span::styled(expand_tabs(code_line), theme.code())))
+        .collect()
+}
+
+fn highlighted_lines(
+    parse_state: &mut ParseState,
+    content: &str,
+    theme: &Theme,
+) -> color_eyre::Result<Vec<Line<'static>>> {
+    let mut scope_stack = ScopeStack::new();
+    let mut lines = Vec::new();
+
+    for code_line in LinesWithEndings::from(content) {
+        let ops = parse_state.parse_line(code_line, &SYNTAX_SET)?;
+        let code_line = code_line.trim_end_matches(['\n', '\r']);
+
+        let mut spans = Vec::new();
+        let mut start = 0;
+        for (position, op) in ops {
+            let position = position.min(code_line.len());
+            if position > start {
+                spans.push(Span::styled(
+                    expand_tabs(&code_line[start..position]),
+                    token_style(&scope_stack, theme),
+                ));
+                start = position;
+            }
+            scope_stack.apply(&op)?;
+        }
+        if start < code_line.len() {
+            spans.push(Span::styled(
+                expand_tabs(&code_line[start..]),
+                token_style(&scope_stack, theme),
+            ));
+        }
+
+        lines.push(Line::from(spans));
+    }
+
+    Ok(lines)
+}
+
+/// Renders the lines of a code block, highlighting the syntax if the language is known
+pub(super) fn highlight_code_block(lang: &str, content: &str, theme: &Theme) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    if !lang.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("[{lang}]"),
+            theme.inactive(),
+        )));
+    }
+
+    let syntax = (!lang.is_empty())
+        .then(|| SYNTAX_SET.find_syntax_by_token(lang))
+        .flatten();
+
+    let code_lines = match syntax {
+        Some(syntax) => highlighted_lines(&mut ParseState::new(syntax), content, theme)
+            .unwrap_or_else(|err| {
+                debug!("unable to highlight code block ({lang}): {err}");
+                plain_lines(content, theme)
+            }),
+        None => plain_lines(content, theme),
+    };
+
+    lines.extend(code_lines);
+    lines
+}
@@ -1,15 +1,18 @@
 use super::model::ArticleContentModelData;
+use super::syntax_highlight::highlight_code_block;
 use crate::prelude::*;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io::Cursor,
+    ops::Range,
     sync::{Arc, Mutex},
 };
 
 use getset::{Getters, MutGetters};
 use image::ImageReader;
-use log::info;
+use indexmap::IndexMap;
+use log::{debug, info};
 use news_flash::models::Enclosure;
 use ratatui::layout::Flex;
 use ratatui_image::{
@@ -18,6 +21,15 @@ use ratatui_image::{
 use the_other_tui_markdown::RendererBuilder;
 use throbber_widgets_tui::{Throbber, ThrobberState, WhichUse};
 
+// lines shown above a search match when jumping to it
+const SEARCH_CONTEXT_LINES: u16 = 2;
+
+// columns scrolled horizontally in code blocks and tables
+const HORIZONTAL_SCROLL_AMOUNT: u16 = 8;
+
+// maximum number of decoded inline images kept in memory
+const MAX_CACHED_CONTENT_IMAGES: usize = 32;
+
 const NO_THUMB_PLACEHOLDER: &[u8] =
     include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/no-thumb.png"));
 
@@ -28,10 +40,44 @@ pub struct ArticleContentViewData {
     vertical_scroll: u16,
     #[getset(get = "pub(super)")]
     max_scroll: u16,
+    // code blocks and tables are not wrapped but scrolled horizontally
+    horizontal_scroll: u16,
+    max_horizontal_scroll: u16,
 
     #[getset(get = "pub(super), get_mut = "pub(super))]
     scrollbar_state: ScrollbarState,
 
+    // Reading position state
+    pending_position: Option<f32>,
+    #[getset(get = "pub(super)")]
+    progress: Option<f32>,
+    // true once actual content (not the placeholder) has been laid out
+    content_rendered: bool,
+    // the end of an article only counts as read if the user has scrolled
+    scrolled: bool,
+
+    // In-article search state
+    search_term: Option<SearchTerm>,
+    search_match_rows: Vec<u16>,
+    current_search_match: Option<usize>,
+    search_jump_pending: bool,
+
+    // Headings of the markdown content: level, title and (wrapped) row
+    #[getset(get = "pub(super)")]
+    headings: Vec<(u8, String, u16)>,
+
+    // Indices of the lines of code blocks and tables
+    preformatted_lines: Vec<usize>,
+
+    // Inline images: URL and index of the first line reserved for the image
+    image_lines: Vec<(String, usize)>,
+    // URL of the article against which relative image URLs are resolved
+    base_url: Option<url::Url>,
+    // decoded inline images by URL (None if fetching or decoding failed)
+    content_images: IndexMap<String, Option<StatefulProtocol>>,
+    content_images_fetching: HashSet<String>,
+    content_images_to_fetch: Vec<String>,
+
     // Image rendering state
     image: Option<StatefulProtocol>,
     placeholder_image: StatefulProtocol,
@@ -59,11 +105,28 @@ impl Default for ArticleContentViewData {
         Self {
             vertical_scroll: 0,
             max_scroll: 0,
+            horizontal_scroll: 0,
+            max_horizontal_scroll: 0,
             image: None,
             placeholder_image,
             picker, // TODO gracefully handle errors
             thumbnail_fetching_throbber: ThrobberState::default(),
             scrollbar_state: ScrollbarState::default(),
+            pending_position: None,
+            progress: None,
+            content_rendered: false,
+            scrolled: false,
+            search_term: None,
+            search_match_rows: Vec::default(),
+            current_search_match: None,
+            search_jump_pending: false,
+            headings: Vec::default(),
+            preformatted_lines: Vec::default(),
+            image_lines: Vec::default(),
+            base_url: None,
+            content_images: IndexMap::default(),
+            content_images_fetching: HashSet::default(),
+            content_images_to_fetch: Vec::default(),
             url_for_hint: Default::default(),
         }
     }
@@ -101,30 +164,192 @@ impl ArticleContentViewData {
         self.thumbnail_fetching_throbber.calc_next();
     }
 
+    // a position which is still to be restored is dropped once the user scrolls
+    fn on_scrolled(&mut self) {
+        self.scrolled = true;
+        self.pending_position = None;
+    }
+
     pub(super) fn scroll_up(&mut self) {
+        self.on_scrolled();
         self.vertical_scroll = self.vertical_scroll.saturating_sub(1);
     }
 
     pub(super) fn scroll_down(&mut self) {
+        self.on_scrolled();
         self.vertical_scroll = (self.vertical_scroll + 1).min(self.max_scroll);
     }
 
     pub(super) fn scroll_page_up(&mut self, scroll_amount: u16) {
+        self.on_scrolled();
         self.vertical_scroll = self.vertical_scroll.saturating_sub(scroll_amount);
     }
 
     pub(super) fn scroll_page_down(&mut self, scroll_amount: u16) {
+        self.on_scrolled();
         self.vertical_scroll = (self.vertical_scroll + scroll_amount).min(self.max_scroll);
     }
 
     pub(super) fn scroll_to_top(&mut self) {
+        self.on_scrolled();
         self.vertical_scroll = 0;
     }
 
     pub(super) fn scroll_to_bottom(&mut self) {
+        self.on_scrolled();
         self.vertical_scroll = self.max_scroll;
     }
 
+    pub(super) fn scroll_left(&mut self) {
+        self.horizontal_scroll = self
+            .horizontal_scroll
+            .saturating_sub(HORIZONTAL_SCROLL_AMOUNT);
+    }
+
+    pub(super) fn scroll_right(&mut self) {
+        self.horizontal_scroll =
+            (self.horizontal_scroll + HORIZONTAL_SCROLL_AMOUNT).min(self.max_horizontal_scroll);
+    }
+
+    pub(super) fn restore_reading_position(
+        &mut self,
+        position: Option<f32>,
+        progress: Option<f32>,
+    ) {
+        self.vertical_scroll = 0;
+        self.horizontal_scroll = 0;
+        // the scroll offset can only be computed when the content has been laid out
+        self.pending_position = position;
+        self.progress = progress;
+        self.content_rendered = false;
+        self.scrolled = false;
+        self.search_match_rows.clear();
+        self.current_search_match = None;
+    }
+
+    pub(super) fn set_search_term(&mut self, search_term: Option<SearchTerm>) {
+        self.search_jump_pending = search_term.is_some();
+        self.search_term = search_term;
+        self.search_match_rows.clear();
+        self.current_search_match = None;
+    }
+
+    pub(super) fn has_search_term(&self) -> bool {
+        self.search_term.is_some()
+    }
+
+    pub(super) fn num_search_matches(&self) -> usize {
+        self.search_match_rows.len()
+    }
+
+    pub(super) fn take_content_images_to_fetch(&mut self) -> Vec<String> {
+        self.content_images_fetching
+            .extend(self.content_images_to_fetch.iter().cloned());
+        std::mem::take(&mut self.content_images_to_fetch)
+    }
+
+    // images which could not be fetched or decoded are fetched again
+    pub(super) fn retry_failed_content_images(&mut self) {
+        self.content_images.retain(|_, image| image.is_some());
+    }
+
+    pub(super) fn on_content_image_fetched(&mut self, url: &str, data: Option<&[u8]>) {
+        self.content_images_fetching.remove(url);
+
+        let image = data.and_then(|data| {
+            ImageReader::new(Cursor::new(data))
+                .with_guessed_format()
+                .ok()?
+                .decode()
+                .inspect_err(|err| debug!("unable to decode content image {url}: {err}"))
+                .ok()
+        });
+
+        self.content_images.insert(
+            url.to_owned(),
+            image.map(|image| self.picker.new_resize_protocol(image)),
+        );
+
+        if self.content_images.len() > MAX_CACHED_CONTENT_IMAGES {
+            self.content_images.shift_remove_index(0);
+        }
+    }
+
+    pub(super) fn goto_heading(&mut self, index: usize) -> bool {
+        let Some((_, _, row)) = self.headings.get(index) else {
+            return false;
+        };
+        self.vertical_scroll = (*row).min(self.max_scroll);
+        true
+    }
+
+    pub(super) fn next_heading(&mut self, reverse: bool) -> bool {
+        let vertical_scroll = self.vertical_scroll;
+        let heading = if reverse {
+            self.headings
+                .iter()
+                .rposition(|(_, _, row)| *row < vertical_scroll)
+        } else {
+            self.headings
+                .iter()
+                .position(|(_, _, row)| *row > vertical_scroll)
+        };
+
+        // the last headings may not be reachable as the scroll position is capped
+        match heading {
+            Some(index) if self.headings[index].2.min(self.max_scroll) != vertical_scroll => {
+                self.goto_heading(index)
+            }
+            _ => false,
+        }
+    }
+
+    // jumps to the next (or previous) match and returns true if the search wrapped around
+    pub(super) fn search_next(&mut self, reverse: bool) -> bool {
+        let num_matches = self.search_match_rows.len();
+        if num_matches == 0 {
+            return false;
+        }
+
+        let (next_match, wrapped) = match (self.current_search_match, reverse) {
+            (Some(current), false) => ((current + 1) % num_matches, current + 1 == num_matches),
+            (Some(current), true) => ((current + num_matches - 1) % num_matches, current == 0),
+            (None, false) => self
+                .search_match_rows
+                .iter()
+                .position(|row| *row >= self.vertical_scroll)
+                .map(|index| (index, false))
+                .unwrap_or((0, true)),
+            (None, true) => self
+                .search_match_rows
+                .iter()
+                .rposition(|row| *row <= self.vertical_scroll)
+                .map(|index| (index, false))
+                .unwrap_or((num_matches - 1, true)),
+        };
+
+        self.current_search_match = Some(next_match);
+        self.vertical_scroll = self.search_match_rows[next_match]
+            .saturating_sub(SEARCH_CONTEXT_LINES)
+            .min(self.max_scroll);
+
+        wrapped
+    }
+
+    pub(super) fn reading_position(&self) -> Option<f32> {
+        if !self.content_rendered {
+            return None;
+        }
+
+        // short articles (which fit on the screen) have no position
+        (self.max_scroll > 0).then(|| f32::from(self.vertical_scroll) / f32::from(self.max_scroll))
+    }
+
+    // short articles (which fit on the screen) are at the end once the user tried to scroll
+    pub(super) fn is_at_end(&self) -> bool {
+        self.content_rendered && self.scrolled && self.vertical_scroll >= self.max_scroll
+    }
+
     pub(super) fn render_block(
         &mut self,
         area: Rect,
@@ -132,7 +357,22 @@ impl ArticleContentViewData {
         config: &Config,
         is_focused: bool,
     ) -> Rect {
+        let search_title = match self.search_term.as_ref() {
+            Some(search_term) => Line::styled(
+                format!(
+                    " {search_term} {}/{} ",
+                    self.current_search_match
+                        .map(|index| (index + 1).to_string())
+                        .unwrap_or("-".to_owned()),
+                    self.search_match_rows.len()
+                ),
+                config.theme.header(),
+            ),
+            None => "".into(),
+        };
+
         let block = Block::default()
+            .title_top(search_title)
             .borders(Borders::all())
             .border_type(config.border_theme.eff_type(is_focused))
             .merge_borders(config.border_theme.framing.eff_merge_strategy())
@@ -151,6 +391,19 @@ impl ArticleContentViewData {
                     "".into()
                 }
                 .alignment(HorizontalAlignment::Right),
+            )
+            .title_top(
+                if let Some(progress) = self.progress
+                    && config.content_show_position
+                {
+                    Line::styled(
+                        format!(" {:.0}% read ", progress * 100.0),
+                        config.theme.header(),
+                    )
+                } else {
+                    "".into()
+                }
+                .alignment(HorizontalAlignment::Right),
             );
 
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
@@ -435,6 +688,22 @@ impl ArticleContentViewData {
             .constraints([text_constraint])
             .areas(content_area);
 
+        self.base_url = model_data
+            .article()
+            .as_ref()
+            .and_then(|article| article.url.as_ref())
+            .map(|url| (**url).clone());
+
+        let fat_article = model_data.fat_article().as_ref();
+        let scraped = fat_article.is_some_and(|fat_article| fat_article.scraped_content.is_some());
+        let has_content = model_data.filtered_markdown_content().is_some()
+            || scraped
+            || fat_article.is_some_and(|fat_article| fat_article.plain_text.is_some());
+        // the plain text is replaced once the scraped content arrives
+        let is_final_content = model_data.filtered_markdown_content().is_some()
+            || scraped
+            || (has_content && !config.auto_scrape);
+
         // prefer filtered content
         let text: Text<'_> = if let Some(filtered_markdown_content) =
             model_data.filtered_markdown_content().as_deref()
@@ -470,14 +739,46 @@ impl ArticleContentViewData {
             Text::from("no content available")
         };
 
-        // Calculate the total number of lines the content would take when wrapped
-        let content_lines = Self::calculate_wrapped_lines(&text, paragraph_area.width);
+        if model_data.filtered_markdown_content().is_none()
+            && model_data.markdown_content().is_none()
+        {
+            self.headings.clear();
+            self.preformatted_lines.clear();
+        }
+
+        let text = self.cut_preformatted_lines(text, paragraph_area.width, config);
+
+        // Calculate the row of each line and the total number of lines the content would take when wrapped
+        let line_rows = Self::calculate_line_rows(&text, paragraph_area.width);
+        let content_lines = line_rows.last().copied().unwrap_or_default()
+            + text
+                .lines
+                .last()
+                .map(|line| Self::calculate_wrapped_line(line, paragraph_area.width))
+                .unwrap_or_default();
+        self.update_heading_rows(&text, &line_rows);
 
         // Calculate maximum scroll (ensure it doesn't go negative)
         let max_scroll = content_lines.saturating_sub(paragraph_area.height);
 
+        // Restore the reading position now that the content length is known; the position is kept
+        // until the final content has been laid out
+        let vertical_scroll = match self.pending_position {
+            Some(position) if has_content => {
+                if is_final_content {
+                    self.pending_position = None;
+                }
+                (position * f32::from(max_scroll)).round() as u16
+            }
+            _ => vertical_scroll,
+        };
+
         // Ensure current scroll doesn't exceed maximum
-        let vertical_scroll = vertical_scroll.min(max_scroll);
+        self.max_scroll = max_scroll;
+        self.vertical_scroll = vertical_scroll.min(max_scroll);
+
+        let text = self.highlight_search_matches(text, &line_rows, paragraph_area.width, config);
+        let vertical_scroll = self.vertical_scroll;
 
         let content = Paragraph::new(text)
             .wrap(Wrap { trim: true })
@@ -485,8 +786,18 @@ impl ArticleContentViewData {
 
         content.render(paragraph_area, buf);
 
-        self.max_scroll = max_scroll;
-        self.vertical_scroll = vertical_scroll;
+        if config.content_show_images {
+            self.render_content_images(paragraph_area, &line_rows, config, buf);
+        }
+
+        self.content_rendered = has_content;
+        if let Some(position) = self.reading_position() {
+            self.progress = Some(
+                self.progress
+                    .map(|progress| progress.max(position))
+                    .unwrap_or(position),
+            );
+        }
     }
 
     fn markdown_to_text(&mut self, markdown: &str, config: &Config) -> Text<'static> {
@@ -517,8 +828,30 @@ impl ArticleContentViewData {
         let image_icon = config.icon_set.image_icon();
         let url_icon = config.icon_set.url_icon();
 
+        let image_hints = Arc::new(Mutex::new(Vec::<(String, String)>::new()));
+        let inner_image_hints = image_hints.clone();
+
+        let headings = Arc::new(Mutex::new(Vec::<(u8, String)>::new()));
+        let inner_headings = headings.clone();
+        let markdown_theme = the_other_tui_markdown::Theme {
+            table_header: config.theme.table_header(),
+            table_separator: config.theme.table_separator(),
+            ..Default::default()
+        };
+        let table_separator_style = markdown_theme.table_separator;
+
+        let code_blocks = Arc::new(Mutex::new(Vec::<Vec<Line<'static>>>::new()));
+        let inner_code_blocks = code_blocks.clone();
+        let code_theme = config.theme.clone();
+
         let text = {
             let renderer = RendererBuilder::new()
+                .with_theme(markdown_theme.clone())
+                .with_code_block(move |lang, content| {
+                    let lines = highlight_code_block(lang, content, &code_theme);
+                    inner_code_blocks.lock().unwrap().push(lines.clone()); // unwrap is save here: locking with sync calls
+                    lines
+                })
                 .with_link(move |alt, url| {
                     let mut url_for_hint = inner_link_url_for_hint.lock().unwrap(); // unwrap is save here: locking with sync calls
                     let hint = inner_link_hint_iterator.lock().unwrap().next().unwrap(); // unwrap is save here: locking with sync calls
@@ -542,6 +875,10 @@ impl ArticleContentViewData {
                     url_for_hint
                         .entry(hint.to_owned())
                         .or_insert(url.to_owned());
+                    inner_image_hints
+                        .lock()
+                        .unwrap() // unwrap is save here: locking with sync calls
+                        .push((format!("{hint}{image_icon}"), url.to_owned()));
                     let mut spans = vec![
                         Span::styled(format!("{hint}{image_icon}"), image_hint_style),
                         Span::styled(alt.to_owned(), image_alt_text_style),
@@ -552,46 +889,439 @@ impl ArticleContentViewData {
                     }
                     spans
                 })
+                .with_heading(move |level, spans| {
+                    // same as the default rendering but remembering the heading
+                    let title = spans
+                        .iter()
+                        .map(|span| span.content.as_ref())
+                        .collect::<String>();
+                    inner_headings.lock().unwrap().push((level, title)); // unwrap is save here: locking with sync calls
+                    let style = match level {
+                        1 => markdown_theme.h1,
+                        2 => markdown_theme.h2,
+                        3 => markdown_theme.h3,
+                        4 => markdown_theme.h4,
+                        5 => markdown_theme.h5,
+                        _ => markdown_theme.h6,
+                    };
+                    let mut heading_spans = vec![Span::styled(
+                        format!("{} ", "#".repeat(level as usize)),
+                        style,
+                    )];
+                    heading_spans.extend(spans);
+                    vec![Line::from(heading_spans)]
+                })
                 .build();
 
             the_other_tui_markdown::into_text_with_renderer(markdown, &renderer).to_owned()
         };
 
+        let text = if config.content_show_images {
+            self.reserve_image_lines(
+                text,
+                &image_hints.lock().unwrap(),
+                config.content_image_height,
+            )
+        } else {
+            self.image_lines.clear();
+            text
+        };
+
+        self.preformatted_lines = Self::find_preformatted_lines(
+            &text,
+            &code_blocks.lock().unwrap(),
+            table_separator_style,
+        );
+
         self.url_for_hint = url_for_hint.lock().unwrap().to_owned();
+        self.headings = headings
+            .lock()
+            .unwrap()
+            .drain(..)
+            .map(|(level, title)| (level, title, 0))
+            .collect();
 
         text
     }
 
-    fn calculate_wrapped_lines(text: &ratatui::text::Text, width: u16) -> u16 {
-        let mut total_lines = 0u16;
+    // locates the lines of code blocks and tables in the rendered text
+    fn find_preformatted_lines(
+        text: &Text,
+        code_blocks: &[Vec<Line<'static>>],
+        table_separator_style: Style,
+    ) -> Vec<usize> {
+        let mut preformatted_lines = Vec::new();
+
+        // code blocks appear in the same order in which they were rendered
+        let mut start = 0;
+        for code_block in code_blocks
+            .iter()
+            .filter(|code_block| !code_block.is_empty())
+        {
+            if let Some(offset) = text.lines[start..]
+                .windows(code_block.len())
+                .position(|lines| lines == code_block.as_slice())
+            {
+                start += offset;
+                preformatted_lines.extend(start..start + code_block.len());
+                start += code_block.len();
+            }
+        }
+
+        // tables are identified by the rule below their header
+        let is_table_rule = |line: &Line| {
+            !line.spans.is_empty()
+                && line.spans.iter().all(|span| {
+                    span.style == table_separator_style
+                        && span.content.chars().all(|c| c == '─' || c == '┼')
+                })
+        };
+        let is_empty = |line: &Line| line.spans.iter().all(|span| span.content.is_empty());
+        for (index, line) in text.lines.iter().enumerate().skip(1) {
+            if is_table_rule(line) && !is_empty(&text.lines[index - 1]) {
+                preformatted_lines.push(index - 1);
+                preformatted_lines.extend(
+                    (index..text.lines.len()).take_while(|index| !is_empty(&text.lines[*index])),
+                );
+            }
+        }
+
+        preformatted_lines.sort_unstable();
+        preformatted_lines.dedup();
+        preformatted_lines
+    }
+
+    // cuts code blocks and tables to the visible columns instead of wrapping them
+    fn cut_preformatted_lines<'a>(
+        &mut self,
+        mut text: Text<'a>,
+        width: u16,
+        config: &Config,
+    ) -> Text<'a> {
+        let width = usize::from(width);
+        let max_line_width = self
+            .preformatted_lines
+            .iter()
+            .filter_map(|index| text.lines.get(*index))
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.chars().count())
+                    .sum()
+            })
+            .max()
+            .unwrap_or(0usize);
+
+        self.max_horizontal_scroll = max_line_width.saturating_sub(width) as u16;
+        self.horizontal_scroll = self.horizontal_scroll.min(self.max_horizontal_scroll);
 
-        for line in text.lines.iter() {
-            if line.spans.is_empty() {
-                total_lines += 1;
+        if width < 2 {
+            return text;
+        }
+
+        let scroll = usize::from(self.horizontal_scroll);
+        let more_style = config.theme.inactive();
+
+        for index in self.preformatted_lines.iter() {
+            let Some(line) = text.lines.get_mut(*index) else {
                 continue;
+            };
+
+            let line_width: usize = line
+                .spans
+                .iter()
+                .map(|span| span.content.chars().count())
+                .sum();
+            let more_left = scroll > 0 && line_width > 0;
+            let more_right = line_width > scroll + width;
+
+            let from = scroll + usize::from(more_left);
+            let to = scroll + width - usize::from(more_right);
+
+            let mut spans = Vec::new();
+            if more_left {
+                spans.push(Span::styled("←", more_style));
             }
 
-            let line_content: String = line
+            let mut column = 0;
+            for span in line.spans.iter() {
+                let span_width = span.content.chars().count();
+                let (span_from, span_to) = (
+                    from.clamp(column, column + span_width),
+                    to.clamp(column, column + span_width),
+                );
+                if span_from < span_to {
+                    let content = span
+                        .content
+                        .chars()
+                        .skip(span_from - column)
+                        .take(span_to - span_from)
+                        .collect::<String>();
+                    spans.push(Span::styled(content, span.style));
+                }
+                column += span_width;
+            }
+
+            if more_right {
+                spans.push(Span::styled("→", more_style));
+            }
+
+            // leading whitespace would be trimmed by the paragraph, non-breaking spaces are kept
+            if let Some(first) = spans.first_mut() {
+                let trimmed = first.content.trim_start_matches(' ');
+                let indentation = first.content.len() - trimmed.len();
+                if indentation > 0 {
+                    first.content = format!("{}{trimmed}", "\u{00a0}".repeat(indentation)).into();
+                }
+            }
+
+            line.spans = spans;
+        }
+
+        text
+    }
+
+    // inserts empty lines below each line with images into which the images are rendered
+    fn reserve_image_lines(
+        &mut self,
+        text: Text<'static>,
+        image_hints: &[(String, String)],
+        image_height: u16,
+    ) -> Text<'static> {
+        self.image_lines.clear();
+        let mut lines = Vec::with_capacity(text.lines.len());
+
+        for line in text.lines {
+            let urls = line
                 .spans
                 .iter()
-                .map(|span| span.content.as_ref())
-                .collect();
+                .filter_map(|span| {
+                    image_hints
+                        .iter()
+                        .find(|(hint, _)| *hint == span.content)
+                        .map(|(_, url)| url.to_owned())
+                })
+                .collect::<Vec<String>>();
 
-            if line_content.is_empty() {
-                total_lines += 1;
-            } else {
-                // Calculate how many lines this content will take when wrapped
-                let line_width = line_content.chars().count() as u16;
-                let wrapped_lines = (line_width + width - 1) / width.max(1); // Ceiling division
-                total_lines += wrapped_lines.max(1);
+            lines.push(line);
+
+            for url in urls {
+                let url = resolve_url(self.base_url.as_ref(), &url);
+                self.image_lines.push((url, lines.len()));
+                lines.extend((0..image_height).map(|_| Line::default()));
             }
         }
 
-        total_lines
+        Text::from(lines)
+    }
+
+    fn render_content_images(
+        &mut self,
+        paragraph_area: Rect,
+        line_rows: &[u16],
+        config: &Config,
+        buf: &mut Buffer,
+    ) {
+        let image_height = config.content_image_height;
+        let visible_rows = self.vertical_scroll..self.vertical_scroll + paragraph_area.height;
+        // images up to one page above or below the visible area are fetched in advance
+        let prefetch_rows = self.vertical_scroll.saturating_sub(paragraph_area.height)
+            ..self.vertical_scroll + 2 * paragraph_area.height;
+
+        for (url, line_index) in self.image_lines.iter() {
+            let Some(row) = line_rows.get(*line_index).copied() else {
+                continue;
+            };
+
+            let Some(image) = self.content_images.get_mut(url) else {
+                if (prefetch_rows.contains(&row) || prefetch_rows.contains(&(row + image_height)))
+                    && !self.content_images_fetching.contains(url)
+                    && !self.content_images_to_fetch.contains(url)
+                {
+                    self.content_images_to_fetch.push(url.to_owned());
+                }
+                continue;
+            };
+
+            // images can only be rendered as a whole
+            if let Some(image) = image
+                && visible_rows.contains(&row)
+                && row + image_height <= visible_rows.end
+            {
+                let image_area = Rect {
+                    x: paragraph_area.x,
+                    y: paragraph_area.y + (row - self.vertical_scroll),
+                    width: paragraph_area.width,
+                    height: image_height,
+                };
+                StatefulImage::new()
+                    .resize(Resize::Fit(Some(FilterType::Lanczos3)))
+                    .render(image_area, buf, image);
+            }
+        }
+    }
+
+    // rows are only known after wrapping, so the headings are located in the rendered text
+    fn update_heading_rows(&mut self, text: &Text, line_rows: &[u16]) {
+        let mut lines = text.lines.iter().zip(line_rows);
+        for (level, title, heading_row) in self.headings.iter_mut() {
+            let heading_line = format!("{} {title}", "#".repeat(*level as usize));
+            if let Some((_, row)) = lines.find(|(line, _)| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect::<String>()
+                    == heading_line
+            }) {
+                *heading_row = *row;
+            }
+        }
+    }
+
+    fn highlight_search_matches<'a>(
+        &mut self,
+        mut text: Text<'a>,
+        line_rows: &[u16],
+        width: u16,
+        config: &Config,
+    ) -> Text<'a> {
+        let Some(regex) = self.search_term.as_ref().map(SearchTerm::to_regex) else {
+            return text;
+        };
+
+        // first pass: locate all matches and the (wrapped) rows they are displayed in
+        let mut match_rows = Vec::new();
+        let line_matches = text
+            .lines
+            .iter()
+            .zip(line_rows)
+            .map(|(line, row)| {
+                let line_content: String = line
+                    .spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect();
+                let ranges = regex
+                    .find_iter(&line_content)
+                    .filter(|found| !found.is_empty())
+                    .map(|found| found.range())
+                    .collect::<Vec<Range<usize>>>();
+                match_rows.extend(ranges.iter().map(|range| {
+                    let column = line_content[..range.start].chars().count() as u16;
+                    row + column / width.max(1)
+                }));
+                ranges
+            })
+            .collect::<Vec<Vec<Range<usize>>>>();
+
+        self.search_match_rows = match_rows;
+        if self.search_jump_pending {
+            self.search_jump_pending = false;
+            self.search_next(false);
+        }
+
+        // second pass: highlight the matches
+        let mut match_index = 0;
+        for (line, ranges) in text.lines.iter_mut().zip(line_matches) {
+            if ranges.is_empty() {
+                continue;
+            }
+            line.spans = Self::highlight_ranges(&line.spans, &ranges, |index, style| {
+                let style = config.theme.highlighted(&style);
+                if self.current_search_match == Some(match_index + index) {
+                    config.theme.selected(&style)
+                } else {
+                    style
+                }
+            });
+            match_index += ranges.len();
+        }
+
+        text
+    }
+
+    fn highlight_ranges<'a>(
+        spans: &[Span<'a>],
+        ranges: &[Range<usize>],
+        to_match_style: impl Fn(usize, Style) -> Style,
+    ) -> Vec<Span<'a>> {
+        let mut highlighted_spans = Vec::new();
+        let mut offset = 0;
+
+        for span in spans {
+            let content = span.content.as_ref();
+            let span_end = offset + content.len();
+            let mut position = offset;
+
+            for (index, range) in ranges.iter().enumerate() {
+                let start = range.start.max(position);
+                let end = range.end.min(span_end);
+                if start >= end {
+                    continue;
+                }
+                if start > position {
+                    highlighted_spans.push(Span::styled(
+                        content[position - offset..start - offset].to_owned(),
+                        span.style,
+                    ));
+                }
+                highlighted_spans.push(Span::styled(
+                    content[start - offset..end - offset].to_owned(),
+                    to_match_style(index, span.style),
+                ));
+                position = end;
+            }
+
+            if position < span_end {
+                highlighted_spans.push(Span::styled(
+                    content[position - offset..].to_owned(),
+                    span.style,
+                ));
+            }
+
+            offset = span_end;
+        }
+
+        highlighted_spans
+    }
+
+    // returns the first (wrapped) row of each line
+    fn calculate_line_rows(text: &ratatui::text::Text, width: u16) -> Vec<u16> {
+        text.lines
+            .iter()
+            .scan(0u16, |row, line| {
+                let line_row = *row;
+                *row += Self::calculate_wrapped_line(line, width);
+                Some(line_row)
+            })
+            .collect()
+    }
+
+    fn calculate_wrapped_line(line: &Line, width: u16) -> u16 {
+        if line.spans.is_empty() {
+            return 1;
+        }
+
+        let line_content: String = line
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+
+        if line_content.is_empty() {
+            1
+        } else {
+            // Calculate how many lines this content will take when wrapped
+            let line_width = line_content.chars().count() as u16;
+            let wrapped_lines = (line_width + width - 1) / width.max(1); // Ceiling division
+            wrapped_lines.max(1)
+        }
     }
 
     pub fn picker_updated(&mut self, picker: &Picker) -> color_eyre::Result<()> {
         self.picker = picker.to_owned();
+        // inline images have to be fetched again for the new protocol
+        self.content_images.clear();
         let cursor = Cursor::new(NO_THUMB_PLACEHOLDER);
         self.placeholder_image = self.picker.new_resize_protocol(
             ImageReader::new(cursor)
@@ -0,0 +1,111 @@
+use std::{collections::HashMap, ops::Range};
+
+use chrono::{Datelike, Days, NaiveDate};
+
+// consecutive articles in the article list which are shown below a header row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArticleGroup {
+    pub label: String,
+    pub articles: Range<usize>,
+}
+
+// sorts the articles stably into groups; the groups are ordered by their first article so that
+// the sort order of the articles is kept as far as possible
+pub fn group_articles<A>(
+    articles: &mut Vec<A>,
+    label_of: impl Fn(&A) -> String,
+) -> Vec<ArticleGroup> {
+    let mut position_of_label: HashMap<String, usize> = HashMap::new();
+    let mut grouped: Vec<(String, Vec<A>)> = Vec::new();
+
+    for article in articles.drain(..) {
+        let label = label_of(&article);
+        let position = *position_of_label.entry(label.clone()).or_insert_with(|| {
+            grouped.push((label, Vec::new()));
+            grouped.len() - 1
+        });
+        grouped[position].1.push(article);
+    }
+
+    grouped
+        .into_iter()
+        .map(|(label, group_articles)| {
+            let start = articles.len();
+            articles.extend(group_articles);
+            ArticleGroup {
+                label,
+                articles: start..articles.len(),
+            }
+        })
+        .collect()
+}
+
+pub fn day_label(date: NaiveDate, today: NaiveDate) -> String {
+    if date == today {
+        "Today".to_owned()
+    } else if Some(date) == today.checked_sub_days(Days::new(1)) {
+        "Yesterday".to_owned()
+    } else {
+        date.format("%A, %Y-%m-%d").to_string()
+    }
+}
+
+pub fn week_label(date: NaiveDate, today: NaiveDate) -> String {
+    let week = date.iso_week();
+    if week == today.iso_week() {
+        "This week".to_owned()
+    } else if today
+        .checked_sub_days(Days::new(7))
+        .is_some_and(|last_week| last_week.iso_week() == week)
+    {
+        "Last week".to_owned()
+    } else {
+        format!("Week {}, {}", week.week(), week.year())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_group_articles() {
+        let mut articles = vec!["a1", "b1", "a2", "c1", "b2"];
+
+        let groups = group_articles(&mut articles, |title| title[..1].to_owned());
+
+        assert_eq!(articles, ["a1", "a2", "b1", "b2", "c1"]);
+        assert_eq!(
+            groups,
+            [
+                ArticleGroup {
+                    label: "a".to_owned(),
+                    articles: 0..2
+                },
+                ArticleGroup {
+                    label: "b".to_owned(),
+                    articles: 2..4
+                },
+                ArticleGroup {
+                    label: "c".to_owned(),
+                    articles: 4..5
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_date_labels() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let date = |day| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+
+        assert_eq!(day_label(date(18), today), "Today");
+        assert_eq!(day_label(date(17), today), "Yesterday");
+        assert_eq!(day_label(date(1), today), "Thursday, 2026-10-01");
+
+        // 2026-10-18 is a Sunday
+        assert_eq!(week_label(date(12), today), "This week");
+        assert_eq!(week_label(date(11), today), "Last week");
+        assert_eq!(week_label(date(1), today), "Week 40, 2026");
+    }
+}
@@ -1,3 +1,4 @@
+mod groups;
 mod model;
 mod view;
 
@@ -8,6 +9,7 @@ pub mod prelude {
 use crate::ui::articles_list::{model::ArticleListModelData, view::FilterState};
 use log::trace;
 use news_flash::models::{Article, ArticleID, Marked, Read, Tag};
+use ratatui_image::picker::Picker;
 use view::ArticleListViewData;
 
 use crate::prelude::*;
@@ -35,14 +37,24 @@ impl ArticlesList {
         news_flash_utils: Arc<NewsFlashUtils>,
         message_sender: UnboundedSender<Message>,
     ) -> Self {
+        let mut view_data = ArticleListViewData::default();
+        if config.article_card_thumbnails {
+            view_data.set_picker(Picker::from_query_stdio().ok());
+        }
+
         Self {
             config: config.clone(),
             message_sender,
 
-            view_data: ArticleListViewData::default(),
-            filter_state: FilterState::new(config.article_scope, config.default_sort_order.clone()),
+            view_data,
+            filter_state: FilterState::new(
+                config.article_scope,
+                config.default_sort_order.clone(),
+                config.group_by,
+                config.article_list_layout,
+            ),
 
-            model_data: ArticleListModelData::new(news_flash_utils.clone()),
+            model_data: ArticleListModelData::new(config.clone(), news_flash_utils.clone()),
 
             is_focused: false,
         }
@@ -53,7 +65,7 @@ impl ArticlesList {
         article_id: Option<&ArticleID>,
     ) -> color_eyre::Result<()> {
         // get current selection index
-        let current_index = self.view_data.get_table_state().selected().unwrap_or(0);
+        let current_index = self.current_article_position().unwrap_or(0);
 
         // save offset distance
         let offset = *self.view_data.get_table_state_mut().offset_mut();
@@ -72,8 +84,11 @@ impl ArticlesList {
                 .iter()
                 .position(|article| article.article_id == *article_id)
         {
-            *self.view_data.get_table_state_mut().offset_mut() =
-                index.saturating_sub(offset_distance);
+            *self.view_data.get_table_state_mut().offset_mut() = self
+                .view_data
+                .row_of_article(index)
+                .unwrap_or_default()
+                .saturating_sub(offset_distance);
             return self.select_index_and_send_message(Some(index));
         }
 
@@ -92,28 +107,46 @@ impl ArticlesList {
         &mut self,
         index: Option<usize>,
     ) -> color_eyre::Result<()> {
-        let index = index
-            .or(self.view_data.get_table_state().selected())
-            .unwrap_or_default();
-
-        match self.model_data.articles().get(index) {
-            Some(article) => {
-                self.view_data.table_state_mut().select(Some(index));
-                self.message_sender
-                    .send(Message::Event(Event::ArticleSelected(Some(
-                        article.article_id.to_owned(),
-                    ))))?;
-            }
-            None => {
-                self.message_sender
-                    .send(Message::Event(Event::ArticleSelected(None)))?;
-            }
+        let row = match index {
+            Some(index) => self.view_data.row_of_article(index),
+            None => self.view_data.get_table_state().selected(),
+        }
+        .unwrap_or_default();
+
+        self.select_row_and_send_message(row, true)
+    }
+
+    // selects the row (or the nearest selectable row in the given direction) and sends the
+    // selected article; a selected group header selects no article
+    fn select_row_and_send_message(&mut self, row: usize, forward: bool) -> color_eyre::Result<()> {
+        let mut article_id = None;
+
+        if self.view_data.row_count() > 0 {
+            let row = self.view_data.selectable_row(row, forward);
+            self.view_data.table_state_mut().select(Some(row));
+            article_id = self
+                .view_data
+                .article_index(row)
+                .and_then(|index| self.model_data.articles().get(index))
+                .map(|article| article.article_id.to_owned());
         }
 
+        self.message_sender
+            .send(Message::Event(Event::ArticleSelected(article_id)))?;
+
         self.adjust_offset();
         Ok(())
     }
 
+    // index of the selected article or, if a group header is selected, of the first article of
+    // the group
+    fn current_article_position(&self) -> Option<usize> {
+        self.view_data
+            .table_state()
+            .selected()
+            .and_then(|row| self.view_data.article_position(row))
+    }
+
     pub(super) fn select_next_unread(&mut self, handle_here: bool) -> color_eyre::Result<()> {
         let select = self.first_unread();
 
@@ -134,37 +167,111 @@ impl ArticlesList {
     }
 
     fn first_unread(&self) -> Option<usize> {
-        let current_index = self.view_data.table_state().selected().unwrap_or(0);
+        let current_index = self.current_article_position().unwrap_or(0);
 
+        // articles of collapsed groups are skipped
         self.model_data
             .articles()
             .iter()
             .enumerate()
-            .find(|(index, article)| *index >= current_index && article.unread == Read::Unread)
+            .find(|(index, article)| {
+                *index >= current_index
+                    && article.unread == Read::Unread
+                    && self
+                        .view_data
+                        .row_of_article(*index)
+                        .and_then(|row| self.view_data.article_index(row))
+                        == Some(*index)
+            })
             .map(|(index, _)| index)
     }
 
     pub(super) fn open_in_browser(&mut self, action_scope: &ActionScope) -> color_eyre::Result<()> {
-        let articles = self.get_articles_by_action_scope(action_scope)?;
+        let items = self.share_items(action_scope)?;
 
-        for article in articles.iter() {
-            if let Some(url) = article.url.as_ref()
-                && let Err(error) = webbrowser::open(url.as_ref())
-            {
-                tooltip(
-                    &self.message_sender,
-                    format!("unable to open article in webbrowser: {}", error).as_str(),
-                    TooltipFlavor::Error,
-                )?;
-                break;
-            }
+        if let Err(error) = open_in_browser(&self.config, &items) {
+            tooltip(
+                &self.message_sender,
+                format!("unable to open article in webbrowser: {}", error).as_str(),
+                TooltipFlavor::Error,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn share_action_scope(
+        &mut self,
+        options: ShareOptions,
+        target: &str,
+        action_scope: &ActionScope,
+    ) -> color_eyre::Result<()> {
+        let items = self.share_items(action_scope)?;
+
+        share_with_target(
+            &self.config,
+            self.model_data.news_flash_utils(),
+            &self.message_sender,
+            options,
+            target,
+            Some(action_scope),
+            items,
+        )
+    }
+
+    // title, URL, feed, etc. of the articles in scope which have a URL
+    fn share_items(&mut self, action_scope: &ActionScope) -> color_eyre::Result<Vec<ShareItem>> {
+        Ok(self
+            .get_articles_by_action_scope(action_scope)?
+            .iter()
+            .filter_map(|article| {
+                let tags = self
+                    .model_data
+                    .tags_for_article()
+                    .get(&article.article_id)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|tag_id| self.model_data.tag_map().get(tag_id))
+                    .map(|tag| tag.label.as_str())
+                    .collect();
+                ShareItem::from_article(
+                    article,
+                    self.model_data.feed_map().get(&article.feed_id),
+                    tags,
+                    &self.config.date_format,
+                )
+            })
+            .collect())
+    }
+
+    async fn queue_action_scope(&mut self, action_scope: &ActionScope) -> color_eyre::Result<()> {
+        let news_flash_utils = self.model_data.news_flash_utils().clone();
+        let mut entries = Vec::new();
+        for article in self.get_articles_by_action_scope(action_scope)?.iter() {
+            let enclosures = news_flash_utils
+                .accounts()
+                .get_enclosures(&article.article_id)
+                .await?;
+            entries.extend(QueueEntry::from_article(
+                article,
+                self.model_data.feed_map().get(&article.feed_id),
+                &enclosures,
+                news_flash_utils.downloads(),
+            ));
         }
 
+        self.message_sender
+            .send(Message::Event(Event::PlaybackQueueAppend(entries)))?;
         Ok(())
     }
 
     pub(super) fn get_current_article(&self) -> Option<Article> {
-        if let Some(index) = self.view_data.get_table_state().selected() {
+        if let Some(index) = self
+            .view_data
+            .get_table_state()
+            .selected()
+            .and_then(|row| self.view_data.article_index(row))
+        {
             return self.model_data.articles().get(index).cloned();
         }
         None
@@ -186,6 +293,8 @@ impl ArticlesList {
                     tag_map: self.model_data.tag_map(),
                     last_sync: self.model_data.last_sync(),
                     flagged: self.model_data.flagged_articles(),
+                    duplicates: self.model_data.duplicates(),
+                    downloaded: self.model_data.downloaded_articles(),
                 },
             )
         };
@@ -244,7 +353,7 @@ impl ArticlesList {
                 }
             }
             direction @ (S::Above | S::Below) => {
-                if let Some(index) = self.view_data.table_state().selected() {
+                if let Some(index) = self.current_article_position() {
                     let offset = if matches!(direction, S::Above) { 1 } else { 0 };
                     let (above, below) = self.model_data.articles().split_at(index + offset);
                     (if matches!(direction, S::Above) {
@@ -258,6 +367,14 @@ impl ArticlesList {
                 }
             }
             S::Query(query) => self.model_data.get_queried_articles(query).to_vec(),
+            S::Group => self
+                .view_data
+                .table_state()
+                .selected()
+                .and_then(|row| self.view_data.group_of_row(row))
+                .and_then(|group| self.model_data.groups().get(group))
+                .map(|group| self.model_data.articles()[group.articles.clone()].to_vec())
+                .unwrap_or_default(),
         })
     }
 
@@ -368,7 +485,7 @@ impl ArticlesList {
             );
         };
 
-        if let Some(selected) = self.view_data.get_table_state_mut().selected() {
+        if let Some(selected) = self.current_article_position() {
             let split_index = if !reversed {
                 selected + offset
             } else {
@@ -421,6 +538,33 @@ impl ArticlesList {
         Ok(())
     }
 
+    // collapses or expands the group of the selected row; with all, every group is collapsed if
+    // any is expanded and expanded otherwise
+    fn toggle_groups(&mut self, all: bool) {
+        let groups = self.model_data.groups();
+        let collapsed_groups = self.filter_state.collapsed_groups_mut();
+
+        if all {
+            if groups
+                .iter()
+                .any(|group| !collapsed_groups.contains(&group.label))
+            {
+                collapsed_groups.extend(groups.iter().map(|group| group.label.to_owned()));
+            } else {
+                collapsed_groups.clear();
+            }
+        } else if let Some(group) = self
+            .view_data
+            .table_state()
+            .selected()
+            .and_then(|row| self.view_data.group_of_row(row))
+            .and_then(|group| groups.get(group))
+            && !collapsed_groups.remove(&group.label)
+        {
+            collapsed_groups.insert(group.label.to_owned());
+        }
+    }
+
     fn on_tag_or_untag(
         &mut self,
         action_scope: ActionScope,
@@ -521,6 +665,11 @@ impl ArticlesList {
 impl crate::messages::MessageReceiver for ArticlesList {
     async fn process_command(&mut self, message: &Message) -> color_eyre::Result<()> {
         if matches!(message, Message::Event(Event::Tick)) {
+            for url in self.view_data.take_thumbnails_to_fetch() {
+                self.model_data
+                    .news_flash_utils()
+                    .fetch_card_thumbnail(url, self.config.content_image_max_kilobytes * 1024);
+            }
             return Ok(());
         }
 
@@ -550,12 +699,12 @@ impl crate::messages::MessageReceiver for ArticlesList {
 
             match command {
                 C::NavigateUp if handle_command => {
-                    self.view_data.get_table_state_mut().select_previous();
-                    self.select_index_and_send_message(None)?;
+                    let row = self.view_data.get_table_state().selected().unwrap_or(0);
+                    self.select_row_and_send_message(row.saturating_sub(1), false)?;
                 }
                 C::NavigateDown if handle_command => {
-                    self.view_data.get_table_state_mut().select_next();
-                    self.select_index_and_send_message(None)?;
+                    let row = self.view_data.get_table_state().selected().unwrap_or(0);
+                    self.select_row_and_send_message(row + 1, true)?;
                 }
                 C::NavigatePageUp if handle_command => {
                     let lines = *self.view_data.article_lines();
@@ -563,7 +712,8 @@ impl crate::messages::MessageReceiver for ArticlesList {
                         self.view_data
                             .get_table_state_mut()
                             .scroll_up_by(lines.saturating_sub(1));
-                        self.select_index_and_send_message(None)?;
+                        let row = self.view_data.get_table_state().selected().unwrap_or(0);
+                        self.select_row_and_send_message(row, false)?;
                     };
                 }
                 C::NavigatePageDown if handle_command => {
@@ -572,17 +722,16 @@ impl crate::messages::MessageReceiver for ArticlesList {
                         self.view_data.get_table_state_mut().scroll_down_by(
                             lines.saturating_sub(self.config.articles_after_selection as u16),
                         );
-                        self.select_index_and_send_message(None)?;
+                        let row = self.view_data.get_table_state().selected().unwrap_or(0);
+                        self.select_row_and_send_message(row, true)?;
                     }
                 }
                 C::NavigateFirst if handle_command => {
-                    self.view_data.get_table_state_mut().select_first();
-                    self.select_index_and_send_message(None)?;
+                    self.select_row_and_send_message(0, true)?;
                 }
                 C::NavigateLast if handle_command => {
-                    self.view_data.get_table_state_mut().select_last();
-                    // manually "select" as select_last does not know the number of rows
-                    self.select_index_and_send_message(Some(self.model_data.articles().len() - 1))?;
+                    let last_row = self.view_data.row_count().saturating_sub(1);
+                    self.select_row_and_send_message(last_row, false)?;
                 }
 
                 C::Show(scope) if handle_command => {
@@ -595,6 +744,41 @@ impl crate::messages::MessageReceiver for ArticlesList {
                     self.open_in_browser(&action_scope)?;
                 }
 
+                C::ArticleDownload(enclosure_type, action_scope) => {
+                    let articles = self.get_articles_by_action_scope(&action_scope)?;
+                    self.model_data
+                        .news_flash_utils()
+                        .download_enclosures(articles, enclosure_type);
+                }
+
+                C::Queue(Some(action_scope)) => {
+                    self.queue_action_scope(&action_scope).await?;
+                }
+
+                C::ArticleShare(options, target, Some(action_scope)) => {
+                    self.share_action_scope(options, &target, &action_scope)?;
+                }
+
+                C::Pipe(options, in_target, out_target, Some(action_scope), command) => {
+                    let articles = self.get_articles_by_action_scope(&action_scope)?;
+                    if articles.is_empty() {
+                        tooltip(
+                            &self.message_sender,
+                            "no articles to pipe",
+                            TooltipFlavor::Warning,
+                        )?;
+                    } else {
+                        tooltip(
+                            &self.message_sender,
+                            &*format!("piping {} articles through {command}", articles.len()),
+                            TooltipFlavor::Info,
+                        )?;
+                        self.model_data
+                            .news_flash_utils()
+                            .pipe_articles(articles, options, in_target, out_target, command);
+                    }
+                }
+
                 C::ActionSetRead(action_scope) if handle_command => {
                     self.set_action_scope_read_status(&action_scope, Read::Read)?;
                     view_needs_update = true;
@@ -653,6 +837,11 @@ impl crate::messages::MessageReceiver for ArticlesList {
 
                 C::ArticleListSearch(query) => {
                     *self.filter_state.article_search_query_mut() = Some(query.clone());
+                    if query.uses_duplicates() {
+                        self.model_data
+                            .update_duplicates(&self.filter_state)
+                            .await?;
+                    }
                     self.view_data.update(
                         self.config.clone(),
                         &self.model_data,
@@ -704,6 +893,23 @@ impl crate::messages::MessageReceiver for ArticlesList {
                     model_needs_update = true;
                 }
 
+                C::ArticleListGroupBy(group_by) => {
+                    *self.filter_state.group_by_mut() = group_by;
+                    self.filter_state.collapsed_groups_mut().clear();
+                    model_needs_update = true;
+                }
+
+                C::ArticleListGroupToggle(all) if handle_command => {
+                    self.toggle_groups(all);
+                    view_needs_update = true;
+                }
+
+                C::ArticleListLayout(layout) => {
+                    let layout = layout.unwrap_or(self.filter_state.layout().toggle());
+                    *self.filter_state.layout_mut() = layout;
+                    view_needs_update = true;
+                }
+
                 C::ArticleListSortClear => {
                     self.filter_state.clear_sort_order();
                     model_needs_update = true;
@@ -740,28 +946,46 @@ impl crate::messages::MessageReceiver for ArticlesList {
 
                 MouseArticleClick(row_offset) => {
                     // Select the article at the clicked row offset
-                    let offset = self.view_data.get_table_state_mut().offset();
-                    let target_index = offset + *row_offset as usize;
-                    if target_index < self.model_data.articles().len() {
+                    let target_row = self.view_data.row_at_line(*row_offset as usize);
+                    if self.view_data.is_header(target_row) {
+                        // clicking a group header collapses or expands the group
                         self.view_data
                             .get_table_state_mut()
-                            .select(Some(target_index));
-                        self.select_index_and_send_message(None)?;
+                            .select(Some(target_row));
+                        self.toggle_groups(false);
+                        view_needs_update = true;
+                    } else if target_row < self.view_data.row_count() {
+                        self.select_row_and_send_message(target_row, true)?;
                     }
                 }
 
                 MouseScrollDown(Panel::ArticleList) => {
+                    let max = self.view_data.row_count().saturating_sub(1);
                     let offset = self.view_data.get_table_state_mut().offset_mut();
-                    let max = self.model_data.articles().len().saturating_sub(1);
                     *offset = (*offset).saturating_add(1).min(max);
                 }
 
+                AsyncCardThumbnailFetchFinished(url, data) => {
+                    self.view_data.on_thumbnail_fetched(url, data.as_deref());
+                    self.message_sender
+                        .send(Message::Command(Command::Redraw))?;
+                }
+
+                ImageProtocolPickerUpdated(picker) if self.view_data.has_picker() => {
+                    self.view_data.set_picker(Some(picker.to_owned()));
+                }
+
                 MouseScrollUp(Panel::ArticleList) => {
                     let offset = self.view_data.get_table_state_mut().offset_mut();
                     *offset = (*offset).saturating_sub(1);
                 }
 
-                event if event.caused_model_update() => model_needs_update = true,
+                event if event.caused_model_update() || event.changed_articles() => {
+                    if event.changed_articles() {
+                        self.model_data.invalidate_duplicates();
+                    }
+                    model_needs_update = true;
+                }
 
                 _ => {}
             }
@@ -1,17 +1,27 @@
-use crate::{prelude::*, ui::articles_list::view::FilterState};
+use crate::{
+    prelude::*,
+    ui::articles_list::{
+        groups::{ArticleGroup, day_label, group_articles, week_label},
+        view::FilterState,
+    },
+};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, hash_map::Entry},
+    mem::take,
     sync::Arc,
 };
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use getset::{Getters, MutGetters};
 use log::info;
-use news_flash::models::{Article, ArticleID, Category, Feed, FeedID, Marked, Tag, TagID};
+use news_flash::models::{
+    Article, ArticleFilter, ArticleID, Category, Feed, FeedID, Marked, Read, Tag, TagID,
+};
 
 #[derive(Getters, MutGetters)]
 #[getset(get = "pub(super)")]
 pub struct ArticleListModelData {
+    config: Arc<Config>,
     news_flash_utils: Arc<NewsFlashUtils>,
     articles: Vec<Article>,
     feed_map: HashMap<FeedID, Feed>,
@@ -19,14 +29,23 @@ pub struct ArticleListModelData {
     tags_for_article: HashMap<ArticleID, Vec<TagID>>,
     tag_map: HashMap<TagID, Tag>,
     last_sync: DateTime<Utc>,
+    duplicates: DuplicateIndex,
+    // the duplicate index must be built (again) before it is used
+    duplicates_outdated: bool,
+    // copies of a listed article which are hidden in the list
+    collapsed: HashMap<ArticleID, Vec<ArticleID>>,
+    downloaded_articles: HashSet<ArticleID>,
+    // groups of consecutive articles; empty if the articles are not grouped
+    groups: Vec<ArticleGroup>,
 
     #[get_mut = "pub(super)"]
     flagged_articles: HashSet<ArticleID>,
 }
 
 impl ArticleListModelData {
-    pub(super) fn new(news_flash_utils: Arc<NewsFlashUtils>) -> Self {
+    pub(super) fn new(config: Arc<Config>, news_flash_utils: Arc<NewsFlashUtils>) -> Self {
         Self {
+            config,
             news_flash_utils: news_flash_utils.clone(),
 
             articles: Default::default(),
@@ -35,24 +54,29 @@ impl ArticleListModelData {
             tags_for_article: Default::default(),
             tag_map: Default::default(),
             last_sync: Default::default(),
+            duplicates: Default::default(),
+            duplicates_outdated: true,
+            collapsed: Default::default(),
+            downloaded_articles: Default::default(),
+            groups: Default::default(),
             flagged_articles: Default::default(),
         }
     }
 
     pub(super) async fn update(&mut self, filter_state: &FilterState) -> color_eyre::Result<()> {
-        let news_flash = self.news_flash_utils.news_flash_lock.read().await;
+        let accounts = self.news_flash_utils.accounts();
 
         // last sync
-        self.last_sync = news_flash.last_sync().await;
+        self.last_sync = accounts.last_sync().await;
 
         // fill model data
-        let (feeds, feed_mappings) = news_flash.get_feeds()?;
+        let (feeds, feed_mappings) = accounts.get_feeds().await?;
         self.feed_map = NewsFlashUtils::generate_id_map(&feeds, |f| f.feed_id.clone())
             .into_iter()
             .map(|(k, v)| (k, v.clone()))
             .collect();
 
-        let (categories, _) = news_flash.get_categories()?;
+        let (categories, _) = accounts.get_categories().await?;
 
         let category_for_category_id = NewsFlashUtils::generate_id_map(&categories, |category| {
             category.category_id.to_owned()
@@ -75,7 +99,7 @@ impl ArticleListModelData {
             })
             .collect::<HashMap<FeedID, Category>>();
 
-        let (tags, taggings) = news_flash.get_tags()?;
+        let (tags, taggings) = accounts.get_tags().await?;
         self.tag_map = NewsFlashUtils::generate_id_map(&tags, |t| t.tag_id.clone())
             .into_iter()
             .map(|(k, v)| (k, v.clone()))
@@ -102,12 +126,56 @@ impl ArticleListModelData {
             })
         });
 
-        drop(news_flash);
+        self.update_duplicates(filter_state).await?;
+
+        self.downloaded_articles = self.news_flash_utils.downloads().downloaded_articles();
 
         // apply the current filter
         self.filter_articles(filter_state).await
     }
 
+    pub(super) fn invalidate_duplicates(&mut self) {
+        self.duplicates_outdated = true;
+    }
+
+    // the duplicate index is only built if it is used and only rebuilt after articles changed;
+    // copies are searched among all articles (not only the listed ones) which are not older
+    // than the articles kept
+    pub(super) async fn update_duplicates(
+        &mut self,
+        filter_state: &FilterState,
+    ) -> color_eyre::Result<()> {
+        if !self.config.collapse_duplicates
+            && !self.config.mark_duplicates_read
+            && !filter_state.uses_duplicates()
+        {
+            self.duplicates = DuplicateIndex::default();
+            self.duplicates_outdated = true;
+            return Ok(());
+        }
+
+        if !self.duplicates_outdated {
+            return Ok(());
+        }
+
+        let article_filter = ArticleFilter {
+            newer_than: (self.config.keep_articles_days > 0).then(|| {
+                Utc::now() - chrono::TimeDelta::days(self.config.keep_articles_days as i64)
+            }),
+            ..Default::default()
+        };
+        self.duplicates = DuplicateIndex::new(
+            &self
+                .news_flash_utils
+                .accounts()
+                .get_articles(article_filter)
+                .await?,
+            self.config.duplicate_titles,
+        );
+        self.duplicates_outdated = false;
+        Ok(())
+    }
+
     pub(super) fn effectively_flagged_articles(&self) -> Vec<ArticleID> {
         self.flagged_articles
             .intersection(&HashSet::from_iter(
@@ -129,13 +197,16 @@ impl ArticleListModelData {
             return Ok(());
         };
 
-        let news_flash = self.news_flash_utils.news_flash_lock.read().await;
-
         // TODO make configurable
         article_filter.order_by = Some(news_flash::models::OrderBy::Published);
         article_filter.order = Some(news_flash::models::ArticleOrder::NewestFirst);
 
-        self.articles = news_flash.get_articles(article_filter.clone())?;
+        // articles of all accounts end up in one list
+        self.articles = self
+            .news_flash_utils
+            .accounts()
+            .get_articles(article_filter.clone())
+            .await?;
 
         if augmented_article_filter.is_augmented() {
             self.articles = self.get_queried_articles(&augmented_article_filter.article_query);
@@ -151,9 +222,82 @@ impl ArticleListModelData {
             .get_effective_sort_order()
             .sort(&mut self.articles, &self.feed_map);
 
+        self.collapse_duplicates();
+
+        self.group_articles(*filter_state.group_by());
+
         Ok(())
     }
 
+    // the articles are grouped after sorting so that the order within a group is the sort order
+    fn group_articles(&mut self, group_by: GroupBy) {
+        if group_by == GroupBy::None {
+            self.groups.clear();
+            return;
+        }
+
+        let today = chrono::Local::now().date_naive();
+        let mut articles = take(&mut self.articles);
+        self.groups = group_articles(&mut articles, |article| {
+            self.group_label(article, group_by, today)
+        });
+        self.articles = articles;
+    }
+
+    fn group_label(&self, article: &Article, group_by: GroupBy, today: NaiveDate) -> String {
+        let date = article.date.with_timezone(&chrono::Local).date_naive();
+        match group_by {
+            GroupBy::None => String::new(),
+            GroupBy::Day => day_label(date, today),
+            GroupBy::Week => week_label(date, today),
+            GroupBy::Feed => self
+                .feed_map
+                .get(&article.feed_id)
+                .map(|feed| html_sanitize(&feed.label))
+                .unwrap_or_else(|| "unknown feed".to_owned()),
+            GroupBy::Category => self
+                .category_for_feed
+                .get(&article.feed_id)
+                .map(|category| category.label.to_owned())
+                .unwrap_or_else(|| "no category".to_owned()),
+            GroupBy::Tag => self
+                .tags_for_article
+                .get(&article.article_id)
+                .and_then(|tag_ids| tag_ids.first())
+                .and_then(|tag_id| self.tag_map.get(tag_id))
+                .map(|tag| tag.label.to_owned())
+                .unwrap_or_else(|| "no tag".to_owned()),
+        }
+    }
+
+    // only the first copy of an article is listed, the other copies are collapsed into it
+    fn collapse_duplicates(&mut self) {
+        self.collapsed.clear();
+        if !self.config.collapse_duplicates {
+            return;
+        }
+
+        let mut listed_for_group: HashMap<usize, ArticleID> = HashMap::new();
+        self.articles.retain(|article| {
+            let Some(group) = self.duplicates.group_of(&article.article_id) else {
+                return true;
+            };
+            match listed_for_group.entry(group) {
+                Entry::Vacant(entry) => {
+                    entry.insert(article.article_id.to_owned());
+                    true
+                }
+                Entry::Occupied(entry) => {
+                    self.collapsed
+                        .entry(entry.get().to_owned())
+                        .or_default()
+                        .push(article.article_id.to_owned());
+                    false
+                }
+            }
+        });
+    }
+
     pub(super) fn get_queried_articles(&self, query: &ArticleQuery) -> Vec<Article> {
         query.filter(
             &self.articles,
@@ -164,6 +308,8 @@ impl ArticleListModelData {
                 tag_map: self.tag_map(),
                 last_sync: self.last_sync(),
                 flagged: &self.flagged_articles,
+                duplicates: &self.duplicates,
+                downloaded: &self.downloaded_articles,
             },
         )
     }
@@ -171,17 +317,35 @@ impl ArticleListModelData {
     pub(super) fn set_read_status(
         &mut self,
         article_ids: Vec<ArticleID>,
-        read: news_flash::models::Read,
+        read: Read,
     ) -> color_eyre::Result<usize> {
         // no articles -> no changes needed
         if article_ids.is_empty() {
             return Ok(0);
         }
 
-        let article_ids_set: HashSet<ArticleID> = article_ids.iter().cloned().collect();
+        // collapsed copies always share the status of the listed article
+        let mut article_ids_set: HashSet<ArticleID> = article_ids
+            .iter()
+            .flat_map(|article_id| self.collapsed.get(article_id).into_iter().flatten())
+            .chain(article_ids.iter())
+            .cloned()
+            .collect();
 
-        self.news_flash_utils
-            .set_article_status(article_ids.clone(), read, true); // undoable
+        if read == Read::Read && self.config.mark_duplicates_read {
+            article_ids_set.extend(
+                article_ids
+                    .iter()
+                    .flat_map(|article_id| self.duplicates.duplicates_of(article_id))
+                    .cloned(),
+            );
+        }
+
+        self.news_flash_utils.set_article_status(
+            article_ids_set.iter().cloned().collect(),
+            read,
+            true,
+        ); // undoable
 
         self.articles
             .iter_mut()
@@ -1,18 +1,27 @@
 use crate::prelude::*;
 use crate::ui::articles_list::model::ArticleListModelData;
-use std::sync::Arc;
+use std::{collections::HashSet, io::Cursor, sync::Arc};
 
 use getset::{Getters, MutGetters};
-use news_flash::models::{ArticleFilter, Marked, Read};
+use image::ImageReader;
+use indexmap::IndexMap;
+use log::debug;
+use news_flash::models::{Article, ArticleFilter, Marked, Read};
 use ratatui::layout::Constraint;
 use ratatui::layout::Rect;
-use ratatui::text::{Line, Span};
+use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{
-    Block, Borders, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Table,
-    TableState, Widget,
+    Block, Borders, Cell, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
+    Table, TableState, Widget,
+};
+use ratatui_image::{
+    FilterType, Resize, StatefulImage, picker::Picker, protocol::StatefulProtocol,
 };
 use strum::IntoEnumIterator;
 
+// maximum number of decoded card thumbnails kept in memory
+const MAX_CACHED_THUMBNAILS: usize = 128;
+
 #[derive(Getters, MutGetters)]
 #[getset(get = "pub(super)")]
 pub struct FilterState {
@@ -40,11 +49,29 @@ pub struct FilterState {
 
     #[get_mut = "pub(super)"]
     sticky_adhoc_filter: bool,
+
+    #[get_mut = "pub(super)"]
+    group_by: GroupBy,
+
+    // labels of the groups whose articles are hidden
+    #[get_mut = "pub(super)"]
+    collapsed_groups: HashSet<String>,
+
+    #[get_mut = "pub(super)"]
+    layout: ListLayout,
 }
 
 impl FilterState {
-    pub fn new(article_scope: ArticleScope, default_sort_order: SortOrder) -> Self {
+    pub fn new(
+        article_scope: ArticleScope,
+        default_sort_order: SortOrder,
+        group_by: GroupBy,
+        layout: ListLayout,
+    ) -> Self {
         Self {
+            group_by,
+            collapsed_groups: Default::default(),
+            layout,
             default_sort_order,
             article_scope,
             augmented_article_filter: None,
@@ -88,6 +115,21 @@ impl FilterState {
         Some(self.article_scope)
     }
 
+    // whether the filters or the search query refer to duplicates
+    pub fn uses_duplicates(&self) -> bool {
+        self.augmented_article_filter
+            .as_ref()
+            .is_some_and(|filter| filter.article_query.uses_duplicates())
+            || self
+                .article_adhoc_filter
+                .as_ref()
+                .is_some_and(ArticleQuery::uses_duplicates)
+            || self
+                .article_search_query
+                .as_ref()
+                .is_some_and(ArticleQuery::uses_duplicates)
+    }
+
     pub fn uses_default_sort_order(&self) -> bool {
         self.adhoc_sort_order.is_none()
             && (self
@@ -143,7 +185,8 @@ impl Widget for &mut ArticlesList {
                 .gen_block(&self.config, &self.filter_state, self.is_focused, area);
         let inner = block.inner(area);
 
-        *self.view_data.article_lines_mut() = Some(area.height.saturating_sub(1));
+        *self.view_data.article_lines_mut() =
+            Some((area.height.saturating_sub(1) / self.view_data.article_height.max(1)).max(1));
 
         StatefulWidget::render(
             &self.view_data.table,
@@ -152,6 +195,11 @@ impl Widget for &mut ArticlesList {
             &mut self.view_data.table_state,
         );
 
+        if *self.filter_state.layout() == ListLayout::Cards && self.config.article_card_thumbnails {
+            self.view_data
+                .render_thumbnails(&self.config, &self.model_data, inner, buf);
+        }
+
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .symbols(self.config.border_theme.scrollbar_set(self.is_focused))
             .style(self.config.theme.eff_border(self.is_focused));
@@ -174,6 +222,21 @@ impl Widget for &mut ArticlesList {
     }
 }
 
+// a row of the article table: the header of a group or an article (index into the articles of
+// the model)
+#[derive(Debug, Clone, Copy)]
+pub(super) enum ListRow {
+    Header {
+        group: usize,
+        first_article: usize,
+        collapsed: bool,
+    },
+    Article {
+        index: usize,
+        group: Option<usize>,
+    },
+}
+
 #[derive(Default, Getters, MutGetters)]
 #[getset(get = "pub(super)")]
 pub struct ArticleListViewData<'a> {
@@ -188,6 +251,20 @@ pub struct ArticleListViewData<'a> {
     article_lines: Option<u16>,
 
     article_count: usize,
+
+    rows: Vec<ListRow>,
+    // row of each article; articles of collapsed groups are represented by the header row
+    row_for_article: Vec<usize>,
+
+    // lines per article: 1 for the table, the lines of `article_card` for cards
+    article_height: u16,
+
+    // decoded card thumbnails by URL (None if fetching or decoding failed)
+    thumbnails: IndexMap<String, Option<StatefulProtocol>>,
+    thumbnails_fetching: HashSet<String>,
+    thumbnails_to_fetch: Vec<String>,
+    // only queried if thumbnails are shown on the cards
+    picker: Option<Picker>,
 }
 
 impl<'a> ArticleListViewData<'a> {
@@ -234,7 +311,12 @@ impl<'a> ArticleListViewData<'a> {
 
     fn build_position(&self, config: &Config) -> Line<'static> {
         if self.article_count > 0 && config.article_list_show_position {
-            let selected = self.table_state.selected().unwrap_or(0).saturating_add(1);
+            let selected = self
+                .table_state
+                .selected()
+                .and_then(|row| self.article_position(row))
+                .unwrap_or(0)
+                .saturating_add(1);
             let all = self.article_count;
             Line::styled(format!(" {selected}/{all} ",), config.theme.header())
         } else {
@@ -251,129 +333,56 @@ impl<'a> ArticleListViewData<'a> {
     ) {
         let selected_style = config.theme.selected(&Default::default());
 
-        let read_icon = config.icon_set.read_icon().to_string();
-        let unread_icon = config.icon_set.unread_icon().to_string();
-        let marked_icon = config.icon_set.marked_icon().to_string();
-        let unmarked_icon = config.icon_set.unmarked_icon().to_string();
-
         let placeholders: Vec<&str> = config
             .article_table
             .split(",")
             .map(|placeholder| placeholder.trim())
             .collect();
 
+        let cards = *filter_state.layout() == ListLayout::Cards;
+        let thumbnails = cards && config.article_card_thumbnails;
+        self.article_height = if cards {
+            config.article_card.len().max(1) as u16
+        } else {
+            1
+        };
+
         let mut max_tags: u16 = 0;
 
         let entries: Vec<Row> = model_data
             .articles()
             .iter()
             .map(|article| {
-                let row_vec: Vec<Line> = placeholders
-                    .iter()
-                    .map(|placeholder| match *placeholder {
-                        "{title}" => html_sanitize(
-                            article
-                                .title
-                                .as_deref()
-                                .or(article.summary.as_deref())
-                                .unwrap_or("no title and summary"),
-                        )
-                        .into(),
-                        "{tag_icons}" => Line::from(
-                            match model_data.tags_for_article().get(&article.article_id) {
-                                Some(tag_ids) => {
-                                    max_tags = u16::max(max_tags, tag_ids.len() as u16);
-
-                                    tag_ids
-                                        .iter()
-                                        .map(|tag_id| {
-                                            let Some(tag) = model_data.tag_map().get(tag_id) else {
-                                                return Span::from("");
-                                            };
-
-                                            let style = match NewsFlashUtils::tag_color(tag) {
-                                                Some(color) => config.theme.tag().fg(color),
-                                                None => config.theme.tag(),
-                                            };
-                                            Span::styled(
-                                                config.icon_set.tag_icon().to_string(),
-                                                style,
-                                            )
-                                        })
-                                        .collect::<Vec<Span>>()
-                                }
-                                None => vec![Span::from("")],
-                            },
-                        ),
-                        "{author}" => {
-                            html_sanitize(article.author.as_deref().unwrap_or("no author")).into()
-                        }
-                        "{feed}" => html_sanitize(
-                            model_data
-                                .feed_map()
-                                .get(&article.feed_id)
-                                .map(|feed| feed.label.as_str())
-                                .unwrap_or("unknown feed"),
-                        )
-                        .into(),
-                        "{date}" => article
-                            .date
-                            .with_timezone(&chrono::Local)
-                            .format(&config.date_format)
-                            .to_string()
-                            .into(),
-                        "{age}" => {
-                            let now = chrono::Utc::now();
-                            let duration = now.signed_duration_since(article.date);
-
-                            let weeks = duration.num_weeks();
-                            let days = duration.num_days();
-                            let hours = duration.num_hours();
-                            let minutes = duration.num_minutes();
-                            let seconds = duration.num_seconds();
-
-                            if weeks > 0 {
-                                format!("{:>2}w", weeks)
-                            } else if days > 0 {
-                                format!("{:>2}d", days)
-                            } else if hours > 0 {
-                                format!("{:>2}h  ", hours)
-                            } else if minutes > 0 {
-                                format!("{:>2}m", minutes)
-                            } else {
-                                format!("{:>2}s", seconds)
-                            }
-                        }
-                        .into(),
-                        "{read}" => if article.unread == Read::Read {
-                            format!(" {}", read_icon)
-                        } else {
-                            format!(" {}", unread_icon)
-                        }
-                        .into(),
-                        "{marked}" => if article.marked == Marked::Marked {
-                            format!(" {}", marked_icon)
-                        } else {
-                            format!(" {}", unmarked_icon)
-                        }
-                        .into(),
-                        "{url}" => article
-                            .url
-                            .as_ref()
-                            .map(|url| url.to_string())
-                            .unwrap_or("?".into())
-                            .into(),
-                        "{flagged}" => if model_data.flagged_articles().is_empty() {
-                            "".to_string()
-                        } else if model_data.flagged_articles().contains(&article.article_id) {
-                            format!(" {}", config.icon_set.flagged_icon())
-                        } else {
-                            "  ".to_string()
-                        }
-                        .into(),
-                        _ => format!("{placeholder}?").into(),
-                    })
-                    .collect();
+                let row = if cards {
+                    let lines: Vec<Line> = config
+                        .article_card
+                        .iter()
+                        .map(|template| {
+                            card_line(template, article, &config, model_data, &mut max_tags)
+                        })
+                        .collect();
+                    let mut cells = Vec::new();
+                    if thumbnails {
+                        cells.push(Cell::default());
+                    }
+                    cells.push(Cell::from(Text::from(lines)));
+                    Row::new(cells).height(self.article_height)
+                } else {
+                    Row::new(
+                        placeholders
+                            .iter()
+                            .map(|placeholder| {
+                                placeholder_line(
+                                    placeholder,
+                                    article,
+                                    &config,
+                                    model_data,
+                                    &mut max_tags,
+                                )
+                            })
+                            .collect::<Vec<Line>>(),
+                    )
+                };
 
                 let mut style = match filter_state.article_search_query.as_ref() {
                     Some(query)
@@ -386,6 +395,8 @@ impl<'a> ArticleListViewData<'a> {
                                 tag_map: model_data.tag_map(),
                                 last_sync: model_data.last_sync(),
                                 flagged: model_data.flagged_articles(),
+                                duplicates: model_data.duplicates(),
+                                downloaded: model_data.downloaded_articles(),
                             },
                         ) =>
                     {
@@ -404,13 +415,35 @@ impl<'a> ArticleListViewData<'a> {
                     style = config.theme.flagged(&style);
                 }
 
-                Row::new(row_vec).style(style)
+                row.style(style)
             })
             .collect();
 
+        // group headers are shown in the title column (table) or next to the thumbnails (cards)
+        let (columns, label_column) = if cards {
+            (thumbnails as usize + 1, thumbnails as usize)
+        } else {
+            (
+                placeholders.len(),
+                placeholders
+                    .iter()
+                    .position(|placeholder| *placeholder == "{title}")
+                    .unwrap_or(0),
+            )
+        };
+        let entries = self.build_rows(
+            &config,
+            model_data,
+            filter_state,
+            columns,
+            label_column,
+            entries,
+        );
+
         let constraint_for_placeholder = |placeholder: &str| {
             if placeholder == "{read}"
                 || placeholder == "{marked}"
+                || placeholder == "{downloaded}"
                 || (placeholder == "{flagged}" && !model_data.flagged_articles().is_empty())
             {
                 Constraint::Length(2)
@@ -432,16 +465,280 @@ impl<'a> ArticleListViewData<'a> {
             .content_length(entries.len())
             .position(0);
 
-        self.article_count = entries.len();
+        self.article_count = model_data.articles().len();
 
-        self.table = Table::new(
-            entries,
+        let constraints = if thumbnails {
+            vec![
+                Constraint::Length(self.thumbnail_width()),
+                Constraint::Fill(1),
+            ]
+        } else if cards {
+            vec![Constraint::Fill(1)]
+        } else {
             placeholders
                 .iter()
                 .map(|placeholder| constraint_for_placeholder(placeholder))
-                .collect::<Vec<Constraint>>(),
+                .collect::<Vec<Constraint>>()
+        };
+
+        self.table = Table::new(entries, constraints).row_highlight_style(selected_style);
+    }
+
+    // inserts the group headers and leaves out the articles of collapsed groups
+    fn build_rows(
+        &mut self,
+        config: &Config,
+        model_data: &ArticleListModelData,
+        filter_state: &FilterState,
+        columns: usize,
+        label_column: usize,
+        article_rows: Vec<Row<'a>>,
+    ) -> Vec<Row<'a>> {
+        self.rows.clear();
+
+        if model_data.groups().is_empty() {
+            self.rows = (0..article_rows.len())
+                .map(|index| ListRow::Article { index, group: None })
+                .collect();
+            self.row_for_article = (0..article_rows.len()).collect();
+            return article_rows;
+        }
+
+        let mut article_rows = article_rows.into_iter().map(Some).collect::<Vec<_>>();
+        let mut entries = Vec::new();
+        self.row_for_article = vec![0; article_rows.len()];
+
+        for (group_index, group) in model_data.groups().iter().enumerate() {
+            let collapsed = filter_state.collapsed_groups().contains(&group.label);
+            let unread = model_data.articles()[group.articles.clone()]
+                .iter()
+                .filter(|article| article.unread == Read::Unread)
+                .count();
+
+            let header_row = self.rows.len();
+            self.rows.push(ListRow::Header {
+                group: group_index,
+                first_article: group.articles.start,
+                collapsed,
+            });
+
+            let label = format!(
+                "{} {} ({unread}/{})",
+                if collapsed {
+                    config.icon_set.group_collapsed_icon()
+                } else {
+                    config.icon_set.group_expanded_icon()
+                },
+                group.label,
+                group.articles.len()
+            );
+            entries.push(
+                Row::new((0..columns).map(|column| {
+                    if column == label_column {
+                        Line::from(label.to_owned())
+                    } else {
+                        Line::default()
+                    }
+                }))
+                .style(config.theme.header()),
+            );
+
+            for index in group.articles.clone() {
+                if collapsed {
+                    self.row_for_article[index] = header_row;
+                    continue;
+                }
+                self.row_for_article[index] = self.rows.len();
+                self.rows.push(ListRow::Article {
+                    index,
+                    group: Some(group_index),
+                });
+                entries.extend(article_rows[index].take());
+            }
+        }
+
+        entries
+    }
+
+    pub(super) fn thumbnail_width(&self) -> u16 {
+        self.article_height * 4
+    }
+
+    // renders the thumbnails of the visible cards into the first column of the table; missing
+    // thumbnails are fetched on the next tick
+    fn render_thumbnails(
+        &mut self,
+        config: &Config,
+        model_data: &ArticleListModelData,
+        area: Rect,
+        buf: &mut ratatui::prelude::Buffer,
+    ) {
+        let width = self.thumbnail_width().min(area.width);
+        let mut y = area.y;
+
+        for row in self.table_state.offset()..self.rows.len() {
+            let height = match self.rows[row] {
+                ListRow::Header { .. } => 1,
+                ListRow::Article { .. } => self.article_height,
+            };
+            if y + height > area.bottom() {
+                break;
+            }
+
+            if let ListRow::Article { index, .. } = self.rows[row]
+                && let Some(url) = model_data
+                    .articles()
+                    .get(index)
+                    .and_then(|article| article.thumbnail_url.as_ref())
+            {
+                match self.thumbnails.get_mut(url) {
+                    Some(Some(thumbnail)) => {
+                        let mut image = StatefulImage::new();
+                        if config.thumbnail_resize {
+                            image = image.resize(Resize::Fit(Some(FilterType::Lanczos3)));
+                        }
+                        let thumbnail_area = Rect {
+                            x: area.x,
+                            y,
+                            width,
+                            height,
+                        };
+                        image.render(thumbnail_area, buf, thumbnail);
+                    }
+                    Some(None) => {}
+                    None => {
+                        if !self.thumbnails_fetching.contains(url)
+                            && !self.thumbnails_to_fetch.contains(url)
+                        {
+                            self.thumbnails_to_fetch.push(url.to_owned());
+                        }
+                    }
+                }
+            }
+
+            y += height;
+        }
+    }
+
+    // the row shown at the line below the first visible row (cards span several lines)
+    pub(super) fn row_at_line(&self, line: usize) -> usize {
+        let mut row = self.table_state.offset();
+        let mut top = 0;
+
+        while let Some(list_row) = self.rows.get(row) {
+            top += match list_row {
+                ListRow::Header { .. } => 1,
+                ListRow::Article { .. } => self.article_height.max(1) as usize,
+            };
+            if top > line {
+                break;
+            }
+            row += 1;
+        }
+
+        row
+    }
+
+    pub(super) fn take_thumbnails_to_fetch(&mut self) -> Vec<String> {
+        self.thumbnails_fetching
+            .extend(self.thumbnails_to_fetch.iter().cloned());
+        std::mem::take(&mut self.thumbnails_to_fetch)
+    }
+
+    pub(super) fn on_thumbnail_fetched(&mut self, url: &str, data: Option<&[u8]>) {
+        self.thumbnails_fetching.remove(url);
+
+        let Some(picker) = self.picker.as_ref() else {
+            return;
+        };
+
+        let image = data.and_then(|data| {
+            ImageReader::new(Cursor::new(data))
+                .with_guessed_format()
+                .ok()?
+                .decode()
+                .inspect_err(|err| debug!("unable to decode thumbnail {url}: {err}"))
+                .ok()
+        });
+
+        self.thumbnails.insert(
+            url.to_owned(),
+            image.map(|image| picker.new_resize_protocol(image)),
+        );
+
+        if self.thumbnails.len() > MAX_CACHED_THUMBNAILS {
+            self.thumbnails.shift_remove_index(0);
+        }
+    }
+
+    pub(super) fn set_picker(&mut self, picker: Option<Picker>) {
+        self.picker = picker;
+        // thumbnails have to be fetched again for the new protocol
+        self.thumbnails.clear();
+    }
+
+    pub(super) fn has_picker(&self) -> bool {
+        self.picker.is_some()
+    }
+
+    pub(super) fn article_index(&self, row: usize) -> Option<usize> {
+        match self.rows.get(row)? {
+            ListRow::Article { index, .. } => Some(*index),
+            ListRow::Header { .. } => None,
+        }
+    }
+
+    // the article or, for a header, the first article of the group
+    pub(super) fn article_position(&self, row: usize) -> Option<usize> {
+        match self.rows.get(row)? {
+            ListRow::Article { index, .. } => Some(*index),
+            ListRow::Header { first_article, .. } => Some(*first_article),
+        }
+    }
+
+    pub(super) fn row_of_article(&self, index: usize) -> Option<usize> {
+        self.row_for_article.get(index).copied()
+    }
+
+    pub(super) fn group_of_row(&self, row: usize) -> Option<usize> {
+        match self.rows.get(row)? {
+            ListRow::Article { group, .. } => *group,
+            ListRow::Header { group, .. } => Some(*group),
+        }
+    }
+
+    // headers of expanded groups cannot be selected, collapsed groups are selected by their header
+    fn is_selectable(&self, row: usize) -> bool {
+        !matches!(
+            self.rows.get(row),
+            Some(ListRow::Header {
+                collapsed: false,
+                ..
+            })
         )
-        .row_highlight_style(selected_style);
+    }
+
+    // the nearest selectable row in the given direction (or in the other direction if there is
+    // none)
+    pub(super) fn selectable_row(&self, row: usize, forward: bool) -> usize {
+        let row = row.min(self.rows.len().saturating_sub(1));
+        let after = (row..self.rows.len()).find(|row| self.is_selectable(*row));
+        let before = (0..=row).rev().find(|row| self.is_selectable(*row));
+
+        if forward {
+            after.or(before)
+        } else {
+            before.or(after)
+        }
+        .unwrap_or(row)
+    }
+
+    pub(super) fn is_header(&self, row: usize) -> bool {
+        matches!(self.rows.get(row), Some(ListRow::Header { .. }))
+    }
+
+    pub(super) fn row_count(&self) -> usize {
+        self.rows.len()
     }
 
     pub(super) fn gen_block(
@@ -485,3 +782,186 @@ impl<'a> ArticleListViewData<'a> {
         &self.table_state
     }
 }
+
+// the content of a placeholder of `article_table` or `article_card`
+fn placeholder_line(
+    placeholder: &str,
+    article: &Article,
+    config: &Config,
+    model_data: &ArticleListModelData,
+    max_tags: &mut u16,
+) -> Line<'static> {
+    match placeholder {
+        "{title}" => {
+            let mut title = Line::from(html_sanitize(
+                article
+                    .title
+                    .as_deref()
+                    .or(article.summary.as_deref())
+                    .unwrap_or("no title and summary"),
+            ));
+            if let Some(collapsed) = model_data.collapsed().get(&article.article_id) {
+                title.push_span(Span::styled(
+                    format!(" +{}", collapsed.len()),
+                    config.theme.inactive(),
+                ));
+            }
+            title
+        }
+        "{tag_icons}" => Line::from(
+            match model_data.tags_for_article().get(&article.article_id) {
+                Some(tag_ids) => {
+                    *max_tags = u16::max(*max_tags, tag_ids.len() as u16);
+
+                    tag_ids
+                        .iter()
+                        .map(|tag_id| {
+                            let Some(tag) = model_data.tag_map().get(tag_id) else {
+                                return Span::from("");
+                            };
+
+                            let style = match NewsFlashUtils::tag_color(tag) {
+                                Some(color) => config.theme.tag().fg(color),
+                                None => config.theme.tag(),
+                            };
+                            Span::styled(config.icon_set.tag_icon().to_string(), style)
+                        })
+                        .collect::<Vec<Span>>()
+                }
+                None => vec![Span::from("")],
+            },
+        ),
+        "{author}" => html_sanitize(article.author.as_deref().unwrap_or("no author")).into(),
+        "{feed}" => html_sanitize(
+            model_data
+                .feed_map()
+                .get(&article.feed_id)
+                .map(|feed| feed.label.as_str())
+                .unwrap_or("unknown feed"),
+        )
+        .into(),
+        "{date}" => article
+            .date
+            .with_timezone(&chrono::Local)
+            .format(&config.date_format)
+            .to_string()
+            .into(),
+        "{age}" => {
+            let now = chrono::Utc::now();
+            let duration = now.signed_duration_since(article.date);
+
+            let weeks = duration.num_weeks();
+            let days = duration.num_days();
+            let hours = duration.num_hours();
+            let minutes = duration.num_minutes();
+            let seconds = duration.num_seconds();
+
+            if weeks > 0 {
+                format!("{:>2}w", weeks)
+            } else if days > 0 {
+                format!("{:>2}d", days)
+            } else if hours > 0 {
+                format!("{:>2}h  ", hours)
+            } else if minutes > 0 {
+                format!("{:>2}m", minutes)
+            } else {
+                format!("{:>2}s", seconds)
+            }
+        }
+        .into(),
+        "{read}" => if article.unread == Read::Read {
+            format!(" {}", config.icon_set.read_icon())
+        } else {
+            format!(" {}", config.icon_set.unread_icon())
+        }
+        .into(),
+        "{marked}" => if article.marked == Marked::Marked {
+            format!(" {}", config.icon_set.marked_icon())
+        } else {
+            format!(" {}", config.icon_set.unmarked_icon())
+        }
+        .into(),
+        "{url}" => article
+            .url
+            .as_ref()
+            .map(|url| url.to_string())
+            .unwrap_or("?".into())
+            .into(),
+        "{flagged}" => if model_data.flagged_articles().is_empty() {
+            "".to_string()
+        } else if model_data.flagged_articles().contains(&article.article_id) {
+            format!(" {}", config.icon_set.flagged_icon())
+        } else {
+            "  ".to_string()
+        }
+        .into(),
+        "{downloaded}" => if model_data
+            .downloaded_articles()
+            .contains(&article.article_id)
+        {
+            format!(" {}", config.icon_set.downloaded_icon())
+        } else {
+            "  ".to_string()
+        }
+        .into(),
+        "{tags}" => Line::from(
+            model_data
+                .tags_for_article()
+                .get(&article.article_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|tag_id| model_data.tag_map().get(tag_id))
+                .flat_map(|tag| {
+                    let style = match NewsFlashUtils::tag_color(tag) {
+                        Some(color) => config.theme.tag().fg(color),
+                        None => config.theme.tag(),
+                    };
+                    [
+                        Span::styled(
+                            format!("{} {}", config.icon_set.tag_icon(), tag.label),
+                            style,
+                        ),
+                        Span::from(" "),
+                    ]
+                })
+                .collect::<Vec<Span>>(),
+        ),
+        "{summary}" => html_sanitize(article.summary.as_deref().unwrap_or_default())
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .into(),
+        _ => format!("{placeholder}?").into(),
+    }
+}
+
+// a line of `article_card`: the placeholders are replaced by their content, the text in between
+// is kept
+fn card_line(
+    template: &str,
+    article: &Article,
+    config: &Config,
+    model_data: &ArticleListModelData,
+    max_tags: &mut u16,
+) -> Line<'static> {
+    let mut line = Line::default();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{')
+        && let Some(length) = rest[start..].find('}')
+    {
+        line.push_span(Span::from(rest[..start].to_owned()));
+        let placeholder_line = placeholder_line(
+            &rest[start..=start + length],
+            article,
+            config,
+            model_data,
+            max_tags,
+        );
+        line.spans.extend(placeholder_line.spans);
+        rest = &rest[start + length + 1..];
+    }
+    line.push_span(Span::from(rest.to_owned()));
+
+    line
+}
@@ -164,6 +164,8 @@ impl CommandInput {
                 self.generate_help_content_enclosure_type(&current_part)?
             }
             Err(E::PipeTargetExpected) => self.generate_help_content_pipe_target(&current_part)?,
+            Err(E::GroupByExpected) => self.generate_help_content_group_by(&current_part)?,
+            Err(E::LayoutExpected) => self.generate_help_content_layout(&current_part)?,
 
             Err(E::ShareTargetExpected) => {
                 self.generate_help_content_share_target(&current_part)?
@@ -273,10 +275,10 @@ impl CommandInput {
         &mut self,
         current_part: &str,
     ) -> color_eyre::Result<()> {
-        let (tags, _) = {
-            let news_flash = self.news_flash_utils.news_flash_lock.read().await;
-            news_flash.get_tags()?
-        };
+        let (mut tags, _) = self.news_flash_utils.accounts().get_tags().await?;
+        // accounts share tags with the same label
+        let mut labels = std::collections::HashSet::new();
+        tags.retain(|tag| labels.insert(tag.label.to_owned()));
 
         self.completion_targets = Some(tags.iter().map(|tag| tag.label.to_owned()).collect());
 
@@ -720,6 +722,31 @@ impl CommandInput {
         )
     }
 
+    fn generate_help_content_group_by(&mut self, current_part: &str) -> color_eyre::Result<()> {
+        self.generate_help_content_enum::<GroupBy>(
+            current_part,
+            "Grouping",
+            |group_by| Some(group_by.as_ref().to_owned()),
+            |group_by| group_by.get_message().unwrap_or_default().to_owned(),
+            |group_by| {
+                group_by
+                    .get_detailed_message()
+                    .unwrap_or_default()
+                    .to_owned()
+            },
+        )
+    }
+
+    fn generate_help_content_layout(&mut self, current_part: &str) -> color_eyre::Result<()> {
+        self.generate_help_content_enum::<ListLayout>(
+            current_part,
+            "Layout",
+            |layout| Some(layout.as_ref().to_owned()),
+            |layout| layout.get_message().unwrap_or_default().to_owned(),
+            |layout| layout.get_detailed_message().unwrap_or_default().to_owned(),
+        )
+    }
+
     fn generate_help_content_pipe_target(&mut self, current_part: &str) -> color_eyre::Result<()> {
         self.generate_help_content_enum::<PipeTarget>(
             current_part,
@@ -0,0 +1,341 @@
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+use news_flash::models::{CategoryID, Url};
+use ratatui::{
+    crossterm::event::KeyEvent,
+    layout::{Constraint, Flex, Layout},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Padding, Paragraph, Widget, Wrap},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+// maximum number of candidates visible at once
+const MAX_VISIBLE_CANDIDATES: usize = 6;
+
+struct FeedAddState {
+    // name given to `feedadd`; the title of the feed is used if none was given
+    name: Option<String>,
+    candidates: Vec<FeedCandidate>,
+    selected: usize,
+    // `None` adds the feed at the top level
+    categories: Vec<(Option<CategoryID>, String)>,
+    category: usize,
+}
+
+impl FeedAddState {
+    fn selected_candidate(&self) -> Option<&FeedCandidate> {
+        self.candidates.get(self.selected)
+    }
+}
+
+/// Dialog for choosing one of the feeds found by `feedadd`, showing a preview of the feed and
+/// the category it is added to
+pub struct FeedAddPopup {
+    config: Arc<Config>,
+    news_flash_utils: Arc<NewsFlashUtils>,
+    message_sender: UnboundedSender<Message>,
+    state: Option<FeedAddState>,
+}
+
+impl FeedAddPopup {
+    pub fn new(
+        config: Arc<Config>,
+        news_flash_utils: Arc<NewsFlashUtils>,
+        message_sender: UnboundedSender<Message>,
+    ) -> Self {
+        Self {
+            config,
+            news_flash_utils,
+            message_sender,
+            state: None,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.state.is_some()
+    }
+
+    fn on_key_event(&mut self, key_event: &KeyEvent) -> color_eyre::Result<()> {
+        let Some(state) = self.state.as_mut() else {
+            return Ok(());
+        };
+
+        if !key_event.is_press() {
+            return Ok(());
+        }
+
+        let Some(command) = self
+            .config
+            .input_config
+            .match_single_key_to_single_command(&Key::from(*key_event))
+            .cloned()
+        else {
+            return Ok(());
+        };
+
+        use Command as C;
+        match command {
+            C::NavigateUp => state.selected = state.selected.saturating_sub(1),
+            C::NavigateDown => {
+                state.selected = (state.selected + 1).min(state.candidates.len().saturating_sub(1))
+            }
+            C::NavigateLeft => {
+                state.category = state
+                    .category
+                    .checked_sub(1)
+                    .unwrap_or(state.categories.len().saturating_sub(1))
+            }
+            C::NavigateRight => {
+                state.category = (state.category + 1) % state.categories.len().max(1)
+            }
+            C::InputSubmit => self.subscribe()?,
+            C::InputAbort => {
+                self.state = None;
+                tooltip(
+                    &self.message_sender,
+                    "adding feed aborted",
+                    TooltipFlavor::Info,
+                )?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn subscribe(&mut self) -> color_eyre::Result<()> {
+        let Some(state) = self.state.as_ref() else {
+            return Ok(());
+        };
+        let Some(candidate) = state.selected_candidate() else {
+            return Ok(());
+        };
+
+        if let Some(error) = candidate.error.as_ref() {
+            tooltip(
+                &self.message_sender,
+                format!("unable to load feed: {error}").as_str(),
+                TooltipFlavor::Error,
+            )?;
+            return Ok(());
+        }
+
+        let category_id = state
+            .categories
+            .get(state.category)
+            .and_then(|(category_id, _)| category_id.to_owned());
+
+        self.news_flash_utils.add_feed(
+            Url::new(candidate.url.to_owned()),
+            Some(
+                state
+                    .name
+                    .to_owned()
+                    .unwrap_or_else(|| candidate.title.to_owned()),
+            ),
+            category_id,
+        );
+        tooltip(&self.message_sender, "adding feed...", TooltipFlavor::Info)?;
+
+        self.state = None;
+        Ok(())
+    }
+
+    fn preview(&self, candidate: &FeedCandidate) -> Text<'static> {
+        let theme = &self.config.theme;
+        let mut lines = vec![Line::from(vec![
+            Span::styled("URL:     ", theme.header()),
+            Span::styled(candidate.url.to_string(), theme.paragraph()),
+        ])];
+
+        if let Some(website) = candidate.website.as_ref() {
+            lines.push(Line::from(vec![
+                Span::styled("Website: ", theme.header()),
+                Span::styled(website.to_owned(), theme.paragraph()),
+            ]));
+        }
+
+        if let Some(error) = candidate.error.as_ref() {
+            lines.push(Line::default());
+            lines.push(Line::styled(error.to_owned(), theme.tooltip_error()));
+            return Text::from(lines);
+        }
+
+        let frequency = match candidate.posts_per_week {
+            Some(posts_per_week) if posts_per_week >= 1.0 => {
+                format!("~{posts_per_week:.1} posts per week")
+            }
+            Some(posts_per_week) => format!("~{:.1} posts per month", posts_per_week * 30.0 / 7.0),
+            None => "posting frequency unknown".to_owned(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Entries: ", theme.header()),
+            Span::styled(
+                format!(
+                    "{} {}, {frequency}",
+                    candidate.entry_count,
+                    candidate.format.unwrap_or_default()
+                ),
+                theme.paragraph(),
+            ),
+        ]));
+        lines.push(Line::default());
+
+        lines.extend(candidate.entries.iter().map(|entry| {
+            let date = entry
+                .published
+                .map(|published| {
+                    published
+                        .with_timezone(&chrono::Local)
+                        .format(&self.config.date_format)
+                        .to_string()
+                })
+                .unwrap_or_default();
+            Line::from(vec![
+                Span::styled(format!("{date} "), theme.inactive()),
+                Span::styled(entry.title.to_owned(), theme.paragraph()),
+            ])
+        }));
+
+        Text::from(lines)
+    }
+}
+
+impl Widget for &FeedAddPopup {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some(state) = self.state.as_ref() else {
+            return;
+        };
+        let theme = &self.config.theme;
+
+        let candidates_height = state.candidates.len().min(MAX_VISIBLE_CANDIDATES) as u16;
+        let preview = state
+            .selected_candidate()
+            .map(|candidate| self.preview(candidate))
+            .unwrap_or_default();
+
+        let width = (area.width * 4 / 5).max(area.width.min(60));
+        let height = (candidates_height + preview.height() as u16 + 6).min(area.height);
+
+        let [popup_area] = Layout::horizontal([Constraint::Length(width)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [popup_area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(popup_area);
+
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(self.config.border_theme.focused)
+            .border_style(theme.border_focused())
+            .title_top(Line::styled(
+                format!(" Add Feed ({} found) ", state.candidates.len()),
+                theme.header(),
+            ))
+            .title_bottom(Line::styled(
+                " enter: subscribe, left/right: category, esc: cancel ",
+                theme.inactive(),
+            ))
+            .padding(Padding::horizontal(1));
+
+        if self.config.shadows {
+            block = block.shadow(Shadow::light_shade());
+        }
+
+        let inner_area = block.inner(popup_area);
+        Widget::render(Clear, popup_area, buf);
+        block.render(popup_area, buf);
+
+        let [candidates_area, _, preview_area, _, category_area] = Layout::vertical([
+            Constraint::Length(candidates_height),
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner_area);
+
+        // candidates (keeping the selected one visible)
+        let offset = (state.selected + 1).saturating_sub(candidates_height as usize);
+        let candidate_lines = state
+            .candidates
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .map(|(index, candidate)| {
+                let style = if candidate.error.is_some() {
+                    theme.inactive()
+                } else {
+                    theme.feed()
+                };
+                let line = Line::from(vec![
+                    Span::styled(format!("{} ", self.config.icon_set.feed_icon()), style),
+                    Span::styled(candidate.title.to_owned(), style),
+                    Span::styled(
+                        candidate
+                            .format
+                            .map(|format| format!(" ({format})"))
+                            .unwrap_or_default(),
+                        theme.inactive(),
+                    ),
+                ]);
+                if index == state.selected {
+                    line.patch_style(theme.selected(&Style::default()))
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<Line>>();
+        Paragraph::new(candidate_lines).render(candidates_area, buf);
+
+        Paragraph::new(preview)
+            .wrap(Wrap { trim: false })
+            .render(preview_area, buf);
+
+        let category = state
+            .categories
+            .get(state.category)
+            .map(|(_, path)| path.as_str())
+            .unwrap_or_default();
+        Line::from(vec![
+            Span::styled("Category: ", theme.header()),
+            Span::styled(format!("◀ {category} ▶"), theme.category()),
+        ])
+        .render(category_area, buf);
+    }
+}
+
+impl MessageReceiver for FeedAddPopup {
+    async fn process_command(&mut self, message: &Message) -> color_eyre::Result<()> {
+        let mut redraw_required = false;
+        if let Message::Event(event) = message {
+            use Event as E;
+            match event {
+                E::ShowFeedAddPopup(name, candidates, categories, category) => {
+                    self.state = Some(FeedAddState {
+                        name: name.to_owned(),
+                        candidates: candidates.to_owned(),
+                        selected: 0,
+                        categories: categories.to_owned(),
+                        category: *category,
+                    });
+                    redraw_required = true;
+                }
+                E::Key(key_event) if self.is_visible() => {
+                    self.on_key_event(key_event)?;
+                    redraw_required = true;
+                }
+                _ => {}
+            }
+        }
+
+        if redraw_required {
+            self.message_sender
+                .send(Message::Command(Command::Redraw))?;
+        }
+
+        Ok(())
+    }
+}
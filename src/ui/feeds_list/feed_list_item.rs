@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use crate::ui::feeds_list::model::AccountNode;
 use std::fmt::{Debug, Display};
 use std::str::FromStr;
 
@@ -15,6 +16,7 @@ pub enum FeedListItem {
     Feed(Box<Feed>),
     Categories,
     Category(Box<Category>),
+    Account(Box<AccountNode>),
     Tags,
     Tag(Box<Tag>),
     Query(Box<LabeledQuery>),
@@ -28,6 +30,7 @@ impl Debug for FeedListItem {
             I::Feed(feed) => write!(f, "Feed({})", feed.feed_id),
             I::Categories => write!(f, "Categories"),
             I::Category(category) => write!(f, "Category({})", category.category_id),
+            I::Account(account) => write!(f, "Account({})", account.name),
             I::Tags => write!(f, "Tags"),
             I::Tag(tag) => write!(f, "Tag({})", tag.label),
             I::Query(query) => write!(f, "Query({})", query.label),
@@ -77,9 +80,14 @@ impl FeedListItem {
                 config.feeds_label.to_owned(),
                 config.theme.feed(),
             ),
+            // feeds which failed during the last syncs get the error icon
             Feed(feed) => (
                 feed.label.as_str(),
-                config.icon_set.feed_icon(),
+                if feed.error_count > 0 {
+                    config.icon_set.feed_error_icon()
+                } else {
+                    config.icon_set.feed_icon()
+                },
                 config.feed_label.to_owned(),
                 config.theme.feed(),
             ),
@@ -95,6 +103,12 @@ impl FeedListItem {
                 config.category_label.to_owned(),
                 config.theme.category(),
             ),
+            Account(account) => (
+                account.name.as_str(),
+                config.icon_set.account_icon(),
+                config.account_label.to_owned(),
+                config.theme.category(),
+            ),
             Tags => (
                 "",
                 config.icon_set.tags_icon(),
@@ -166,6 +180,15 @@ impl FeedListItem {
             Feeds => "all feeds".to_owned(),
             Categories => "all categories".to_owned(),
             Category(category) => format!("Category: {}", category.label).to_owned(),
+            Account(account) => format!("Account: {}", account.name),
+            Feed(feed) if feed.error_count > 0 => {
+                format!(
+                    "Feed: {} (failed {} times: {})",
+                    feed.label,
+                    feed.error_count,
+                    feed.error_message.as_deref().unwrap_or("unknown error")
+                )
+            }
             Feed(feed) => {
                 format!(
                     "Feed: {} ({})",
@@ -201,6 +224,11 @@ impl TryFrom<FeedListItem> for AugmentedArticleFilter {
                 ..Default::default()
             }
             .into(),
+            Account(account) => ArticleFilter {
+                feeds: account.feed_ids.into(),
+                ..Default::default()
+            }
+            .into(),
             Tags => AugmentedArticleFilter::from_str("tagged").unwrap(),
             Tag(tag) => ArticleFilter {
                 tags: vec![tag.tag_id].into(),
@@ -220,6 +248,7 @@ impl Display for FeedListItem {
             Feed(feed) => write!(f, "feed {}", feed.label),
             Categories => write!(f, "categories"),
             Category(category) => write!(f, "category {}", category.label),
+            Account(account) => write!(f, "account {}", account.name),
             Tags => write!(f, "tags"),
             Tag(tag) => write!(f, "tag #{}", tag.label),
             Query(labeled_article_query) => {
@@ -7,7 +7,7 @@ pub mod prelude {
 }
 
 use feed_list_item::FeedListItem;
-use log::info;
+use log::{info, warn};
 use news_flash::models::{CategoryID, PluginCapabilities, UnifiedMapping, Url};
 use ratatui::layout::Position;
 use tui_tree_widget::TreeItem;
@@ -104,7 +104,7 @@ impl FeedList {
                     .set_category_read(category.category_id.clone())?,
                 Tag(tag) => self.model_data.set_tag_read(tag.tag_id.clone())?,
                 Tags => {}
-                Categories | Query(_) => {
+                Categories | Account(_) | Query(_) => {
                     // reroute to article list
                     self.message_sender.send(Message::Command(Command::In(
                         Panel::ArticleList,
@@ -127,7 +127,7 @@ impl FeedList {
         use FeedListItem::*;
         if let Some(selected) = self.selected().as_ref() {
             match selected {
-                not_supported @ (Feeds | Tags | Query(_) | Categories) => {
+                not_supported @ (Feeds | Tags | Query(_) | Categories | Account(_)) => {
                     return tooltip(
                         &self.message_sender,
                         format!("renaming not supported for {not_supported}").as_str(),
@@ -140,7 +140,7 @@ impl FeedList {
                 Category(category) => {
                     if !self
                         .model_data
-                        .features()
+                        .features_of(&category.category_id)
                         .await?
                         .contains(PluginCapabilities::MODIFY_CATEGORIES)
                     {
@@ -182,7 +182,7 @@ impl FeedList {
         use FeedListItem::*;
         if let Some(selected) = self.selected().as_ref() {
             match selected {
-                not_supported @ (Feeds | Tags | Query(_) | Categories) => {
+                not_supported @ (Feeds | Tags | Query(_) | Categories | Account(_)) => {
                     tooltip(
                         &self.message_sender,
                         format!("removing not supported for {not_supported}").as_str(),
@@ -193,7 +193,7 @@ impl FeedList {
                 Feed(feed) => {
                     if !self
                         .model_data
-                        .features()
+                        .features_of(&feed.feed_id)
                         .await?
                         .contains(PluginCapabilities::ADD_REMOVE_FEEDS)
                     {
@@ -209,7 +209,7 @@ impl FeedList {
                 Category(category) => {
                     if !self
                         .model_data
-                        .features()
+                        .features_of(&category.category_id)
                         .await?
                         .contains(PluginCapabilities::MODIFY_CATEGORIES)
                     {
@@ -254,8 +254,121 @@ impl FeedList {
             })
     }
 
+    fn show_feed_add_popup(
+        &self,
+        name: &Option<String>,
+        category_id: &Option<CategoryID>,
+        candidates: &[FeedCandidate],
+    ) -> color_eyre::Result<()> {
+        let categories = std::iter::once((None, "none (top level)".to_owned()))
+            .chain(
+                self.model_data
+                    .category_paths()
+                    .into_iter()
+                    .map(|(category_id, path)| (Some(category_id), path)),
+            )
+            .collect::<Vec<(Option<CategoryID>, String)>>();
+
+        let category = categories
+            .iter()
+            .position(|(other_category_id, _)| other_category_id == category_id)
+            .unwrap_or_default();
+
+        self.message_sender
+            .send(Message::Event(Event::ShowFeedAddPopup(
+                name.to_owned(),
+                candidates.to_vec(),
+                categories,
+                category,
+            )))?;
+
+        Ok(())
+    }
+
+    async fn show_feed_info(&self) -> color_eyre::Result<()> {
+        let Some(FeedListItem::Feed(feed)) = self.selected() else {
+            return tooltip(
+                &self.message_sender,
+                "select a feed to show its info",
+                TooltipFlavor::Warning,
+            );
+        };
+
+        let statistics = self.model_data.feed_statistics(&feed.feed_id).await?;
+        let format_date = |date: Option<chrono::DateTime<chrono::Utc>>| {
+            date.map(|date| {
+                date.with_timezone(&chrono::Local)
+                    .format(&self.config.date_format)
+                    .to_string()
+            })
+        };
+
+        let fields: Vec<(&str, Option<String>)> = vec![
+            ("Title", Some(feed.label.to_owned())),
+            (
+                "Feed URL",
+                feed.feed_url.as_ref().map(|url| url.to_string()),
+            ),
+            ("Website", feed.website.as_ref().map(|url| url.to_string())),
+            ("Category", self.model_data.category_path_for(&feed.feed_id)),
+            ("Articles", Some(statistics.total.to_string())),
+            ("Unread", Some(statistics.unread.to_string())),
+            ("Marked", Some(statistics.marked.to_string())),
+            ("Tagged", Some(statistics.tagged.to_string())),
+            (
+                "Posts per week",
+                statistics
+                    .posts_per_week
+                    .map(|posts_per_week| format!("{posts_per_week:.1}")),
+            ),
+            ("Last article", format_date(statistics.newest)),
+            ("Oldest article", format_date(statistics.oldest)),
+            ("Icon", feed.icon_url.as_ref().map(|url| url.to_string())),
+        ];
+
+        let name_width = fields
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or_default();
+
+        let lines = fields
+            .iter()
+            .map(|(name, value)| {
+                Line::from(vec![
+                    Span::styled(format!("{name:<name_width$}  "), self.config.theme.header()),
+                    match value {
+                        Some(value) => {
+                            Span::styled(value.to_owned(), self.config.theme.paragraph())
+                        }
+                        None => Span::styled("-", self.config.theme.inactive()),
+                    },
+                ])
+            })
+            .collect::<Vec<Line<'static>>>();
+
+        // selecting a field yanks its value
+        let commands = fields
+            .into_iter()
+            .map(|(_, value)| value.map_or(Command::NoOperation, Command::ClipboardCopy))
+            .collect::<Vec<Command>>();
+
+        self.message_sender
+            .send(Message::Event(Event::ShowSelectionPopup(
+                format!("Feed Info: {}", feed.label),
+                Text::from(lines),
+                commands,
+            )))?;
+
+        Ok(())
+    }
+
     async fn add_category(&self, name: &String) -> color_eyre::Result<()> {
-        let features = self.model_data.features().await?;
+        let mut category_id = self.maybe_selected_category();
+        let features = match category_id.as_ref() {
+            Some(category_id) => self.model_data.features_of(category_id).await?,
+            None => self.model_data.features().await?,
+        };
         if !features.contains(
             PluginCapabilities::SUPPORT_CATEGORIES | PluginCapabilities::MODIFY_CATEGORIES,
         ) {
@@ -266,7 +379,6 @@ impl FeedList {
             )?;
         }
 
-        let mut category_id = self.maybe_selected_category();
         if category_id.is_some() && !features.intersects(PluginCapabilities::SUPPORT_SUBCATEGORIES)
         {
             category_id = None;
@@ -291,7 +403,7 @@ impl FeedList {
     async fn check_tag_capability(&self) -> color_eyre::Result<bool> {
         if !self
             .model_data
-            .features()
+            .any_features()
             .await?
             .contains(PluginCapabilities::SUPPORT_TAGS)
         {
@@ -306,9 +418,18 @@ impl FeedList {
     }
 
     async fn change_feed_url(&self, url: &Url) -> color_eyre::Result<()> {
+        let Some(FeedListItem::Feed(feed)) = self.selected() else {
+            tooltip(
+                &self.message_sender,
+                "no feed selected",
+                TooltipFlavor::Warning,
+            )?;
+            return Ok(());
+        };
+
         if !self
             .model_data
-            .features()
+            .features_of(&feed.feed_id)
             .await?
             .contains(PluginCapabilities::EDIT_FEED_URLS)
         {
@@ -320,15 +441,6 @@ impl FeedList {
             return Ok(());
         }
 
-        let Some(FeedListItem::Feed(feed)) = self.selected() else {
-            tooltip(
-                &self.message_sender,
-                "no feed selected",
-                TooltipFlavor::Warning,
-            )?;
-            return Ok(());
-        };
-
         self.model_data
             .change_feed_url(feed.feed_id.to_owned(), url.to_string())?;
 
@@ -508,6 +620,19 @@ impl FeedList {
                     .map(|count| *count > 0)
                     .unwrap_or(false),
             },
+            FeedListItem::Account(account) => match scope {
+                S::All => true,
+                S::Unread => self
+                    .model_data
+                    .unread_count_for_account()
+                    .get(&account.name)
+                    .is_some_and(|count| *count > 0),
+                S::Marked => self
+                    .model_data
+                    .marked_count_for_account()
+                    .get(&account.name)
+                    .is_some_and(|count| *count > 0),
+            },
             // implementation for All and Tags
             // FeedListItem::All => match scope {
             //     S::Unread => *self.model_data.unread_count_all() > 0,
@@ -620,6 +745,7 @@ impl FeedList {
             FeedListItem::Tag(_)
             | FeedListItem::Feeds
             | FeedListItem::Categories
+            | FeedListItem::Account(_)
             | FeedListItem::Tags
             | FeedListItem::Query(_) => false,
         }
@@ -679,6 +805,41 @@ impl FeedList {
     async fn sort(&self) -> color_eyre::Result<()> {
         self.model_data.sort().await
     }
+
+    // automatic syncs are skipped in offline mode, outside of the sync windows and on metered
+    // connections
+    fn scheduled_sync(&self) -> color_eyre::Result<()> {
+        if self.model_data.is_offline_mode() {
+            info!("offline mode, skipping scheduled sync");
+            return Ok(());
+        }
+
+        if !self.config.is_in_sync_window(chrono::Local::now().time()) {
+            info!("outside of sync windows, skipping scheduled sync");
+            return Ok(());
+        }
+
+        let Some(metered_check_cmd) = self.config.metered_check_cmd.clone() else {
+            self.message_sender
+                .send(Message::Command(Command::FeedListSync))?;
+            return Ok(());
+        };
+
+        let message_sender = self.message_sender.clone();
+        tokio::spawn(async move {
+            match is_metered_connection(&metered_check_cmd).await {
+                Ok(true) => {
+                    info!("metered connection, skipping scheduled sync");
+                    return;
+                }
+                Ok(false) => {}
+                Err(err) => warn!("unable to check for metered connection: {err}"),
+            }
+            let _ = message_sender.send(Message::Command(Command::FeedListSync));
+        });
+
+        Ok(())
+    }
 }
 
 impl MessageReceiver for FeedList {
@@ -691,8 +852,7 @@ impl MessageReceiver for FeedList {
                     .map(|time_to_sync| Instant::now() > time_to_sync)
                     .unwrap_or(false)
             {
-                self.message_sender
-                    .send(Message::Command(Command::FeedListSync))?;
+                self.scheduled_sync()?;
                 self.last_sync = Instant::now();
             }
 
@@ -806,7 +966,7 @@ impl MessageReceiver for FeedList {
                     match action_scope {
                         ActionScope::All => self.model_data.set_all_read()?,
                         ActionScope::Current => self.set_current_read()?,
-                        query_scope @ ActionScope::Query(_) => {
+                        query_scope @ (ActionScope::Query(_) | ActionScope::Group) => {
                             // don't know how to handle article query => "rerouting" to article
                             // list
                             self.message_sender.send(Message::Command(Command::In(
@@ -831,14 +991,29 @@ impl MessageReceiver for FeedList {
                             TooltipFlavor::Error,
                         )?;
                     } else {
-                        self.model_data.add_feed(
-                            url.as_ref()
-                                .ok_or(color_eyre::eyre::eyre!("no url defined"))?
-                                .to_owned(),
-                            name.clone(),
-                            self.maybe_selected_category(),
-                        )?;
-                        tooltip(&self.message_sender, "adding feed...", TooltipFlavor::Info)?;
+                        let url = url
+                            .as_ref()
+                            .ok_or(color_eyre::eyre::eyre!("no url defined"))?
+                            .to_owned();
+                        // there is no discovery in offline mode, the URL is added as it is
+                        if self.model_data.is_offline_mode() {
+                            self.model_data.add_feed(
+                                url,
+                                name.clone(),
+                                self.maybe_selected_category(),
+                            )?;
+                        } else {
+                            self.model_data.discover_feeds(
+                                url,
+                                name.clone(),
+                                self.maybe_selected_category(),
+                            )?;
+                            tooltip(
+                                &self.message_sender,
+                                "looking for feeds...",
+                                TooltipFlavor::Info,
+                            )?;
+                        }
                     }
                 }
 
@@ -907,6 +1082,10 @@ impl MessageReceiver for FeedList {
                     self.paste_feed_or_category(position)?;
                 }
 
+                C::FeedListFeedInfo => {
+                    self.show_feed_info().await?;
+                }
+
                 C::FeedListSort => {
                     self.sort().await?;
                     model_needs_update = true;
@@ -958,6 +1137,18 @@ impl MessageReceiver for FeedList {
                     self.is_focused = *state == AppState::FeedSelection;
                 }
 
+                E::AsyncFeedDiscoveryFinished(url, name, category_id, candidates) => {
+                    if candidates.is_empty() {
+                        self.model_data.add_feed(
+                            url.to_owned(),
+                            name.clone(),
+                            category_id.clone(),
+                        )?;
+                    } else {
+                        self.show_feed_add_popup(name, category_id, candidates)?;
+                    }
+                }
+
                 E::AsyncFeedAddFinished(feed) => {
                     tooltip(
                         &self.message_sender,
@@ -1,10 +1,17 @@
-use std::{cmp::Ordering, collections::HashMap, hash::Hash, sync::Arc};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    sync::Arc,
+};
+
+use chrono::{DateTime, Utc};
 
 use getset::Getters;
 use log::info;
 use news_flash::models::{
     ArticleFilter, ArticleID, Category, CategoryID, CategoryMapping, Feed, FeedID, FeedMapping,
-    PluginCapabilities, Tag, TagID, Url,
+    Marked, NEWSFLASH_TOPLEVEL, PluginCapabilities, Read, Tag, TagID, Url,
 };
 use ratatui::style::Color;
 
@@ -16,6 +23,24 @@ pub enum FeedOrCategory {
     Category(CategoryID),
 }
 
+/// Article statistics of a single feed
+pub(super) struct FeedStatistics {
+    pub total: usize,
+    pub unread: usize,
+    pub marked: usize,
+    pub tagged: usize,
+    pub posts_per_week: Option<f64>,
+    pub newest: Option<DateTime<Utc>>,
+    pub oldest: Option<DateTime<Utc>>,
+}
+
+/// Top-level node of an account in the unified view
+#[derive(Clone, Hash, Eq, PartialEq, Debug)]
+pub struct AccountNode {
+    pub name: String,
+    pub feed_ids: Vec<FeedID>,
+}
+
 #[derive(Getters)]
 #[getset(get = "pub")]
 pub(super) struct FeedListModelData {
@@ -34,6 +59,11 @@ pub(super) struct FeedListModelData {
     marked_count_for_feed_or_category: HashMap<FeedOrCategory, i64>,
     category_tree: HashMap<CategoryID, Vec<FeedOrCategory>>,
     roots: Vec<FeedOrCategory>,
+    // only filled in the unified view
+    account_nodes: Vec<AccountNode>,
+    roots_for_account: HashMap<String, Vec<FeedOrCategory>>,
+    unread_count_for_account: HashMap<String, i64>,
+    marked_count_for_account: HashMap<String, i64>,
     category_mapping_for_category: HashMap<CategoryID, CategoryMapping>,
     feed_mapping_for_feed: HashMap<FeedID, FeedMapping>,
 
@@ -68,16 +98,24 @@ impl FeedListModelData {
             marked_count_for_feed_or_category: HashMap::default(),
             category_tree: HashMap::default(),
             roots: Vec::default(),
+            account_nodes: Vec::default(),
+            roots_for_account: HashMap::default(),
+            unread_count_for_account: HashMap::default(),
+            marked_count_for_account: HashMap::default(),
             category_mapping_for_category: HashMap::default(),
             feed_mapping_for_feed: HashMap::default(),
         }
     }
 
+    pub(super) fn is_offline_mode(&self) -> bool {
+        self.news_flash_utils.is_offline_mode()
+    }
+
     pub(super) async fn update(&mut self) -> color_eyre::Result<()> {
-        let news_flash = self.news_flash_utils.news_flash_lock.read().await;
+        let accounts = self.news_flash_utils.accounts();
 
         // feeds
-        let (feeds, feed_mappings) = news_flash.get_feeds()?;
+        let (feeds, feed_mappings) = accounts.get_feeds().await?;
         self.feed_mapping_for_feed =
             NewsFlashUtils::generate_id_map(&feed_mappings, |feed_mapping| {
                 feed_mapping.feed_id.clone()
@@ -89,7 +127,7 @@ impl FeedListModelData {
         self.feed_map = NewsFlashUtils::generate_id_map(&self.feeds, |f| f.feed_id.clone());
 
         // categories
-        let (categories, category_mappings) = news_flash.get_categories()?;
+        let (categories, category_mappings) = accounts.get_categories().await?;
         self.categories = categories;
         self.category_mapping_for_category =
             NewsFlashUtils::generate_id_map(&category_mappings, |category_mapping| {
@@ -103,7 +141,7 @@ impl FeedListModelData {
             NewsFlashUtils::generate_id_map(&self.categories, |c| c.category_id.clone());
 
         // tags
-        let (tags, taggings) = news_flash.get_tags()?;
+        let (tags, taggings) = accounts.get_tags().await?;
         self.tags = tags;
 
         self.articles_for_tag = NewsFlashUtils::generate_one_to_many(
@@ -112,7 +150,7 @@ impl FeedListModelData {
             |a| a.article_id.clone(),
         );
 
-        self.unread_count_for_tag = self.update_unread_count_for_tags(&news_flash)?;
+        self.unread_count_for_tag = self.update_unread_count_for_tags().await?;
 
         // build category/feed tree
         self.category_tree = HashMap::new();
@@ -203,20 +241,20 @@ impl FeedListModelData {
         self.roots.append(&mut feed_roots);
 
         // no we can build the tree structure
-        self.unread_count_all = news_flash.unread_count_all()?;
-        let mut unread_count_for_feed_or_category = news_flash
-            .unread_count_feed_map(true)?
+        self.unread_count_all = accounts.unread_count_all().await?;
+        let mut unread_count_for_feed_or_category = accounts
+            .unread_count_feed_map(true)
+            .await?
             .into_iter()
             .map(|(feed_id, unread)| (feed_id.into(), unread))
             .collect();
-        let mut marked_count_for_feed_or_category = news_flash
-            .marked_count_feed_map()?
+        let mut marked_count_for_feed_or_category = accounts
+            .marked_count_feed_map()
+            .await?
             .into_iter()
             .map(|(feed_id, marked)| (feed_id.into(), marked))
             .collect();
 
-        drop(news_flash);
-
         self.roots.iter().for_each(|feed_or_category| {
             // count unread
             if let FeedOrCategory::Category(category_id) = feed_or_category {
@@ -238,20 +276,82 @@ impl FeedListModelData {
         self.unread_count_for_feed_or_category = unread_count_for_feed_or_category;
         self.marked_count_for_feed_or_category = marked_count_for_feed_or_category;
 
+        self.update_accounts();
+
         Ok(())
     }
 
-    fn update_unread_count_for_tags(
-        &self,
-        news_flash: &tokio::sync::RwLockReadGuard<'_, news_flash::NewsFlash>,
-    ) -> color_eyre::Result<HashMap<TagID, i64>> {
+    /// Returns the top-level category of the account owning the feed
+    pub(super) fn toplevel_category_for(&self, feed_id: &FeedID) -> CategoryID {
+        self.news_flash_utils
+            .accounts()
+            .account_of(feed_id)
+            .qualify_id((*NEWSFLASH_TOPLEVEL).to_owned())
+    }
+
+    // groups the roots by the accounts owning them
+    fn update_accounts(&mut self) {
+        self.account_nodes.clear();
+        self.roots_for_account.clear();
+        self.unread_count_for_account.clear();
+        self.marked_count_for_account.clear();
+
+        let accounts = self.news_flash_utils.accounts();
+        if !accounts.is_unified() {
+            return;
+        }
+
+        for account in accounts.iter() {
+            let roots = self
+                .roots
+                .iter()
+                .filter(|root| match root {
+                    FeedOrCategory::Feed(feed_id) => accounts.is_owner(account, feed_id),
+                    FeedOrCategory::Category(category_id) => {
+                        accounts.is_owner(account, category_id)
+                    }
+                })
+                .cloned()
+                .collect::<Vec<FeedOrCategory>>();
+
+            let count = |count_map: &HashMap<FeedOrCategory, i64>| {
+                roots
+                    .iter()
+                    .filter_map(|root| count_map.get(root))
+                    .sum::<i64>()
+            };
+            self.unread_count_for_account.insert(
+                account.name().to_owned(),
+                count(&self.unread_count_for_feed_or_category),
+            );
+            self.marked_count_for_account.insert(
+                account.name().to_owned(),
+                count(&self.marked_count_for_feed_or_category),
+            );
+
+            self.account_nodes.push(AccountNode {
+                name: account.name().to_owned(),
+                feed_ids: self
+                    .feeds
+                    .iter()
+                    .filter(|feed| accounts.is_owner(account, &feed.feed_id))
+                    .map(|feed| feed.feed_id.to_owned())
+                    .collect(),
+            });
+            self.roots_for_account
+                .insert(account.name().to_owned(), roots);
+        }
+    }
+
+    async fn update_unread_count_for_tags(&self) -> color_eyre::Result<HashMap<TagID, i64>> {
+        let accounts = self.news_flash_utils.accounts();
         let mut unread_count_for_tag = HashMap::new();
 
         for tag in self.tags.iter() {
             let filter = ArticleFilter::tag_unread(&tag.tag_id);
             unread_count_for_tag.insert(
                 tag.tag_id.clone(),
-                news_flash.get_articles(filter)?.len() as i64,
+                accounts.get_articles(filter).await?.len() as i64,
             );
         }
 
@@ -370,16 +470,114 @@ impl FeedListModelData {
         Ok(())
     }
 
+    pub(super) fn discover_feeds(
+        &self,
+        url: Url,
+        name: Option<String>,
+        category_id: Option<CategoryID>,
+    ) -> color_eyre::Result<()> {
+        self.news_flash_utils.discover_feeds(url, name, category_id);
+        Ok(())
+    }
+
     pub(super) fn add_feed(
         &self,
         url: Url,
-        label: Option<String>,
+        name: Option<String>,
         category_id: Option<CategoryID>,
     ) -> color_eyre::Result<()> {
-        self.news_flash_utils.add_feed(url, label, category_id);
+        self.news_flash_utils.add_feed(url, name, category_id);
         Ok(())
     }
 
+    // all categories with their path (e.g., `News/Tech`), prefixed by the account name in the
+    // unified view
+    pub(super) fn category_paths(&self) -> Vec<(CategoryID, String)> {
+        let accounts = self.news_flash_utils.accounts();
+
+        let mut category_paths = self
+            .categories
+            .iter()
+            .map(|category| {
+                let mut path = vec![category.label.to_owned()];
+                let mut parent = self.parent_category(&category.category_id);
+                while let Some(category) = parent
+                    && path.len() <= self.categories.len()
+                {
+                    path.push(category.label.to_owned());
+                    parent = self.parent_category(&category.category_id);
+                }
+                if accounts.is_unified() {
+                    path.push(accounts.account_of(&category.category_id).name().to_owned());
+                }
+                path.reverse();
+                (category.category_id.to_owned(), path.join("/"))
+            })
+            .collect::<Vec<(CategoryID, String)>>();
+
+        category_paths.sort_by_key(|(_, path)| path.to_lowercase());
+        category_paths
+    }
+
+    // path of the category the feed is in (`None` if the feed is at the top level)
+    pub(super) fn category_path_for(&self, feed_id: &FeedID) -> Option<String> {
+        let category_id = &self.feed_mapping_for_feed.get(feed_id)?.category_id;
+        self.category_paths()
+            .into_iter()
+            .find(|(other_category_id, _)| other_category_id == category_id)
+            .map(|(_, path)| path)
+    }
+
+    pub(super) async fn feed_statistics(
+        &self,
+        feed_id: &FeedID,
+    ) -> color_eyre::Result<FeedStatistics> {
+        let articles = self
+            .news_flash_utils
+            .accounts()
+            .get_articles(ArticleFilter {
+                feeds: vec![feed_id.to_owned()].into(),
+                ..Default::default()
+            })
+            .await?;
+
+        let tagged_article_ids = self
+            .articles_for_tag
+            .values()
+            .flatten()
+            .collect::<HashSet<&ArticleID>>();
+
+        let dates = articles
+            .iter()
+            .map(|article| article.date)
+            .collect::<Vec<DateTime<Utc>>>();
+
+        Ok(FeedStatistics {
+            total: articles.len(),
+            unread: articles
+                .iter()
+                .filter(|article| article.unread == Read::Unread)
+                .count(),
+            marked: articles
+                .iter()
+                .filter(|article| article.marked == Marked::Marked)
+                .count(),
+            tagged: articles
+                .iter()
+                .filter(|article| tagged_article_ids.contains(&article.article_id))
+                .count(),
+            posts_per_week: posts_per_week(&dates),
+            newest: dates.iter().max().copied(),
+            oldest: dates.iter().min().copied(),
+        })
+    }
+
+    fn parent_category(&self, category_id: &CategoryID) -> Option<&Category> {
+        self.category_mapping_for_category
+            .get(category_id)
+            .and_then(|category_mapping| self.category_map.get(&category_mapping.parent_id))
+    }
+
     pub(super) fn fetch_feed(&self, feed_id: FeedID) -> color_eyre::Result<()> {
         self.news_flash_utils.fetch_feed(feed_id);
         Ok(())
@@ -423,6 +621,18 @@ impl FeedListModelData {
         Ok(())
     }
 
+    pub(super) async fn features_of<I: AccountId>(
+        &self,
+        id: &I,
+    ) -> color_eyre::Result<PluginCapabilities> {
+        self.news_flash_utils.accounts().features_of(id).await
+    }
+
+    pub(super) async fn any_features(&self) -> color_eyre::Result<PluginCapabilities> {
+        self.news_flash_utils.accounts().any_features().await
+    }
+
+    // capabilities of the primary account, to which new feeds and categories are added
     pub(super) async fn features(&self) -> color_eyre::Result<PluginCapabilities> {
         Ok(self
             .news_flash_utils
@@ -457,8 +667,9 @@ impl FeedListModelData {
     }
 
     pub(super) async fn sort(&self) -> color_eyre::Result<()> {
-        let news_flash = self.news_flash_utils.news_flash_lock.read().await;
-        news_flash.sort_alphabetically().await?;
+        for account in self.news_flash_utils.accounts().iter() {
+            account.news_flash().await.sort_alphabetically().await?;
+        }
         Ok(())
     }
 }
@@ -1,12 +1,12 @@
 use std::collections::HashSet;
 
-use super::model::FeedOrCategory;
+use super::model::{AccountNode, FeedOrCategory};
 use crate::prelude::*;
 use crate::ui::feeds_list::model::FeedListModelData;
 
 use getset::{Getters, MutGetters};
 use log::info;
-use news_flash::models::{Category, Feed, FeedMapping, NEWSFLASH_TOPLEVEL, UnifiedMapping};
+use news_flash::models::{Category, Feed, FeedMapping, UnifiedMapping};
 use news_flash::models::{PluginCapabilities, Tag};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Scrollbar;
@@ -190,17 +190,16 @@ impl FeedListViewData {
         }
     }
 
-    fn add_categories_item(
+    fn map_roots_to_tree_items<'a>(
         &mut self,
         config: &Config,
         model_data: &FeedListModelData,
-        item_type: &FeedListItemType,
+        roots: &[FeedOrCategory],
         search_term: &Option<SearchTerm>,
-    ) -> color_eyre::Result<()> {
+    ) -> Vec<TreeItem<'a, FeedListItem>> {
         let mut root_items = Vec::new();
 
-        let feeds_or_categories = model_data
-            .roots()
+        let feeds_or_categories = roots
             .iter()
             .filter(|feed_or_category| self.include_feed_or_category(model_data, feed_or_category))
             .collect::<Vec<&FeedOrCategory>>();
@@ -231,7 +230,26 @@ impl FeedListViewData {
             }
         }
 
+        root_items
+    }
+
+    fn add_categories_item(
+        &mut self,
+        config: &Config,
+        model_data: &FeedListModelData,
+        item_type: &FeedListItemType,
+        search_term: &Option<SearchTerm>,
+    ) -> color_eyre::Result<()> {
+        let mut root_items =
+            self.map_roots_to_tree_items(config, model_data, model_data.roots(), search_term);
+
         match item_type {
+            // in the unified view, the categories of each account are shown under the account
+            FeedListItemType::Tree if !model_data.account_nodes().is_empty() => {
+                for account_node in model_data.account_nodes() {
+                    self.add_account_item(config, model_data, account_node, search_term)?;
+                }
+            }
             FeedListItemType::Tree => {
                 let categories_item = FeedListItem::Categories;
                 let mut categories_text = categories_item.to_text(config, None, None);
@@ -255,6 +273,47 @@ impl FeedListViewData {
         Ok(())
     }
 
+    fn add_account_item(
+        &mut self,
+        config: &Config,
+        model_data: &FeedListModelData,
+        account_node: &AccountNode,
+        search_term: &Option<SearchTerm>,
+    ) -> color_eyre::Result<()> {
+        let roots = model_data
+            .roots_for_account()
+            .get(&account_node.name)
+            .cloned()
+            .unwrap_or_default();
+        let children = self.map_roots_to_tree_items(config, model_data, &roots, search_term);
+
+        let account_item = FeedListItem::Account(Box::new(account_node.clone()));
+        let mut account_text = account_item.to_text(
+            config,
+            model_data
+                .unread_count_for_account()
+                .get(&account_node.name)
+                .copied(),
+            model_data
+                .marked_count_for_account()
+                .get(&account_node.name)
+                .copied(),
+        );
+        if let Some(search_term) = search_term.as_ref()
+            && search_term.test_text(&account_text)
+        {
+            patch_text_style(
+                &mut account_text,
+                config.theme.highlighted(&Default::default()),
+            );
+            self.found_items.insert(account_item.to_owned());
+        }
+
+        self.tree_items
+            .push(TreeItem::new(account_item, account_text, children)?);
+        Ok(())
+    }
+
     async fn add_tags_item(
         &mut self,
         config: &Config,
@@ -263,7 +322,7 @@ impl FeedListViewData {
         search_term: &Option<SearchTerm>,
     ) -> Result<(), color_eyre::eyre::Error> {
         if model_data
-            .features()
+            .any_features()
             .await?
             .contains(PluginCapabilities::SUPPORT_TAGS)
         {
@@ -503,7 +562,7 @@ impl FeedListViewData {
                     Some(feed_mapping) => feed_mapping.to_owned(),
                     None => FeedMapping {
                         feed_id: feed_id.to_owned(),
-                        category_id: (*NEWSFLASH_TOPLEVEL).to_owned(),
+                        category_id: model_data.toplevel_category_for(&feed_id),
                         sort_index: None,
                     },
                 };
@@ -21,6 +21,53 @@ pub struct PopupState<'a> {
     scroll_offset_y: u16,
     scroll_offset_x: u16,
     search_input_active: bool,
+    commands: Vec<Command>,
+    selected: usize,
+}
+
+impl PopupState<'_> {
+    fn is_selection(&self) -> bool {
+        !self.commands.is_empty()
+    }
+
+    // indices of the lines matching the current search input
+    fn visible_lines(&self) -> Vec<usize> {
+        let Some(search_input) = self.search_input.as_ref() else {
+            return (0..self.contents.lines.len()).collect();
+        };
+
+        let matcher = SkimMatcherV2::default();
+        self.contents
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| {
+                line.spans.iter().any(|span| {
+                    matcher
+                        .fuzzy_match(span.content.as_ref(), &search_input.lines()[0])
+                        .is_some()
+                })
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn select_next(&mut self, reverse: bool) {
+        let visible_lines = self.visible_lines();
+        let position = visible_lines
+            .iter()
+            .position(|index| *index == self.selected);
+        let next = match (position, reverse) {
+            (None, _) => visible_lines.first(),
+            (Some(position), false) => visible_lines.get(position + 1),
+            (Some(position), true) => position
+                .checked_sub(1)
+                .and_then(|position| visible_lines.get(position)),
+        };
+        if let Some(next) = next {
+            self.selected = *next;
+        }
+    }
 }
 
 pub struct HelpPopup<'a> {
@@ -77,12 +124,30 @@ impl<'a> HelpPopup<'a> {
 
         use Command as C;
 
+        if state.is_selection() {
+            match command {
+                C::NavigateUp => state.select_next(true),
+                C::NavigateDown => state.select_next(false),
+                C::InputSubmit => {
+                    if state.visible_lines().contains(&state.selected)
+                        && let Some(command) = state.commands.get(state.selected)
+                    {
+                        self.message_sender
+                            .send(Message::Command(command.to_owned()))?;
+                    }
+                    self.state = None;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
         match command {
-            C::NavigateUp => {
+            C::NavigateUp if !state.is_selection() => {
                 state.scroll_offset_y = (state.scroll_offset_y.saturating_sub(1))
                     .clamp(0, state.contents.height() as u16)
             }
-            C::NavigateDown => {
+            C::NavigateDown if !state.is_selection() => {
                 state.scroll_offset_y = (state.scroll_offset_y.saturating_add(1))
                     .clamp(0, state.contents.height() as u16)
             }
@@ -169,15 +234,18 @@ impl<'a> HelpPopup<'a> {
 
 impl<'a> Widget for &HelpPopup<'a> {
     fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
-        if let Some(PopupState {
-            title,
-            contents,
-            is_modal,
-            scroll_offset_y,
-            scroll_offset_x,
-            search_input,
-            search_input_active,
-        }) = self.state.as_ref()
+        if let Some(
+            state @ PopupState {
+                title,
+                contents,
+                is_modal,
+                scroll_offset_y,
+                scroll_offset_x,
+                search_input,
+                search_input_active,
+                ..
+            },
+        ) = self.state.as_ref()
         {
             let (width, height) = (
                 (contents.width() + 4).min((area.width as usize).saturating_sub(4)),
@@ -216,7 +284,7 @@ impl<'a> Widget for &HelpPopup<'a> {
             block.render(popup_area, buf);
 
             if *is_modal {
-                match search_input {
+                let contents_chunk = match search_input {
                     Some(search_input) => {
                         let [contents_chunk, search_chunk] = Layout::default()
                             .direction(Direction::Vertical)
@@ -227,30 +295,6 @@ impl<'a> Widget for &HelpPopup<'a> {
                             ])
                             .areas(inner_area);
 
-                        let matcher = SkimMatcherV2::default();
-                        let lines = contents
-                            .lines
-                            .iter()
-                            .filter(|line| {
-                                line.spans.iter().any(|span| {
-                                    matcher
-                                        .fuzzy_match(
-                                            span.content.as_ref(),
-                                            &search_input.lines()[0],
-                                        )
-                                        .is_some()
-                                })
-                            })
-                            .cloned()
-                            .collect::<Vec<Line>>();
-
-                        let entries: u16 = lines.len() as u16;
-                        let paragraph = Paragraph::new(lines).scroll((
-                            (*scroll_offset_y).min(entries.saturating_sub(contents_chunk.height)),
-                            *scroll_offset_x,
-                        ));
-                        paragraph.render(contents_chunk, buf);
-
                         if *search_input_active {
                             search_input.render(search_chunk, buf);
                         } else {
@@ -260,14 +304,41 @@ impl<'a> Widget for &HelpPopup<'a> {
                             )
                             .render(search_chunk, buf);
                         }
+                        contents_chunk
                     }
-                    None => {
-                        let paragraph = Paragraph::new(contents.to_owned())
-                            .scroll((*scroll_offset_y, *scroll_offset_x));
-
-                        paragraph.render(inner_area, buf);
-                    }
-                }
+                    None => inner_area,
+                };
+
+                let visible_lines = state.visible_lines();
+                let lines = visible_lines
+                    .iter()
+                    .map(|index| {
+                        let line = contents.lines[*index].to_owned();
+                        if state.is_selection() && *index == state.selected {
+                            line.patch_style(self.config.theme.selected(&Style::default()))
+                        } else {
+                            line
+                        }
+                    })
+                    .collect::<Vec<Line>>();
+
+                let entries: u16 = lines.len() as u16;
+                let scroll_offset_y = match visible_lines
+                    .iter()
+                    .position(|index| *index == state.selected)
+                {
+                    // keep the selected line visible
+                    Some(position) if state.is_selection() => (*scroll_offset_y).clamp(
+                        (position as u16 + 1).saturating_sub(contents_chunk.height),
+                        position as u16,
+                    ),
+                    _ => *scroll_offset_y,
+                };
+                let paragraph = Paragraph::new(lines).scroll((
+                    scroll_offset_y.min(entries.saturating_sub(contents_chunk.height)),
+                    *scroll_offset_x,
+                ));
+                paragraph.render(contents_chunk, buf);
             } else {
                 contents.render(inner_area, buf);
             }
@@ -290,6 +361,8 @@ impl<'a> MessageReceiver for HelpPopup<'a> {
                         scroll_offset_y: 0,
                         scroll_offset_x: 0,
                         search_input_active: false,
+                        commands: Vec::default(),
+                        selected: 0,
                     });
                     redraw_required = true;
                 }
@@ -302,6 +375,22 @@ impl<'a> MessageReceiver for HelpPopup<'a> {
                         scroll_offset_y: 0,
                         scroll_offset_x: 0,
                         search_input_active: false,
+                        commands: Vec::default(),
+                        selected: 0,
+                    });
+                    redraw_required = true;
+                }
+                E::ShowSelectionPopup(title, contents, commands) => {
+                    self.state = Some(PopupState {
+                        contents: contents.to_owned(),
+                        title: title.to_owned(),
+                        is_modal: true,
+                        search_input: None,
+                        scroll_offset_y: 0,
+                        scroll_offset_x: 0,
+                        search_input_active: false,
+                        commands: commands.to_owned(),
+                        selected: 0,
                     });
                     redraw_required = true;
                 }
@@ -3,9 +3,12 @@ mod articles_list;
 mod batch;
 mod command_confirm;
 mod command_input;
+mod feed_add_popup;
 mod feeds_list;
 mod help_popup;
 mod mouse;
+mod playback;
+mod share;
 mod tooltip;
 mod view;
 
@@ -15,9 +18,12 @@ pub mod prelude {
     pub use super::batch::BatchProcessor;
     pub use super::command_confirm::CommandConfirm;
     pub use super::command_input::CommandInput;
+    pub use super::feed_add_popup::FeedAddPopup;
     pub use super::feeds_list::prelude::*;
     pub use super::help_popup::HelpPopup;
     pub use super::mouse::PanelAreas;
+    pub use super::playback::{PlaybackQueue, QueueEntry};
+    pub use super::share::share_with_target;
     pub use super::tooltip::{Tooltip, TooltipFlavor, tooltip};
     pub use super::{App, AppState};
 }
@@ -27,6 +33,7 @@ use crate::prelude::*;
 use chrono::TimeDelta;
 use log::{debug, error, info, trace, warn};
 use news_flash::error::{FeedApiError, NewsFlashError};
+use news_flash::models::{ArticleFilter, TagID};
 use notify_rust::{Notification, Timeout};
 use ratatui::DefaultTerminal;
 use ratatui::crossterm::event::{MouseButton, MouseEventKind};
@@ -144,13 +151,21 @@ pub struct App {
     command_input: CommandInput,
     command_confirm: CommandConfirm,
     help_popup: HelpPopup<'static>,
+    feed_add_popup: FeedAddPopup,
     async_operation_throbber: ThrobberState,
     batch_processor: BatchProcessor,
+    playback_queue: PlaybackQueue,
 
     is_offline: bool,
 
+    // number of operations made while offline which still need to be replayed
+    pending_count: usize,
+
     is_running: bool,
 
+    // profile to switch to after quitting
+    next_profile: Option<String>,
+
     panel_areas: PanelAreas,
 
     /// When true, the user is dragging the horizontal border; stores the initial row of the drag.
@@ -175,6 +190,7 @@ impl App {
             config: Arc::clone(&config_arc),
             news_flash_utils: news_flash_utils.clone(),
             is_running: true,
+            next_profile: None,
             message_sender: message_sender.clone(),
             input_command_generator: InputCommandGenerator::new(
                 config_arc.clone(),
@@ -205,7 +221,17 @@ impl App {
                 news_flash_utils.clone(),
                 message_sender.clone(),
             ),
+            playback_queue: PlaybackQueue::new(
+                config_arc.clone(),
+                news_flash_utils.clone(),
+                message_sender.clone(),
+            ),
             help_popup: HelpPopup::new(config_arc.clone(), message_sender.clone()),
+            feed_add_popup: FeedAddPopup::new(
+                config_arc.clone(),
+                news_flash_utils.clone(),
+                message_sender.clone(),
+            ),
             command_confirm: CommandConfirm::new(config_arc.clone(), message_sender.clone()),
             tooltip: Tooltip::new(
                 "Stay up-to-date! Press `c e` to add eilmeldung release feed!".into(),
@@ -213,6 +239,7 @@ impl App {
             ),
             async_operation_throbber: ThrobberState::default(),
             is_offline: false,
+            pending_count: 0,
             panel_areas: PanelAreas::default(),
             drag_resize_active: false,
             articles_height_override: None,
@@ -222,11 +249,12 @@ impl App {
         app
     }
 
+    /// Runs the application until it is quit and returns the profile to switch to, if requested
     pub async fn run(
         mut self,
         mut message_receiver: UnboundedReceiver<Message>,
         terminal: DefaultTerminal,
-    ) -> color_eyre::Result<()> {
+    ) -> color_eyre::Result<Option<String>> {
         info!("Starting application run loop");
 
         debug!("get offline state");
@@ -237,19 +265,27 @@ impl App {
             .await
             .is_offline();
 
+        // replay operations left over from a previous session
+        self.update_pending_count().await;
+        if self.pending_count > 0 && !self.is_offline {
+            info!("replaying {} pending operations", self.pending_count);
+            self.news_flash_utils.replay_pending_operations();
+        }
+
         // set days before articles get removed
         info!(
             "setting amount of days before articles are removed to {}",
             self.config.keep_articles_days
         );
-        self.news_flash_utils
-            .news_flash_lock
-            .read()
-            .await
-            .set_keep_articles_duration(Some(TimeDelta::days(
-                self.config.keep_articles_days as i64,
-            )))
-            .await?;
+        for account in self.news_flash_utils.accounts().iter() {
+            account
+                .news_flash()
+                .await
+                .set_keep_articles_duration(Some(TimeDelta::days(
+                    self.config.keep_articles_days as i64,
+                )))
+                .await?;
+        }
 
         debug!("Sending ApplicationStarted command");
         self.message_sender
@@ -269,14 +305,15 @@ impl App {
             .send(Message::Batch(self.config.startup_commands.to_vec()))?;
 
         info!("Starting command processing loop");
-        self.process_commands(&mut message_receiver, terminal)
+        let next_profile = self
+            .process_commands(&mut message_receiver, terminal)
             .await?;
 
         // closing receiver
         drop(message_receiver);
 
         info!("Application run loop completed");
-        Ok(())
+        Ok(next_profile)
     }
 
     fn tick(&mut self) -> bool {
@@ -285,14 +322,15 @@ impl App {
             self.async_operation_throbber.calc_next();
             return true;
         }
-        false
+        // progress of the downloads
+        self.news_flash_utils.downloads().status().is_some()
     }
 
     async fn process_commands(
         mut self,
         rx: &mut UnboundedReceiver<Message>,
         mut terminal: DefaultTerminal,
-    ) -> color_eyre::Result<()> {
+    ) -> color_eyre::Result<Option<String>> {
         let mut render_interval =
             tokio::time::interval(Duration::from_millis(1000 / self.config.refresh_fps));
         debug!(
@@ -335,11 +373,13 @@ impl App {
                         && !self.command_input.is_active()
                         && !self.command_confirm.is_active()
                         && !self.help_popup.is_modal().unwrap_or(false)
+                        && !self.feed_add_popup.is_visible()
                         {
                             self.input_command_generator.process_command(&message).await?;
                         }
 
                         self.batch_processor.process_command(&message).await?;
+                        self.playback_queue.process_command(&message).await?;
                         self.process_command(&message).await?;
                         self.feed_list.process_command(&message).await?;
                         self.articles_list.process_command(&message).await?;
@@ -347,6 +387,7 @@ impl App {
                         self.command_input.process_command(&message).await?;
                         self.command_confirm.process_command(&message).await?;
                         self.help_popup.process_command(&message).await?;
+                        self.feed_add_popup.process_command(&message).await?;
 
                         if redraw {
                             terminal.draw(|frame| frame.render_widget(&mut self, frame.area()))?;
@@ -362,7 +403,7 @@ impl App {
         }
 
         info!("Message processing loop ended");
-        Ok(())
+        Ok(self.next_profile)
     }
 
     fn switch_state(&mut self, next_state: AppState) -> color_eyre::eyre::Result<()> {
@@ -409,6 +450,377 @@ impl App {
         Ok(())
     }
 
+    fn switch_profile(&mut self, profile: Option<&str>) -> color_eyre::Result<()> {
+        let current_profile = self.config.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+
+        let Some(profile) = profile else {
+            let profiles = std::iter::once(DEFAULT_PROFILE)
+                .chain(self.config.profiles.keys().map(String::as_str))
+                .map(|profile| {
+                    if profile == current_profile {
+                        format!("*{profile}*")
+                    } else {
+                        profile.to_owned()
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            tooltip(
+                &self.message_sender,
+                &*format!("profiles: {profiles}"),
+                TooltipFlavor::Info,
+            )?;
+            return Ok(());
+        };
+
+        if profile == current_profile {
+            tooltip(
+                &self.message_sender,
+                &*format!("already using profile {profile}"),
+                TooltipFlavor::Warning,
+            )?;
+        } else if profile != DEFAULT_PROFILE && !self.config.profiles.contains_key(profile) {
+            tooltip(
+                &self.message_sender,
+                &*format!("unknown profile {profile}"),
+                TooltipFlavor::Error,
+            )?;
+        } else {
+            info!("switching from profile {current_profile} to {profile}");
+            self.next_profile = Some(profile.to_owned());
+            // quitting lets all components store their state before the profile is switched
+            self.message_sender
+                .send(Message::Command(Command::ApplicationQuit))?;
+        }
+
+        Ok(())
+    }
+
+    async fn switch_offline_mode(&mut self, offline_mode: Option<bool>) -> color_eyre::Result<()> {
+        let offline_mode = offline_mode.unwrap_or(!self.news_flash_utils.is_offline_mode());
+        self.news_flash_utils.set_offline_mode(offline_mode);
+        info!("offline mode: {offline_mode}");
+
+        if offline_mode {
+            tooltip(
+                &self.message_sender,
+                "Offline mode on, no reachability checks and automatic syncs",
+                TooltipFlavor::Info,
+            )?;
+            if !self.is_offline {
+                self.news_flash_utils.set_offline(true);
+            }
+        } else {
+            tooltip(
+                &self.message_sender,
+                "Offline mode off, trying to get online...",
+                TooltipFlavor::Info,
+            )?;
+            if self.is_offline {
+                // if there is no connection, the connectivity monitor takes us offline again
+                self.news_flash_utils.rebuild_client().await?;
+                self.news_flash_utils.set_offline(false);
+            }
+        }
+
+        Ok(())
+    }
+
+    // shows the outputs, errors and stderr of a pipe over multiple articles; if there is nothing
+    // to show, a tooltip is enough
+    fn show_pipe_results(&self, results: &[PipeResult]) -> color_eyre::Result<()> {
+        if results.iter().all(|result| {
+            result.markdown.is_none() && result.error.is_none() && result.status.success()
+        }) {
+            tooltip(
+                &self.message_sender,
+                &*format!("piped {} article(s)", results.len()),
+                TooltipFlavor::Info,
+            )?;
+            return Ok(());
+        }
+
+        let mut lines: Vec<Line<'static>> = Vec::new();
+        for result in results {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::styled(
+                result.title.to_owned(),
+                self.config.theme.header(),
+            ));
+
+            lines.extend(
+                result
+                    .markdown
+                    .iter()
+                    .flat_map(|markdown| markdown.lines())
+                    .map(|line| Line::styled(line.to_owned(), self.config.theme.paragraph())),
+            );
+
+            if !result.status.success() {
+                lines.push(Line::styled(
+                    format!(
+                        "Process returned with error ({})",
+                        result
+                            .status
+                            .code()
+                            .map(|code| code.to_string())
+                            .unwrap_or_else(|| "unknown error code".to_string())
+                    ),
+                    self.config.theme.inactive(),
+                ));
+            }
+
+            lines.extend(
+                result
+                    .error
+                    .iter()
+                    .flat_map(|error| error.lines())
+                    .map(|line| Line::styled(line.to_owned(), self.config.theme.inactive())),
+            );
+        }
+
+        self.message_sender
+            .send(Message::Event(Event::ShowModalHelpPopup(
+                "Pipe Output".to_owned(),
+                Text::from(lines),
+            )))?;
+
+        Ok(())
+    }
+
+    async fn show_reading_stats(&self) -> color_eyre::Result<()> {
+        let accounts = self.news_flash_utils.accounts();
+        let (feeds, feed_mappings) = accounts.get_feeds().await?;
+        let (categories, _) = accounts.get_categories().await?;
+        let (tags, _) = accounts.get_tags().await?;
+        let articles = accounts.get_articles(ArticleFilter::default()).await?;
+
+        let stats = ReadingStats::compute(
+            &self.news_flash_utils.reading_events().load(),
+            &articles,
+            &feeds,
+            &feed_mappings,
+            &categories,
+            &tags,
+        );
+
+        let mut lines: Vec<Line<'static>> = Vec::new();
+        for (title, rows) in stats.sections() {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::styled(title, self.config.theme.header()));
+
+            let label_width = rows
+                .iter()
+                .map(|(label, _)| label.chars().count())
+                .max()
+                .unwrap_or_default();
+            lines.extend(rows.into_iter().map(|(label, value)| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{label:<label_width$}  "),
+                        self.config.theme.inactive(),
+                    ),
+                    Span::styled(value, self.config.theme.paragraph()),
+                ])
+            }));
+        }
+
+        self.message_sender
+            .send(Message::Event(Event::ShowModalHelpPopup(
+                "Reading Statistics".to_owned(),
+                Text::from(lines),
+            )))?;
+
+        Ok(())
+    }
+
+    async fn show_feed_health(&self) -> color_eyre::Result<()> {
+        let (feeds, _) = self.news_flash_utils.accounts().get_feeds().await?;
+        let feed_health = self.news_flash_utils.feed_health().read().await;
+        let report = feed_health.report(&feeds);
+
+        let mut lines: Vec<Line<'static>> = Vec::new();
+        for (feed, health) in report.iter() {
+            let (icon, style) = if health.has_error() {
+                (
+                    self.config.icon_set.feed_error_icon(),
+                    self.config.theme.tooltip_error(),
+                )
+            } else if health.is_moved() {
+                (
+                    self.config.icon_set.warning_icon(),
+                    self.config.theme.tooltip_warning(),
+                )
+            } else {
+                (self.config.icon_set.feed_icon(), self.config.theme.feed())
+            };
+
+            let status = format!(
+                "last success: {}, {}",
+                health.last_success_text(&self.config.date_format),
+                health.status()
+            );
+
+            lines.push(Line::from(vec![
+                Span::styled(format!("{icon} "), style),
+                Span::styled(feed.label.to_owned(), self.config.theme.header()),
+                Span::styled(format!("  {status}"), self.config.theme.inactive()),
+            ]));
+
+            for detail in health.details() {
+                lines.push(Line::styled(
+                    format!("    {detail}"),
+                    self.config.theme.paragraph(),
+                ));
+            }
+        }
+
+        let with_error = report
+            .iter()
+            .filter(|(_, health)| health.has_error())
+            .count();
+        let moved = report
+            .iter()
+            .filter(|(_, health)| health.is_moved())
+            .count();
+
+        self.message_sender
+            .send(Message::Event(Event::ShowHelpPopup(
+                format!("Feed Health ({with_error} with errors, {moved} moved)"),
+                Text::from(lines),
+            )))?;
+
+        Ok(())
+    }
+
+    async fn update_pending_count(&mut self) {
+        self.pending_count = self
+            .news_flash_utils
+            .pending_operations()
+            .read()
+            .await
+            .len();
+    }
+
+    async fn show_pending_operations(&self) -> color_eyre::Result<()> {
+        let pending_operations = self.news_flash_utils.pending_operations().read().await;
+        if pending_operations.is_empty() {
+            return tooltip(
+                &self.message_sender,
+                "no pending operations",
+                TooltipFlavor::Info,
+            );
+        }
+
+        let tag_labels = self
+            .news_flash_utils
+            .accounts()
+            .get_tags()
+            .await?
+            .0
+            .into_iter()
+            .map(|tag| (tag.tag_id, tag.label))
+            .collect::<HashMap<TagID, String>>();
+
+        let lines = pending_operations
+            .operations()
+            .iter()
+            .map(|operation| {
+                let queued = chrono::DateTime::from_timestamp(operation.queued, 0)
+                    .map(|queued| {
+                        queued
+                            .with_timezone(&chrono::Local)
+                            .format(&self.config.date_format)
+                            .to_string()
+                    })
+                    .unwrap_or_default();
+                let tag_label = match &operation.action {
+                    PendingAction::AddTag(_, tag_id) | PendingAction::RemoveTag(_, tag_id) => {
+                        format!(
+                            " #{}",
+                            tag_labels
+                                .get(tag_id)
+                                .map_or(tag_id.as_str(), String::as_str)
+                        )
+                    }
+                    _ => String::new(),
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:>3} ", operation.id),
+                        self.config.theme.inactive(),
+                    ),
+                    Span::styled(format!("{queued} "), self.config.theme.inactive()),
+                    Span::styled(
+                        format!("{}{tag_label}", operation.action),
+                        self.config.theme.paragraph(),
+                    ),
+                ])
+            })
+            .collect::<Vec<Line<'static>>>();
+
+        // selecting an operation drops it (after confirmation)
+        let commands = pending_operations
+            .operations()
+            .iter()
+            .map(|operation| {
+                Command::CommandConfirm(Box::new(Command::PendingDrop(Some(operation.id))))
+            })
+            .collect();
+
+        self.message_sender
+            .send(Message::Event(Event::ShowSelectionPopup(
+                format!("Pending Operations ({})", pending_operations.len()),
+                Text::from(lines),
+                commands,
+            )))?;
+
+        Ok(())
+    }
+
+    async fn drop_pending_operations(&mut self, id: Option<u64>) -> color_eyre::Result<()> {
+        let dropped = {
+            let mut pending_operations = self.news_flash_utils.pending_operations().write().await;
+            match id {
+                Some(id) => pending_operations.remove(&[id])?,
+                None => pending_operations.clear()?,
+            }
+        };
+        self.update_pending_count().await;
+
+        match (id, dropped) {
+            (Some(id), 0) => tooltip(
+                &self.message_sender,
+                &*format!("no pending operation {id}"),
+                TooltipFlavor::Warning,
+            ),
+            _ => tooltip(
+                &self.message_sender,
+                &*format!("dropped {dropped} pending operations"),
+                TooltipFlavor::Info,
+            ),
+        }
+    }
+
+    fn copy_to_clipboard(&self, text: &str) -> color_eyre::Result<()> {
+        match copy_to_clipboard(text, self.config.clipboard_backend) {
+            Ok(()) => tooltip(
+                &self.message_sender,
+                &*format!("copied {text}"),
+                TooltipFlavor::Info,
+            ),
+            Err(error) => tooltip(
+                &self.message_sender,
+                &*format!("unable to copy to clipboard: {error}"),
+                TooltipFlavor::Error,
+            ),
+        }
+    }
+
     fn logout(&self) {
         self.news_flash_utils.logout();
     }
@@ -421,6 +833,7 @@ impl App {
         if self.command_input.is_active()
             || self.command_confirm.is_active()
             || self.help_popup.is_modal().unwrap_or(false)
+            || self.feed_add_popup.is_visible()
         {
             return Ok(());
         }
@@ -530,12 +943,13 @@ impl App {
             return Ok(());
         }
 
-        let news_flash = self.news_flash_utils.news_flash_lock.read().await;
-
-        let output = self
-            .config
-            .notify_after_sync_stats_format
-            .gen_output(&news_flash, new_articles)?;
+        // the ids of the new articles are qualified, so the feeds of all accounts are needed
+        let accounts = self.news_flash_utils.accounts();
+        let output = self.config.notify_after_sync_stats_format.gen_output(
+            accounts.get_feeds().await?,
+            accounts.get_categories().await?,
+            new_articles,
+        );
 
         let Some((summary, body)) = output.split_once("\n") else {
             tooltip(
@@ -618,6 +1032,13 @@ impl MessageReceiver for App {
         use Command::*;
         use Event::*;
         let mut needs_redraw = true;
+
+        if let Message::Event(event) = message
+            && event.caused_model_update()
+        {
+            self.update_pending_count().await;
+        }
+
         match message {
             Message::Command(Logout(confirmation)) => {
                 if confirmation.as_str() != "NOW" {
@@ -636,6 +1057,10 @@ impl MessageReceiver for App {
                 self.is_running = false;
             }
 
+            Message::Command(SwitchProfile(profile)) => {
+                self.switch_profile(profile.as_deref())?;
+            }
+
             Message::Command(ImportOpml(path_str)) => {
                 self.import_opml(path_str).await?;
             }
@@ -666,6 +1091,118 @@ impl MessageReceiver for App {
                     .send(Message::Command(Command::ApplicationQuit))?;
             }
 
+            Message::Command(OfflineMode(offline_mode)) => {
+                self.switch_offline_mode(*offline_mode).await?;
+            }
+
+            Message::Command(FeedHealth(check)) => {
+                if *check {
+                    tooltip(&self.message_sender, "checking feeds", TooltipFlavor::Info)?;
+                    self.news_flash_utils.check_feed_health();
+                } else {
+                    self.show_feed_health().await?;
+                }
+            }
+
+            Message::Event(AsyncFeedHealthCheckFinished) => {
+                self.show_feed_health().await?;
+            }
+
+            Message::Event(AsyncFeedFetchFinished(feed_id, _)) => {
+                self.news_flash_utils.update_feed_health_of(feed_id).await?;
+            }
+
+            Message::Command(ShowStats) => {
+                self.show_reading_stats().await?;
+            }
+
+            Message::Event(AsyncShareWebhookFinished(target, 0, status)) if status.is_success() => {
+                tooltip(
+                    &self.message_sender,
+                    &*format!("nothing shared with {target}"),
+                    TooltipFlavor::Warning,
+                )?;
+            }
+
+            Message::Event(AsyncShareWebhookFinished(target, shared, status)) => {
+                if status.is_success() {
+                    tooltip(
+                        &self.message_sender,
+                        &*format!("shared {shared} articles with {target}: {status}"),
+                        TooltipFlavor::Info,
+                    )?;
+                } else {
+                    tooltip(
+                        &self.message_sender,
+                        &*format!("unable to share with {target} ({shared} shared): {status}"),
+                        TooltipFlavor::Error,
+                    )?;
+                }
+            }
+
+            Message::Event(AsyncDownloadsQueued(0)) => {
+                tooltip(
+                    &self.message_sender,
+                    "no enclosures to download",
+                    TooltipFlavor::Warning,
+                )?;
+            }
+
+            Message::Event(AsyncDownloadsQueued(queued)) => {
+                tooltip(
+                    &self.message_sender,
+                    &*format!("downloading {queued} enclosures"),
+                    TooltipFlavor::Info,
+                )?;
+            }
+
+            Message::Event(AsyncDownloadFinished(_, path)) => {
+                tooltip(
+                    &self.message_sender,
+                    &*format!("downloaded {}", path.display()),
+                    TooltipFlavor::Info,
+                )?;
+            }
+
+            Message::Event(AsyncDownloadFailed(url, error)) => {
+                tooltip(
+                    &self.message_sender,
+                    &*format!("unable to download {url}: {error}"),
+                    TooltipFlavor::Error,
+                )?;
+            }
+
+            Message::Event(AsyncPipeArticlesFinished(results)) => {
+                self.show_pipe_results(results)?;
+            }
+
+            Message::Command(ClipboardCopy(text)) => {
+                self.copy_to_clipboard(text)?;
+            }
+
+            Message::Command(PendingShow) => {
+                self.show_pending_operations().await?;
+            }
+
+            Message::Command(PendingDrop(id)) => {
+                self.drop_pending_operations(*id).await?;
+            }
+
+            Message::Event(AsyncReplayPendingFinished(replayed, skipped, failed)) => {
+                let message = format!(
+                    "replayed {replayed} pending operations ({skipped} already applied, {failed} failed)"
+                );
+                tooltip(
+                    &self.message_sender,
+                    &*message,
+                    if *failed > 0 {
+                        TooltipFlavor::Warning
+                    } else {
+                        TooltipFlavor::Info
+                    },
+                )?;
+            }
+
             Message::Command(ExportOpml(path_str)) => {
                 self.export_opml(path_str).await?;
             }
@@ -798,7 +1335,7 @@ impl MessageReceiver for App {
             Message::Event(Event::ConnectionAvailable) => {
                 let news_flash = self.news_flash_utils.news_flash_lock.read().await;
 
-                if news_flash.is_offline() {
+                if news_flash.is_offline() && !self.news_flash_utils.is_offline_mode() {
                     tooltip(
                         &self.message_sender,
                         "Trying to get online...",
@@ -837,6 +1374,11 @@ impl MessageReceiver for App {
 
                 if !offline {
                     tooltip(&self.message_sender, "Online again", TooltipFlavor::Info)?;
+
+                    if self.pending_count > 0 {
+                        info!("replaying {} pending operations", self.pending_count);
+                        self.news_flash_utils.replay_pending_operations();
+                    }
                 }
             }
 
@@ -850,6 +1392,8 @@ impl MessageReceiver for App {
             }
 
             Message::Event(Event::AsyncSyncFinished(new_articles)) => {
+                self.news_flash_utils.update_feed_health().await?;
+
                 info!(
                     "scheduling after sync commands: {:?}",
                     self.config.after_sync_commands
@@ -0,0 +1,551 @@
+use std::{io::Write, path::Path, process::Stdio, sync::Arc, time::Duration};
+
+use log::{debug, info, warn};
+use news_flash::models::{Article, ArticleID, Enclosure, Feed, Read};
+use serde_json::json;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::Child,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+};
+
+use crate::prelude::*;
+
+// playlist handed to players which are not controlled via IPC
+const PLAYLIST_FILE: &str = "queue.m3u";
+
+// the player needs a moment to create its IPC socket
+const CONNECT_ATTEMPTS: u32 = 50;
+const CONNECT_INTERVAL_MILLIS: u64 = 100;
+
+#[derive(Clone, Debug)]
+pub struct QueueEntry {
+    pub article_id: ArticleID,
+    pub title: String,
+    pub feed: String,
+    // the downloaded file if available, the URL of the enclosure otherwise
+    pub location: String,
+    pub enclosure_type: EnclosureType,
+}
+
+impl QueueEntry {
+    // the default audio or video enclosure of the article
+    pub fn from_article(
+        article: &Article,
+        feed: Option<&Feed>,
+        enclosures: &[Enclosure],
+        downloads: &Downloads,
+    ) -> Option<Self> {
+        let playable = enclosures
+            .iter()
+            .filter(|enclosure| EnclosureType::from(*enclosure) != EnclosureType::Image)
+            .cloned()
+            .collect::<Vec<Enclosure>>();
+        let enclosure = default_enclosure(&playable, None)?;
+
+        let url = enclosure.url.to_string();
+        let location = downloads
+            .files_of(&article.article_id)
+            .into_iter()
+            .find(|file| file.url == url)
+            .map(|file| file.path.to_string_lossy().to_string())
+            .unwrap_or(url);
+
+        Some(Self {
+            article_id: article.article_id.to_owned(),
+            title: html_sanitize(article.title.as_deref().unwrap_or("untitled")),
+            feed: feed
+                .map(|feed| html_sanitize(&feed.label))
+                .unwrap_or_default(),
+            location,
+            enclosure_type: enclosure.into(),
+        })
+    }
+}
+
+#[cfg(unix)]
+type IpcReader = tokio::net::unix::OwnedReadHalf;
+#[cfg(unix)]
+type IpcWriter = tokio::net::unix::OwnedWriteHalf;
+#[cfg(not(unix))]
+type IpcReader = tokio::io::Empty;
+#[cfg(not(unix))]
+type IpcWriter = tokio::io::Sink;
+
+// player controlled via mpv's JSON IPC; the commands are forwarded by the task which connects to
+// the player (see `PlaybackQueue::start_player`)
+struct Player {
+    commands: UnboundedSender<serde_json::Value>,
+    // mpv numbers the entries of its playlist in the order they are added, starting with 1
+    next_entry_id: u64,
+}
+
+impl Player {
+    fn send(&self, command: serde_json::Value) -> color_eyre::Result<()> {
+        self.commands
+            .send(command)
+            .map_err(|_| color_eyre::eyre::eyre!("player is not running"))
+    }
+}
+
+struct PlayerEntry {
+    entry: QueueEntry,
+    entry_id: u64,
+    finished: bool,
+}
+
+// queue of audio and video enclosures played by an external player; with IPC the entries of the
+// queue mirror the playlist of the player
+pub struct PlaybackQueue {
+    config: Arc<Config>,
+    news_flash_utils: Arc<NewsFlashUtils>,
+    message_sender: UnboundedSender<Message>,
+    entries: Vec<PlayerEntry>,
+    playing: Option<u64>,
+    player: Option<Player>,
+}
+
+impl PlaybackQueue {
+    pub fn new(
+        config: Arc<Config>,
+        news_flash_utils: Arc<NewsFlashUtils>,
+        message_sender: UnboundedSender<Message>,
+    ) -> Self {
+        Self {
+            config,
+            news_flash_utils,
+            message_sender,
+            entries: Default::default(),
+            playing: None,
+            player: None,
+        }
+    }
+
+    fn uses_ipc(&self) -> bool {
+        self.config.player_command.contains("{socket}")
+    }
+
+    fn append(&mut self, entries: &[QueueEntry]) -> color_eyre::Result<()> {
+        let mut entries = entries
+            .iter()
+            .filter(|entry| {
+                !self
+                    .entries
+                    .iter()
+                    .any(|queued| !queued.finished && queued.entry.location == entry.location)
+            })
+            .cloned()
+            .collect::<Vec<QueueEntry>>();
+
+        if entries.is_empty() {
+            return tooltip(
+                &self.message_sender,
+                "no audio or video enclosures to queue",
+                TooltipFlavor::Warning,
+            );
+        }
+        let queued = entries.len();
+
+        if !self.uses_ipc() {
+            self.hand_over_playlist(&entries)?;
+            return tooltip(
+                &self.message_sender,
+                &*format!("handed {queued} enclosures to player"),
+                TooltipFlavor::Info,
+            );
+        }
+
+        if self.player.is_none() {
+            self.start_player()?;
+            // the entries which were not played by the last player are played first
+            entries.splice(
+                0..0,
+                self.entries
+                    .drain(..)
+                    .filter(|queued| !queued.finished)
+                    .map(|queued| queued.entry),
+            );
+        }
+
+        let Some(player) = self.player.as_mut() else {
+            return Ok(());
+        };
+        for entry in entries {
+            player.send(json!({ "command": ["loadfile", entry.location, "append-play"] }))?;
+            self.entries.push(PlayerEntry {
+                entry,
+                entry_id: player.next_entry_id,
+                finished: false,
+            });
+            player.next_entry_id += 1;
+        }
+
+        tooltip(
+            &self.message_sender,
+            &*format!("queued {queued} enclosures"),
+            TooltipFlavor::Info,
+        )
+    }
+
+    // starts the player; connecting to its IPC socket takes a moment and is done in the
+    // background, the commands sent in the meantime are forwarded once the player is connected
+    fn start_player(&mut self) -> color_eyre::Result<()> {
+        let socket = std::env::temp_dir().join(format!("eilmeldung-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+
+        let (cmd, args) = prepare_command(
+            &self
+                .config
+                .player_command
+                .replace("{socket}", &socket.to_string_lossy()),
+        )?;
+        info!("starting player {cmd}");
+        // the player is stopped if it cannot be controlled anymore
+        let mut child = tokio::process::Command::new(&cmd)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let (command_sender, mut command_receiver) = unbounded_channel::<serde_json::Value>();
+        let message_sender = self.message_sender.clone();
+        tokio::spawn(async move {
+            let (reader, mut writer) = match connect(&socket, &mut child).await {
+                Ok(connection) => connection,
+                Err(err) => {
+                    let _ =
+                        message_sender.send(Message::Event(Event::PlayerFailed(err.to_string())));
+                    return;
+                }
+            };
+            read_player_events(reader, message_sender.clone());
+
+            // until the queue drops the player
+            while let Some(command) = command_receiver.recv().await {
+                debug!("sending to player: {command}");
+                if let Err(err) = writer.write_all(format!("{command}\n").as_bytes()).await {
+                    let _ =
+                        message_sender.send(Message::Event(Event::PlayerFailed(err.to_string())));
+                    return;
+                }
+            }
+        });
+
+        self.player = Some(Player {
+            commands: command_sender,
+            next_entry_id: 1,
+        });
+        Ok(())
+    }
+
+    fn hand_over_playlist(&self, entries: &[QueueEntry]) -> color_eyre::Result<()> {
+        let path = self.news_flash_utils.state_dir().join(PLAYLIST_FILE);
+        let mut playlist = std::fs::File::create(&path)?;
+        writeln!(playlist, "#EXTM3U")?;
+        for entry in entries {
+            writeln!(playlist, "#EXTINF:-1,{} - {}", entry.feed, entry.title)?;
+            writeln!(playlist, "{}", entry.location)?;
+        }
+
+        let (cmd, args) = prepare_command(
+            &self
+                .config
+                .player_command
+                .replace("{playlist}", &path.to_string_lossy()),
+        )?;
+        std::process::Command::new(&cmd)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        Ok(())
+    }
+
+    // removes the entry at the (1-based) position; removing the playing entry skips it
+    fn remove(&mut self, position: usize) -> color_eyre::Result<()> {
+        let Some(index) = position
+            .checked_sub(1)
+            .filter(|index| *index < self.entries.len())
+        else {
+            return tooltip(
+                &self.message_sender,
+                &*format!("no entry at position {position} in the playback queue"),
+                TooltipFlavor::Error,
+            );
+        };
+
+        if let Some(player) = self.player.as_ref() {
+            player.send(json!({ "command": ["playlist-remove", index] }))?;
+        }
+        let removed = self.entries.remove(index);
+
+        tooltip(
+            &self.message_sender,
+            &*format!("removed {} from the playback queue", removed.entry.title),
+            TooltipFlavor::Info,
+        )
+    }
+
+    fn show_queue(&self) -> color_eyre::Result<()> {
+        if self.entries.is_empty() {
+            return tooltip(
+                &self.message_sender,
+                "playback queue is empty",
+                TooltipFlavor::Info,
+            );
+        }
+
+        let (playing_index, next_index) = playing_and_next(&self.entries, self.playing);
+
+        let lines = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, queued)| {
+                let (state, style) = if Some(index) == playing_index {
+                    (
+                        " now playing",
+                        self.config
+                            .theme
+                            .highlighted(&self.config.theme.paragraph()),
+                    )
+                } else if queued.finished {
+                    (" played", self.config.theme.inactive())
+                } else if Some(index) == next_index {
+                    (" next", self.config.theme.paragraph())
+                } else {
+                    ("", self.config.theme.paragraph())
+                };
+                let icon = match queued.entry.enclosure_type {
+                    EnclosureType::Video => self.config.icon_set.enclosure_video_icon(),
+                    _ => self.config.icon_set.enclosure_audio_icon(),
+                };
+
+                Line::from(vec![
+                    Span::styled(format!("{:>3} ", index + 1), self.config.theme.inactive()),
+                    Span::styled(
+                        format!("{icon} {}: {}", queued.entry.feed, queued.entry.title),
+                        style,
+                    ),
+                    Span::styled(state, self.config.theme.inactive()),
+                ])
+            })
+            .collect::<Vec<Line<'static>>>();
+
+        // selecting an entry removes it from the queue
+        let commands = (1..=self.entries.len()).map(Command::QueueRemove).collect();
+
+        self.message_sender
+            .send(Message::Event(Event::ShowSelectionPopup(
+                format!("Playback Queue ({})", self.entries.len()),
+                Text::from(lines),
+                commands,
+            )))?;
+        Ok(())
+    }
+
+    fn on_playback_finished(&mut self, entry_id: u64) -> color_eyre::Result<()> {
+        let Some(queued) = self
+            .entries
+            .iter_mut()
+            .find(|queued| queued.entry_id == entry_id)
+        else {
+            return Ok(());
+        };
+        queued.finished = true;
+
+        // completely played articles are read
+        self.news_flash_utils.set_article_status(
+            vec![queued.entry.article_id.to_owned()],
+            Read::Read,
+            true,
+        );
+        Ok(())
+    }
+
+    fn on_player_exited(&mut self) {
+        info!("player exited");
+        self.player = None;
+        self.playing = None;
+        self.entries.retain(|queued| !queued.finished);
+    }
+}
+
+#[cfg(unix)]
+async fn connect(socket: &Path, child: &mut Child) -> color_eyre::Result<(IpcReader, IpcWriter)> {
+    for _ in 0..CONNECT_ATTEMPTS {
+        if let Some(status) = child.try_wait()? {
+            return Err(color_eyre::eyre::eyre!(
+                "player exited before accepting commands ({status})"
+            ));
+        }
+        match tokio::net::UnixStream::connect(socket).await {
+            Ok(stream) => return Ok(stream.into_split()),
+            Err(err) => debug!("player not ready: {err}"),
+        }
+        tokio::time::sleep(Duration::from_millis(CONNECT_INTERVAL_MILLIS)).await;
+    }
+    Err(color_eyre::eyre::eyre!(
+        "unable to connect to player via {socket:?}"
+    ))
+}
+
+#[cfg(not(unix))]
+async fn connect(_socket: &Path, _child: &mut Child) -> color_eyre::Result<(IpcReader, IpcWriter)> {
+    Err(color_eyre::eyre::eyre!(
+        "controlling the player via IPC is only supported on Unix"
+    ))
+}
+
+// positions of the playing entry and of the entry played next
+fn playing_and_next(
+    entries: &[PlayerEntry],
+    playing: Option<u64>,
+) -> (Option<usize>, Option<usize>) {
+    let playing_index = entries
+        .iter()
+        .position(|queued| Some(queued.entry_id) == playing);
+    let next_index = entries.iter().enumerate().position(|(index, queued)| {
+        !queued.finished && playing_index.is_none_or(|playing| index > playing)
+    });
+    (playing_index, next_index)
+}
+
+// the start and the (complete) end of a playback as reported by mpv
+fn parse_player_event(line: &str) -> Option<Event> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+        warn!("unable to parse player message: {line}");
+        return None;
+    };
+    let entry_id = value["playlist_entry_id"].as_u64();
+    match (value["event"].as_str(), value["reason"].as_str(), entry_id) {
+        (Some("start-file"), _, Some(entry_id)) => Some(Event::PlaybackStarted(entry_id)),
+        (Some("end-file"), Some("eof"), Some(entry_id)) => Some(Event::PlaybackFinished(entry_id)),
+        _ => None,
+    }
+}
+
+// forwards the start and the end of playbacks as events until the player exits
+fn read_player_events(reader: IpcReader, message_sender: UnboundedSender<Message>) {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let Some(event) = parse_player_event(&line) else {
+                continue;
+            };
+            if message_sender.send(Message::Event(event)).is_err() {
+                return;
+            }
+        }
+        let _ = message_sender.send(Message::Event(Event::PlayerExited));
+    });
+}
+
+impl MessageReceiver for PlaybackQueue {
+    async fn process_command(&mut self, message: &Message) -> color_eyre::Result<()> {
+        use Command as C;
+        use Event as E;
+        match message {
+            Message::Command(C::Queue(None)) => self.show_queue()?,
+            Message::Command(C::QueueRemove(position)) => self.remove(*position)?,
+            Message::Event(E::PlaybackQueueAppend(entries)) => {
+                if let Err(err) = self.append(entries) {
+                    self.player = None;
+                    tooltip(
+                        &self.message_sender,
+                        &*format!("unable to queue enclosures: {err}"),
+                        TooltipFlavor::Error,
+                    )?;
+                }
+            }
+            Message::Event(E::PlaybackStarted(entry_id)) => {
+                self.playing = Some(*entry_id);
+                if let Some(queued) = self
+                    .entries
+                    .iter()
+                    .find(|queued| queued.entry_id == *entry_id)
+                {
+                    tooltip(
+                        &self.message_sender,
+                        &*format!("playing {}", queued.entry.title),
+                        TooltipFlavor::Info,
+                    )?;
+                }
+            }
+            Message::Event(E::PlaybackFinished(entry_id)) => {
+                self.on_playback_finished(*entry_id)?
+            }
+            Message::Event(E::PlayerExited) => self.on_player_exited(),
+            Message::Event(E::PlayerFailed(err)) => {
+                self.on_player_exited();
+                tooltip(
+                    &self.message_sender,
+                    &*format!("unable to control player: {err}"),
+                    TooltipFlavor::Error,
+                )?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn player_entry(entry_id: u64, finished: bool) -> PlayerEntry {
+        PlayerEntry {
+            entry: QueueEntry {
+                article_id: ArticleID::new(&entry_id.to_string()),
+                title: format!("Episode {entry_id}"),
+                feed: "Podcast".to_owned(),
+                location: format!("https://example.com/{entry_id}.mp3"),
+                enclosure_type: EnclosureType::Audio,
+            },
+            entry_id,
+            finished,
+        }
+    }
+
+    #[test]
+    fn test_parse_player_event() {
+        assert!(matches!(
+            parse_player_event(r#"{"event":"start-file","playlist_entry_id":3}"#),
+            Some(Event::PlaybackStarted(3))
+        ));
+        assert!(matches!(
+            parse_player_event(r#"{"event":"end-file","reason":"eof","playlist_entry_id":3}"#),
+            Some(Event::PlaybackFinished(3))
+        ));
+        // skipped or stopped entries are not finished
+        assert!(
+            parse_player_event(r#"{"event":"end-file","reason":"stop","playlist_entry_id":3}"#)
+                .is_none()
+        );
+        assert!(parse_player_event(r#"{"event":"start-file"}"#).is_none());
+        assert!(parse_player_event(r#"{"data":null,"request_id":0,"error":"success"}"#).is_none());
+        assert!(parse_player_event("not json").is_none());
+    }
+
+    #[test]
+    fn test_playing_and_next() {
+        let entries = [
+            player_entry(1, true),
+            player_entry(2, false),
+            player_entry(3, false),
+            player_entry(4, false),
+        ];
+
+        assert_eq!(playing_and_next(&entries, Some(2)), (Some(1), Some(2)));
+        // nothing playing: the first unfinished entry is next
+        assert_eq!(playing_and_next(&entries, None), (None, Some(1)));
+        assert_eq!(playing_and_next(&entries, Some(4)), (Some(3), None));
+        // the playing entry has been removed from the queue
+        assert_eq!(playing_and_next(&entries, Some(7)), (None, Some(1)));
+        assert_eq!(playing_and_next(&[], None), (None, None));
+    }
+}
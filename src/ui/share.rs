@@ -0,0 +1,101 @@
+use crate::prelude::*;
+
+use tokio::sync::mpsc::UnboundedSender;
+
+// number of browser tabs opened by a share target without confirmation
+const MAX_BROWSER_TABS: usize = 10;
+
+// shares the items with the share target of the given name (article list, article content);
+// webhooks are called in the background and report the response when it arrives
+pub fn share_with_target(
+    config: &Config,
+    news_flash_utils: &NewsFlashUtils,
+    message_sender: &UnboundedSender<Message>,
+    options: ShareOptions,
+    target_str: &str,
+    action_scope: Option<&ActionScope>,
+    items: Vec<ShareItem>,
+) -> color_eyre::Result<()> {
+    let Some(target) = config
+        .share_targets
+        .iter()
+        .find(|target| target.as_ref() == target_str)
+    else {
+        tooltip(
+            message_sender,
+            &*format!("unknown share target {target_str}"),
+            TooltipFlavor::Error,
+        )?;
+        return Ok(());
+    };
+
+    if items.is_empty() {
+        tooltip(
+            message_sender,
+            "no articles with URL to share",
+            TooltipFlavor::Warning,
+        )?;
+        return Ok(());
+    }
+
+    let mode = options.mode(target);
+
+    // sharing many articles with a web service opens one tab for each of them
+    if target.opens_browser()
+        && mode == ShareMode::Each
+        && items.len() > MAX_BROWSER_TABS
+        && !options.confirmed
+    {
+        message_sender.send(Message::Command(Command::CommandConfirm(Box::new(
+            Command::ArticleShare(
+                ShareOptions {
+                    confirmed: true,
+                    ..options
+                },
+                target_str.to_owned(),
+                action_scope.cloned(),
+            ),
+        ))))?;
+        return tooltip(
+            message_sender,
+            &*format!("this opens {} browser tabs", items.len()),
+            TooltipFlavor::Warning,
+        );
+    }
+
+    if let ShareTarget::Webhook(webhook) = target {
+        let webhook_items = ShareTarget::items_for_mode(&items, mode);
+        if webhook_items.is_empty() {
+            return tooltip(
+                message_sender,
+                &*format!("nothing to share with {target}"),
+                TooltipFlavor::Warning,
+            );
+        }
+        news_flash_utils.share_to_webhook(webhook.to_owned(), webhook_items);
+        tooltip(
+            message_sender,
+            &*format!("sharing with {target}..."),
+            TooltipFlavor::Info,
+        )?;
+        return Ok(());
+    }
+
+    match target.share(&items, mode, config.clipboard_backend) {
+        Ok(()) if items.len() == 1 => tooltip(
+            message_sender,
+            &*format!("shared with {target}"),
+            TooltipFlavor::Info,
+        ),
+        Ok(()) => tooltip(
+            message_sender,
+            &*format!("shared {} articles with {target}", items.len()),
+            TooltipFlavor::Info,
+        ),
+        Err(error) => tooltip(
+            message_sender,
+            &*format!("unable to share with {target}: {error}"),
+            TooltipFlavor::Error,
+        ),
+    }
+}
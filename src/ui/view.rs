@@ -20,6 +20,10 @@ impl Widget for &mut App {
         if self.help_popup.is_visible() {
             self.help_popup.render(area, buf);
         }
+
+        if self.feed_add_popup.is_visible() {
+            self.feed_add_popup.render(area, buf);
+        }
     }
 }
 
@@ -50,16 +54,49 @@ impl App {
             let left_icon = self.config.icon_set.status_bar_left_icon();
             let right_icon = self.config.icon_set.status_bar_right_icon();
 
-            let [bottom_left, bottom_main, status, bottom_right] = Layout::default()
+            // operations made while offline which are not yet synced
+            let pending = if self.pending_count > 0 {
+                format!("{} pending ", self.pending_count)
+            } else {
+                String::new()
+            };
+            // enclosures which are downloaded in the background
+            let pending = match self.news_flash_utils.downloads().status() {
+                Some(status) => format!(
+                    "{} {}{}{} {pending}",
+                    self.config.icon_set.downloaded_icon(),
+                    status.running,
+                    if status.queued > 0 {
+                        format!("+{}", status.queued)
+                    } else {
+                        String::new()
+                    },
+                    status
+                        .percent()
+                        .map(|percent| format!(" {percent}%"))
+                        .unwrap_or_default(),
+                ),
+                None => pending,
+            };
+            let pending_width = pending.chars().count() as u16;
+
+            let [
+                bottom_left,
+                bottom_main,
+                bottom_pending,
+                status,
+                bottom_right,
+            ] = Layout::default()
                 .direction(Direction::Horizontal)
                 .flex(Flex::Center)
                 .constraints([
                     Constraint::Length(1),
-                    Constraint::Min(bottom.width.saturating_sub(3)),
+                    Constraint::Min(bottom.width.saturating_sub(3 + pending_width)),
+                    Constraint::Length(pending_width),
                     Constraint::Length(1),
                     Constraint::Length(1),
                 ])
-                .areas::<4>(bottom);
+                .areas::<5>(bottom);
 
             let status_span = if self.is_offline {
                 // when offline display offline icon
@@ -99,6 +136,7 @@ impl App {
                 },
             )
             .render(bottom_right, buf);
+            Span::styled(pending, tooltip_line.style).render(bottom_pending, buf);
             tooltip_line.render(bottom_main, buf);
             status_span.render(status, buf);
         }
@@ -8,9 +8,12 @@ use crate::prelude::*;
 pub mod prelude {
     pub use super::StderrRedirect;
     pub use super::html_sanitize;
+    pub use super::is_metered_connection;
     pub use super::lex_ordering;
     pub use super::patch_text_style;
+    pub use super::posts_per_week;
     pub use super::prepare_command;
+    pub use super::resolve_url;
     pub use super::to_bubble;
 }
 
@@ -108,6 +111,40 @@ pub fn prepare_command(command_line: &str) -> color_eyre::Result<(String, Vec<St
     Ok((first.to_string(), args.to_vec()))
 }
 
+// runs the user-supplied metered check (through the shell); exit code 0 means metered
+pub async fn is_metered_connection(command: &str) -> color_eyre::Result<bool> {
+    let status = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await?;
+
+    Ok(status.success())
+}
+
+// average number of posts per week between the oldest and the newest of the given dates
+pub fn posts_per_week(dates: &[chrono::DateTime<chrono::Utc>]) -> Option<f64> {
+    let oldest = dates.iter().min()?;
+    let newest = dates.iter().max()?;
+    let seconds = (*newest - *oldest).num_seconds();
+
+    if dates.len() < 2 || seconds <= 0 {
+        return None;
+    }
+
+    Some((dates.len() - 1) as f64 * 7.0 * 86400.0 / seconds as f64)
+}
+
+// resolves a (possibly relative) URL against the URL of the article; absolute URLs are kept
+pub fn resolve_url(base: Option<&url::Url>, url: &str) -> String {
+    base.and_then(|base| base.join(url).ok())
+        .map(|resolved| resolved.to_string())
+        .unwrap_or_else(|| url.to_owned())
+}
+
 impl Drop for StderrRedirect {
     fn drop(&mut self) {
         unsafe {
@@ -270,3 +307,57 @@ mod prepare_command_test {
         }
     }
 }
+
+#[cfg(test)]
+mod posts_per_week_test {
+    use chrono::{TimeDelta, Utc};
+
+    use super::posts_per_week;
+
+    #[test]
+    fn daily_posts() {
+        let now = Utc::now();
+        let dates = (0..8)
+            .map(|day| now - TimeDelta::days(day))
+            .collect::<Vec<_>>();
+
+        let posts_per_week = posts_per_week(&dates).unwrap();
+        assert!((posts_per_week - 7.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn too_few_dates() {
+        let now = Utc::now();
+        assert_eq!(posts_per_week(&[]), None);
+        assert_eq!(posts_per_week(&[now]), None);
+        assert_eq!(posts_per_week(&[now, now]), None);
+    }
+}
+
+#[cfg(test)]
+mod resolve_url_test {
+    use super::resolve_url;
+
+    #[test]
+    fn resolves_relative_urls() {
+        let base = url::Url::parse("https://example.com/blog/post.html").unwrap();
+
+        assert_eq!(
+            resolve_url(Some(&base), "images/a.png"),
+            "https://example.com/blog/images/a.png"
+        );
+        assert_eq!(
+            resolve_url(Some(&base), "/a.png"),
+            "https://example.com/a.png"
+        );
+        assert_eq!(
+            resolve_url(Some(&base), "//cdn.example.org/a.png"),
+            "https://cdn.example.org/a.png"
+        );
+        assert_eq!(
+            resolve_url(Some(&base), "https://other.org/a.png"),
+            "https://other.org/a.png"
+        );
+        assert_eq!(resolve_url(None, "images/a.png"), "images/a.png");
+    }
+}